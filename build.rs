@@ -0,0 +1,526 @@
+//! Converts images and badge specs into Rust at build time
+//!
+//! * `gfx/images.toml` lists images to resize, dither and pack to 1 bit per
+//!   pixel, they become `ImageRaw<BinaryColor>` constants in `images`
+//! * `badges/*.toml` become `BadgeSpec` constants in `badge::badges`, with
+//!   their images packed the same way
+//! * `gfx/fonts.toml` lists BDF fonts to crop and pack into
+//!   `ProportionalFont` constants in `fonts`
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+use image::imageops::FilterType;
+use image::GrayImage;
+use serde::Deserialize;
+
+#[path = "src/graphics_extensions/dither/ditherer.rs"]
+mod ditherer;
+
+use ditherer::{Dither, Ditherer};
+
+struct Build {
+    manifest_dir: PathBuf,
+    out_dir: PathBuf,
+}
+
+/// Widest image that can be dithered by error diffusion or patterns
+const DITHER_WIDTH: usize = 1024;
+
+/// [`Dither`] as written in `images.toml`
+#[derive(Deserialize)]
+#[serde(remote = "Dither", rename_all = "kebab-case")]
+enum DitherToml {
+    Threshold,
+    FloydSteinberg,
+    Atkinson,
+    Bayer4,
+    Bayer8,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ImageToml {
+    source: String,
+    /// Box the image is resized to fit in, keeping the aspect ratio
+    width: Option<u32>,
+    height: Option<u32>,
+    #[serde(default, with = "DitherToml")]
+    dither: Dither,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BadgeToml {
+    name: String,
+    nickname: Option<String>,
+    title: Option<String>,
+    avatar: Option<String>,
+    #[serde(default)]
+    qr_codes: Vec<QrToml>,
+    logo: Option<String>,
+    organisation: Option<String>,
+    email: Option<String>,
+    phone: Option<String>,
+    url: Option<String>,
+    layout: String,
+    /// Format of the contact card with layout "contact"
+    card: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FontToml {
+    source: String,
+    /// Inclusive ranges of code points to keep, all glyphs if empty
+    #[serde(default)]
+    ranges: Vec<(u32, u32)>,
+    /// Advance by the ink of each glyph instead of the width in the font
+    #[serde(default)]
+    proportional: bool,
+}
+
+/// A glyph as read from a BDF file
+struct BdfGlyph {
+    c: char,
+    advance: i32,
+    /// Offset of the left column from the origin
+    x: i32,
+    /// Offset of the bottom row above the baseline
+    y: i32,
+    rows: Vec<Vec<bool>>,
+}
+
+struct BdfFont {
+    ascent: i32,
+    descent: i32,
+    glyphs: Vec<BdfGlyph>,
+}
+
+/// A QR code image, or text to encode on the badge
+#[derive(Clone, Deserialize)]
+#[serde(untagged)]
+enum QrToml {
+    Image(String),
+    Data { data: String },
+}
+
+/// Dither to black and white, true is white
+fn dither(gray: &GrayImage, method: Dither) -> Vec<Vec<bool>> {
+    let mut ditherer = Ditherer::<DITHER_WIDTH>::new(method);
+    gray.rows()
+        .enumerate()
+        .map(|(y, row)| {
+            let mut levels: Vec<u8> = row.map(|pixel| pixel[0]).collect();
+            ditherer.dither_row(y as u32, &mut levels);
+            levels.iter().map(|&level| level >= 128).collect()
+        })
+        .collect()
+}
+
+/// Pack rows MSB first, padded to whole bytes, as expected by `ImageRaw`
+fn pack(pixels: &[Vec<bool>]) -> Vec<u8> {
+    let mut data = Vec::new();
+    for row in pixels {
+        for chunk in row.chunks(8) {
+            let byte = chunk
+                .iter()
+                .enumerate()
+                .fold(0u8, |byte, (i, &white)| byte | ((white as u8) << (7 - i)));
+            data.push(byte);
+        }
+    }
+    data
+}
+
+/// Parse the parts of a BDF font needed for drawing, glyphs without a
+/// Unicode encoding are left out
+///
+/// Panics naming `file` and the glyph on bitmap rows that aren't hex or are
+/// narrower than the glyph.
+fn parse_bdf(file: &str, source: &str) -> BdfFont {
+    let mut font = BdfFont {
+        ascent: 0,
+        descent: 0,
+        glyphs: Vec::new(),
+    };
+    let number = |value: Option<&str>| value.and_then(|v| v.parse().ok()).unwrap_or(0);
+
+    let mut lines = source.lines();
+    let mut name = "";
+    let mut c = None;
+    let mut advance = 0;
+    let mut bbx = [0; 4];
+    while let Some(line) = lines.next() {
+        let mut words = line.split_whitespace();
+        match words.next() {
+            Some("FONT_ASCENT") => font.ascent = number(words.next()),
+            Some("FONT_DESCENT") => font.descent = number(words.next()),
+            Some("STARTCHAR") => name = words.next().unwrap_or_default(),
+            Some("ENCODING") => {
+                c = u32::try_from(number(words.next()))
+                    .ok()
+                    .and_then(char::from_u32)
+            }
+            Some("DWIDTH") => advance = number(words.next()),
+            Some("BBX") => bbx = [(); 4].map(|_| number(words.next())),
+            Some("BITMAP") => {
+                let [width, height, x, y] = bbx;
+                let rows = (0..height)
+                    .map(|_| {
+                        let hex = lines.next().unwrap_or_default().trim();
+                        let nibbles: Option<Vec<u32>> =
+                            hex.chars().map(|digit| digit.to_digit(16)).collect();
+                        let nibbles = match nibbles {
+                            Some(nibbles) if nibbles.len() as i32 * 4 >= width => nibbles,
+                            _ => panic!(
+                                "{}: glyph {}: bitmap row {:?} isn't {} pixels of hex",
+                                file, name, hex, width
+                            ),
+                        };
+                        (0..width as usize)
+                            .map(|i| nibbles[i / 4] >> (3 - i % 4) & 1 != 0)
+                            .collect()
+                    })
+                    .collect();
+                if let Some(c) = c.take() {
+                    font.glyphs.push(BdfGlyph {
+                        c,
+                        advance,
+                        x,
+                        y,
+                        rows,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    font.glyphs.sort_by_key(|glyph| glyph.c);
+    font
+}
+
+fn optional_str(value: &Option<String>) -> String {
+    match value {
+        Some(value) => format!("Some({:?})", value),
+        None => "None".into(),
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().unwrap().to_string_lossy().into_owned()
+}
+
+fn const_name(name: &str) -> String {
+    name.to_uppercase()
+        .replace(|c: char| !c.is_ascii_alphanumeric(), "_")
+}
+
+impl Build {
+    /// Convert an image and return the path of the raw data and its width
+    fn convert(&self, source: &str, size: Option<(u32, u32)>, method: Dither) -> (PathBuf, u32) {
+        let path = self.manifest_dir.join(source);
+        println!("cargo:rerun-if-changed={}", path.display());
+
+        let image = image::open(&path).unwrap_or_else(|err| panic!("{}: {}", source, err));
+        let image = match size {
+            Some((width, height)) => image.resize(width, height, FilterType::Lanczos3),
+            None => image,
+        };
+
+        // Transparent parts end up white
+        let mut rgba = image.to_rgba8();
+        for pixel in rgba.pixels_mut() {
+            let alpha = pixel[3] as u32;
+            for channel in 0..3 {
+                pixel[channel] =
+                    ((pixel[channel] as u32 * alpha + 255 * (255 - alpha)) / 255) as u8;
+            }
+            pixel[3] = 255;
+        }
+        let gray = image::DynamicImage::ImageRgba8(rgba).to_luma8();
+        if method != Dither::Threshold && gray.width() as usize > DITHER_WIDTH {
+            panic!(
+                "{}: {} pixels wide, resize it to at most {} to dither",
+                source,
+                gray.width(),
+                DITHER_WIDTH
+            );
+        }
+
+        // The same source can be converted at several sizes or dithers, and
+        // different sources can share a constant name, keep them apart
+        let mut hasher = DefaultHasher::new();
+        (source, size, method as u8).hash(&mut hasher);
+        let raw = self.out_dir.join("gfx").join(format!(
+            "{}-{:016x}.raw",
+            const_name(source).to_lowercase(),
+            hasher.finish()
+        ));
+        fs::create_dir_all(raw.parent().unwrap()).unwrap();
+        fs::write(&raw, pack(&dither(&gray, method))).unwrap();
+
+        (raw, gray.width())
+    }
+
+    fn images(&self) -> String {
+        let path = self.manifest_dir.join("gfx").join("images.toml");
+        println!("cargo:rerun-if-changed={}", path.display());
+
+        let images: BTreeMap<String, ImageToml> = match fs::read_to_string(&path) {
+            Ok(source) => {
+                toml::from_str(&source).unwrap_or_else(|err| panic!("{}: {}", path.display(), err))
+            }
+            Err(_) => BTreeMap::new(),
+        };
+
+        let mut code = String::new();
+        for (name, image) in images {
+            let size = match (image.width, image.height) {
+                (None, None) => None,
+                (width, height) => Some((width.unwrap_or(u32::MAX), height.unwrap_or(u32::MAX))),
+            };
+            let (raw, width) = self.convert(&image.source, size, image.dither);
+            writeln!(code, "/// Converted from `{}`", image.source).unwrap();
+            writeln!(
+                code,
+                "pub const {}: ImageRaw<'static, BinaryColor> = ImageRaw::new_binary(include_bytes!({:?}), {});",
+                const_name(&name),
+                raw,
+                width
+            )
+            .unwrap();
+        }
+        code
+    }
+
+    /// Convert a BDF font to a `ProportionalFont`, glyphs are cropped to
+    /// their ink and their bits packed back to back
+    fn font(&self, name: &str, spec: &FontToml) -> String {
+        let path = self.manifest_dir.join(&spec.source);
+        println!("cargo:rerun-if-changed={}", path.display());
+        let source =
+            fs::read_to_string(&path).unwrap_or_else(|err| panic!("{}: {}", spec.source, err));
+        let font = parse_bdf(&spec.source, &source);
+
+        let mut bits = Vec::new();
+        let mut glyphs = String::new();
+        for glyph in &font.glyphs {
+            let code = glyph.c as u32;
+            let wanted = spec.ranges.is_empty()
+                || spec
+                    .ranges
+                    .iter()
+                    .any(|&(first, last)| (first..=last).contains(&code));
+            if !wanted {
+                continue;
+            }
+
+            let ink = |row: usize, col: usize| glyph.rows[row][col];
+            let height = glyph.rows.len();
+            let width = glyph.rows.first().map_or(0, Vec::len);
+            let rows: Vec<usize> = (0..height)
+                .filter(|&row| (0..width).any(|col| ink(row, col)))
+                .collect();
+            let cols: Vec<usize> = (0..width)
+                .filter(|&col| (0..height).any(|row| ink(row, col)))
+                .collect();
+            let (top, left, ink_height, ink_width) =
+                match (rows.first(), rows.last(), cols.first(), cols.last()) {
+                    (Some(&top), Some(&bottom), Some(&left), Some(&right)) => {
+                        (top, left, bottom + 1 - top, right + 1 - left)
+                    }
+                    _ => (0, 0, 0, 0),
+                };
+
+            // Without ink, like a space, half the width of the monospace cell
+            let (x, advance) = match (spec.proportional, ink_width) {
+                (true, 0) => (0, (glyph.advance + 1) / 2),
+                (true, _) => (0, ink_width as i32),
+                (false, _) => (glyph.x + left as i32, glyph.advance),
+            };
+            // Row of the cell the ink starts in, the cell starts at the ascent
+            let y = font.ascent - glyph.y - height as i32 + top as i32;
+
+            writeln!(
+                glyphs,
+                "    Glyph {{ c: {:?}, offset: {}, width: {}, height: {}, x: {}, y: {}, advance: {} }},",
+                glyph.c,
+                bits.len(),
+                ink_width,
+                ink_height,
+                x,
+                y,
+                advance
+            )
+            .unwrap();
+            for row in top..top + ink_height {
+                bits.extend((left..left + ink_width).map(|col| ink(row, col)));
+            }
+        }
+
+        let raw = self
+            .out_dir
+            .join("fonts")
+            .join(name.to_lowercase() + ".raw");
+        fs::create_dir_all(raw.parent().unwrap()).unwrap();
+        fs::write(&raw, pack(&[bits])).unwrap();
+
+        format!(
+            "ProportionalFont {{
+    bitmap: include_bytes!({:?}),
+    glyphs: &[
+{}    ],
+    line_height: {},
+    baseline: {},
+    character_spacing: {},
+    replacement: '?',
+}}",
+            raw,
+            glyphs,
+            font.ascent + font.descent,
+            font.ascent - 1,
+            spec.proportional as u32,
+        )
+    }
+
+    fn fonts(&self) -> String {
+        let path = self.manifest_dir.join("gfx").join("fonts.toml");
+        println!("cargo:rerun-if-changed={}", path.display());
+
+        let fonts: BTreeMap<String, FontToml> = match fs::read_to_string(&path) {
+            Ok(source) => {
+                toml::from_str(&source).unwrap_or_else(|err| panic!("{}: {}", path.display(), err))
+            }
+            Err(_) => BTreeMap::new(),
+        };
+
+        let mut code = String::new();
+        for (name, font) in fonts {
+            writeln!(code, "/// Converted from `{}`", font.source).unwrap();
+            writeln!(
+                code,
+                "pub const {}: ProportionalFont<'static> = {};",
+                const_name(&name),
+                self.font(&name, &font)
+            )
+            .unwrap();
+        }
+        code
+    }
+
+    fn graphic(&self, source: &Option<String>) -> String {
+        match source {
+            Some(source) => {
+                let (raw, width) = self.convert(source, None, Dither::Threshold);
+                format!(
+                    "Some(Graphic::Raw {{ data: include_bytes!({:?}), width: {} }})",
+                    raw, width
+                )
+            }
+            None => "None".into(),
+        }
+    }
+
+    fn badge(&self, spec: &BadgeToml) -> String {
+        let card = match spec.card.as_deref() {
+            None | Some("vcard") => "VCard",
+            Some("mecard") => "MeCard",
+            Some(other) => panic!("unknown card {:?}, expected \"vcard\" or \"mecard\"", other),
+        };
+        let layout = match spec.layout.as_str() {
+            "logo" => "Logo".into(),
+            "qr" => "Qr".into(),
+            "contact" => format!("Contact(CardFormat::{})", card),
+            other => panic!(
+                "unknown layout {:?}, expected \"logo\", \"qr\" or \"contact\"",
+                other
+            ),
+        };
+        if spec.qr_codes.len() > 2 {
+            panic!("{}: at most two qr_codes are supported", spec.name);
+        }
+        let qr_code = |i: usize| match spec.qr_codes.get(i) {
+            Some(QrToml::Data { data }) => format!("Some(Graphic::Qr({:?}))", data),
+            Some(QrToml::Image(source)) => self.graphic(&Some(source.clone())),
+            None => "None".into(),
+        };
+
+        format!(
+            "BadgeSpec {{
+    name: {:?},
+    nickname: {},
+    title: {},
+    avatar: {},
+    qr_codes: [{}, {}],
+    logo: {},
+    organisation: {},
+    email: {},
+    phone: {},
+    url: {},
+    layout: Layout::{},
+}}",
+            spec.name,
+            optional_str(&spec.nickname),
+            optional_str(&spec.title),
+            self.graphic(&spec.avatar),
+            qr_code(0),
+            qr_code(1),
+            self.graphic(&spec.logo),
+            optional_str(&spec.organisation),
+            optional_str(&spec.email),
+            optional_str(&spec.phone),
+            optional_str(&spec.url),
+            layout,
+        )
+    }
+
+    fn badges(&self) -> String {
+        let badges_dir = self.manifest_dir.join("badges");
+        println!("cargo:rerun-if-changed={}", badges_dir.display());
+
+        let mut paths: Vec<PathBuf> = fs::read_dir(&badges_dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                    .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+                    .collect()
+            })
+            .unwrap_or_default();
+        paths.sort();
+
+        let mut code = String::new();
+        for path in paths {
+            println!("cargo:rerun-if-changed={}", path.display());
+            let source = fs::read_to_string(&path).unwrap();
+            let spec: BadgeToml =
+                toml::from_str(&source).unwrap_or_else(|err| panic!("{}: {}", path.display(), err));
+
+            writeln!(code, "/// Generated from `badges/{}`", file_name(&path)).unwrap();
+            writeln!(
+                code,
+                "pub const {}: BadgeSpec<'static> = {};",
+                const_name(&path.file_stem().unwrap().to_string_lossy()),
+                self.badge(&spec)
+            )
+            .unwrap();
+        }
+        code
+    }
+}
+
+fn main() {
+    let build = Build {
+        manifest_dir: PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()),
+        out_dir: PathBuf::from(env::var("OUT_DIR").unwrap()),
+    };
+
+    fs::write(build.out_dir.join("images.rs"), build.images()).unwrap();
+    fs::write(build.out_dir.join("badges.rs"), build.badges()).unwrap();
+    fs::write(build.out_dir.join("fonts.rs"), build.fonts()).unwrap();
+}
@@ -0,0 +1,72 @@
+//! # Rust Badge for badger2040
+//!
+//! Places a label at every anchor point of the screen.
+
+// region: imports and boilerplate
+#![no_std]
+#![no_main]
+
+// Halt if panic
+use panic_halt as _;
+
+// Required traits
+use embedded_hal::digital::v2::OutputPin;
+
+// Hardware
+use badger2040::{Badger2040, Config};
+
+use badger2040::bsp::entry;
+
+// Graphics library
+use embedded_graphics::{
+    geometry::AnchorPoint,
+    mono_font::{ascii::FONT_6X10, MonoTextStyle},
+    pixelcolor::BinaryColor,
+    prelude::*,
+    primitives::PrimitiveStyle,
+    text::Text,
+};
+// endregion
+
+use badger2040::graphics_extensions::{Align, Insets};
+
+#[entry]
+fn main() -> ! {
+    let mut board = Badger2040::take(Config::default()).unwrap();
+
+    board.led.set_high().unwrap();
+
+    let mut screen = board.screen();
+    let area = screen.bounding_box();
+    let style = MonoTextStyle::new(&FONT_6X10, BinaryColor::Off);
+    let labels = [
+        ("top left", AnchorPoint::TopLeft),
+        ("top center", AnchorPoint::TopCenter),
+        ("top right", AnchorPoint::TopRight),
+        ("center left", AnchorPoint::CenterLeft),
+        ("center right", AnchorPoint::CenterRight),
+        ("bottom left", AnchorPoint::BottomLeft),
+        ("bottom center", AnchorPoint::BottomCenter),
+        ("bottom right", AnchorPoint::BottomRight),
+    ];
+    for (label, anchor) in labels {
+        Text::new(label, Point::zero(), style)
+            .align_with_margin(&area, anchor, 4)
+            .draw(&mut screen)
+            .unwrap();
+    }
+
+    let center = Text::new("center", Point::zero(), style).align(&area, AnchorPoint::Center);
+    Insets::symmetric(6, 4)
+        .grow(&center.bounding_box())
+        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::Off, 1))
+        .draw(&mut screen)
+        .unwrap();
+    center.draw(&mut screen).unwrap();
+    board.display.update().unwrap();
+
+    board.led.set_low().unwrap();
+    loop {
+        board.delay.delay_ms(1000);
+    }
+}
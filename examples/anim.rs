@@ -31,14 +31,14 @@ use embedded_graphics::{
     mono_font::{ascii::FONT_10X20, MonoTextStyle},
     pixelcolor::BinaryColor,
     prelude::*,
-    primitives::{PrimitiveStyleBuilder, StrokeAlignment::Outside},
+    primitives::PrimitiveStyle,
     text::{Alignment, Text},
 };
 
 // endregion
 
 // region: embedded_graphics extensions
-use badger2040::graphics_extensions::Centering;
+use badger2040::graphics_extensions::{BoxStyle, Boxed, Centering, Insets};
 // endregion
 
 // region: trig
@@ -114,14 +114,18 @@ fn main() -> ! {
         let r = 50;
         let x = sin(t as f64 / 11.0) * 100.0 + uc8151::WIDTH as f64 / 2.0 - r as f64 / 2.0;
         let y = cos(t as f64 / 13.0) * 50.0 + uc8151::HEIGHT as f64 / 2.0 - r as f64 / 2.0;
-        let circle = Circle::new(Point::new(x as i32, y as i32), r).into_styled(
-            PrimitiveStyleBuilder::new()
-                .stroke_color(BinaryColor::On)
-                .stroke_width(1)
-                .stroke_alignment(Outside)
-                .build(),
-        );
-        circle.draw(&mut display).unwrap();
+        // A white ring around the circle, fully rounded corners make the box round
+        let circle = Circle::new(Point::new(x as i32, y as i32), r)
+            .into_styled(PrimitiveStyle::<BinaryColor>::default());
+        let ring = BoxStyle {
+            border: 1,
+            padding: Insets::ZERO,
+            radius: r / 2 + 1,
+            fill: false,
+            inverted: true,
+            shadow: 0,
+        };
+        Boxed::new(circle, ring).draw(&mut display).unwrap();
 
         display.update().unwrap();
     }
@@ -0,0 +1,79 @@
+//! # Rust Badge for badger2040
+//!
+//! Side banners: "KouvostoTelecom" running up the right edge, "KTC" stacked
+//! letter by letter on the left and a name between them.
+
+// region: imports and boilerplate
+#![no_std]
+#![no_main]
+
+// Halt if panic
+use panic_halt as _;
+
+// Required traits
+use embedded_hal::digital::v2::OutputPin;
+
+// Hardware
+use badger2040::{Badger2040, Config};
+
+use badger2040::bsp::entry;
+
+// Graphics library
+use embedded_graphics::{
+    geometry::AnchorPoint,
+    mono_font::{
+        ascii::{FONT_10X20, FONT_9X15_BOLD},
+        MonoTextStyle,
+    },
+    pixelcolor::BinaryColor,
+    prelude::*,
+    text::{Alignment, Text},
+};
+// endregion
+
+use badger2040::fonts::{ProportionalTextStyle, BOLD_13};
+use badger2040::graphics_extensions::{Align, Centering, RotatedText, Rotation, StackedText};
+
+#[entry]
+fn main() -> ! {
+    let Badger2040 {
+        mut display,
+        mut led,
+        mut delay,
+        ..
+    } = Badger2040::take(Config::default()).unwrap();
+
+    led.set_high().unwrap();
+
+    let area = display.bounding_box();
+
+    let banner = RotatedText::new(
+        "KouvostoTelecom",
+        Point::zero(),
+        ProportionalTextStyle::new(&BOLD_13, BinaryColor::Off),
+        Rotation::Deg270,
+    )
+    .align_with_margin(&area, AnchorPoint::CenterRight, 4);
+    banner.draw(&mut display).unwrap();
+
+    let initials = StackedText::new(
+        "KTC",
+        Point::zero(),
+        MonoTextStyle::new(&FONT_10X20, BinaryColor::Off),
+    )
+    .align_with_margin(&area, AnchorPoint::CenterLeft, 8);
+    initials.draw(&mut display).unwrap();
+
+    let style = MonoTextStyle::new(&FONT_9X15_BOLD, BinaryColor::Off);
+    Text::with_alignment("Taneli Kaivola", Point::zero(), style, Alignment::Center)
+        .center(area.center())
+        .draw(&mut display)
+        .unwrap();
+
+    display.update().unwrap();
+
+    led.set_low().unwrap();
+    loop {
+        delay.delay_ms(1000);
+    }
+}
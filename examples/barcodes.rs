@@ -0,0 +1,68 @@
+//! # Rust Badge for badger2040
+//!
+//! An event ticket with a Code 128 ticket number and an EAN-13 product code
+//! on the left and a Data Matrix of the ticket URL on the right.
+
+// region: imports and boilerplate
+#![no_std]
+#![no_main]
+
+// Halt if panic
+use panic_halt as _;
+
+// Required traits
+use embedded_hal::digital::v2::OutputPin;
+
+// Hardware
+use badger2040::{Badger2040, Config};
+
+use badger2040::bsp::entry;
+
+// Graphics library
+use embedded_graphics::prelude::*;
+// endregion
+
+use badger2040::barcode::{Code128, DataMatrix, Ean13};
+use badger2040::layout::{Grid, Length};
+
+#[entry]
+fn main() -> ! {
+    let Badger2040 {
+        mut display,
+        mut led,
+        mut delay,
+        ..
+    } = Badger2040::take(Config::default()).unwrap();
+
+    led.set_high().unwrap();
+
+    // The Data Matrix as high as the display, the barcodes share the rest
+    let grid = Grid::new(
+        [Length::Fraction(1), Length::Fixed(128)],
+        [Length::Fraction(1); 2],
+    )
+    .spacing(4, 4);
+    let area = display.bounding_box();
+
+    Code128::encode("KTC-0042")
+        .unwrap()
+        .fit_into(&grid.cell(&area, 0, 0))
+        .draw(&mut display)
+        .unwrap();
+    Ean13::encode("400638133393")
+        .unwrap()
+        .fit_into(&grid.cell(&area, 0, 1))
+        .draw(&mut display)
+        .unwrap();
+    DataMatrix::encode(b"https://kouvostotelecom.fi/t/0042")
+        .unwrap()
+        .fit_into(&grid.span(&area, 1, 0, 1, 2))
+        .draw(&mut display)
+        .unwrap();
+    display.update().unwrap();
+
+    led.set_low().unwrap();
+    loop {
+        delay.delay_ms(1000);
+    }
+}
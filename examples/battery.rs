@@ -0,0 +1,77 @@
+//! # Battery voltage for badger2040
+//!
+//! Shows the battery voltage and estimated charge, refreshed every minute,
+//! with a battery indicator in the top right corner.
+
+// region: imports and boilerplate
+#![no_std]
+#![no_main]
+
+// Halt if panic
+use panic_halt as _;
+
+// Required traits
+use core::fmt::Write;
+use embedded_hal::digital::v2::{InputPin, OutputPin};
+
+// Hardware
+use badger2040::graphics_extensions::BatteryIndicator;
+use badger2040::{Badger2040, Config};
+
+use badger2040::bsp::entry;
+use heapless::String;
+
+// Graphics library
+use embedded_graphics::{
+    mono_font::{ascii::FONT_10X20, MonoTextStyle},
+    pixelcolor::BinaryColor,
+    prelude::*,
+    text::{Alignment, Text},
+};
+// endregion
+
+#[entry]
+fn main() -> ! {
+    let Badger2040 {
+        mut display,
+        mut battery,
+        vbus_detect,
+        mut led,
+        mut delay,
+        ..
+    } = Badger2040::take(Config::default()).unwrap();
+
+    let style_black = MonoTextStyle::new(&FONT_10X20, BinaryColor::Off);
+    let screen_center = Point::new((uc8151::WIDTH / 2) as i32, (uc8151::HEIGHT / 2) as i32);
+
+    loop {
+        let reading = battery.read();
+
+        let mut s: String<30> = String::new();
+        write!(
+            s,
+            "{}.{:03} V\n{} %",
+            reading.millivolts / 1000,
+            reading.millivolts % 1000,
+            reading.percent
+        )
+        .unwrap();
+
+        display.clear(BinaryColor::On).unwrap();
+        Text::with_alignment(&s, screen_center, style_black, Alignment::Center)
+            .draw(&mut display)
+            .unwrap();
+
+        let charging = vbus_detect.is_high().unwrap();
+        let corner = Point::new((uc8151::WIDTH - BatteryIndicator::SIZE.width) as i32, 0);
+        BatteryIndicator::new(corner, reading.percent, charging)
+            .draw(&mut display)
+            .unwrap();
+
+        led.set_high().unwrap();
+        display.update().unwrap();
+        led.set_low().unwrap();
+
+        delay.delay_ms(60_000);
+    }
+}
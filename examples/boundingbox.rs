@@ -26,14 +26,13 @@ use embedded_graphics::{
     mono_font::{ascii::FONT_10X20, MonoTextStyle},
     pixelcolor::BinaryColor,
     prelude::*,
-    primitives::{PrimitiveStyleBuilder, StrokeAlignment::Outside},
     text::{Alignment, Text},
 };
 
 // endregion
 
 // region: embedded_graphics extensions
-use badger2040::graphics_extensions::Centering;
+use badger2040::graphics_extensions::{BoxStyle, Boxed, Centering, Insets};
 // endregion
 
 #[entry]
@@ -91,12 +90,11 @@ fn main() -> ! {
     display.setup(&mut delay, uc8151::LUT::Fast).unwrap();
 
     let style_black = MonoTextStyle::new(&FONT_10X20, BinaryColor::Off);
-    let box_style = PrimitiveStyleBuilder::new()
-        .stroke_color(BinaryColor::Off)
-        .stroke_width(10)
-        .stroke_alignment(Outside)
-        .fill_color(BinaryColor::On)
-        .build();
+    let box_style = BoxStyle {
+        border: 10,
+        padding: Insets::ZERO,
+        ..BoxStyle::default()
+    };
 
     let mut led: Pin<_, Output<PushPull>> = pins.led.into_mode();
 
@@ -111,11 +109,7 @@ fn main() -> ! {
 
         let text = text.center(screen_center);
 
-        text.bounding_box()
-            .into_styled(box_style)
-            .draw(&mut display)
-            .unwrap();
-        text.draw(&mut display).unwrap();
+        Boxed::new(text, box_style).draw(&mut display).unwrap();
 
         led.set_high().unwrap();
         display.update().unwrap();
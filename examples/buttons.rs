@@ -0,0 +1,96 @@
+//! # Button events for badger2040
+//!
+//! Shows the latest button event or gesture on the screen.
+//! Hold A+C for three seconds or press up, up, down, down for a gesture.
+
+// region: imports and boilerplate
+#![no_std]
+#![no_main]
+
+// Halt if panic
+use panic_halt as _;
+
+// Required traits
+use core::fmt::Write;
+use embedded_hal::digital::v2::OutputPin;
+
+// Hardware
+use badger2040::buttons::{self, Button, Buttons};
+use badger2040::gestures::{Gesture, Pattern, Recognizer};
+use badger2040::{Badger2040, Config};
+
+use badger2040::bsp::entry;
+use heapless::String;
+
+// Graphics library
+use embedded_graphics::{
+    mono_font::{ascii::FONT_10X20, MonoTextStyle},
+    pixelcolor::BinaryColor,
+    prelude::*,
+    text::{Alignment, Text},
+};
+// endregion
+
+static GESTURES: [Gesture; 2] = [
+    Gesture {
+        name: "A+C held",
+        pattern: Pattern::Chord {
+            buttons: &[Button::A, Button::C],
+            hold_ms: 3000,
+        },
+    },
+    Gesture {
+        name: "Up up down down",
+        pattern: Pattern::Sequence {
+            buttons: &[Button::Up, Button::Up, Button::Down, Button::Down],
+            timeout_ms: 1000,
+        },
+    },
+];
+
+#[entry]
+fn main() -> ! {
+    let Badger2040 {
+        mut display,
+        buttons,
+        mut led,
+        timer,
+        ..
+    } = Badger2040::take(Config {
+        lut: uc8151::LUT::Fast,
+        ..Config::default()
+    })
+    .unwrap();
+
+    let mut buttons = Buttons::from_board(buttons, buttons::Config::default());
+    let mut gestures = Recognizer::new(&GESTURES);
+
+    let style_black = MonoTextStyle::new(&FONT_10X20, BinaryColor::Off);
+    let screen_center = Point::new((uc8151::WIDTH / 2) as i32, (uc8151::HEIGHT / 2) as i32);
+
+    loop {
+        let now_ms = (timer.get_counter().ticks() / 1000) as u32;
+        buttons.poll(now_ms).unwrap();
+
+        let mut s: String<30> = String::new();
+        if let Some(event) = buttons.next_event() {
+            match gestures.handle(event, now_ms) {
+                Some(name) => write!(s, "{}", name).unwrap(),
+                None => write!(s, "{:?}\n{:?}", event.button, event.kind).unwrap(),
+            }
+        } else if let Some(name) = gestures.tick(now_ms) {
+            write!(s, "{}", name).unwrap();
+        }
+
+        if !s.is_empty() {
+            display.clear(BinaryColor::On).unwrap();
+            Text::with_alignment(&s, screen_center, style_black, Alignment::Center)
+                .draw(&mut display)
+                .unwrap();
+
+            led.set_high().unwrap();
+            display.update().unwrap();
+            led.set_low().unwrap();
+        }
+    }
+}
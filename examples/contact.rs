@@ -0,0 +1,52 @@
+//! # Rust Badge for badger2040
+//!
+//! Zokol's badge with a contact card QR code instead of the logo.
+
+// region: imports and boilerplate
+#![no_std]
+#![no_main]
+
+// Halt if panic
+use panic_halt as _;
+
+// Required traits
+use embedded_hal::digital::v2::OutputPin;
+
+// Hardware
+use badger2040::{Badger2040, Config};
+
+use badger2040::bsp::entry;
+
+// Graphics library
+use embedded_graphics::prelude::*;
+// endregion
+
+use badger2040::badge::{badges::ZOKOL, BadgeSpec, Layout};
+use badger2040::contact::CardFormat;
+
+#[entry]
+fn main() -> ! {
+    let Badger2040 {
+        mut display,
+        mut led,
+        mut delay,
+        ..
+    } = Badger2040::take(Config::default()).unwrap();
+
+    led.set_high().unwrap();
+
+    let badge = BadgeSpec {
+        organisation: Some("KouvostoTelecom"),
+        email: Some("zokol@example.com"),
+        url: Some("https://kouvostotelecom.fi"),
+        layout: Layout::Contact(CardFormat::VCard),
+        ..ZOKOL
+    };
+    badge.draw(&mut display).unwrap();
+    display.update().unwrap();
+
+    led.set_low().unwrap();
+    loop {
+        delay.delay_ms(1000);
+    }
+}
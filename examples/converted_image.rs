@@ -0,0 +1,49 @@
+//! # Rust Badge for badger2040
+//!
+//! Draws an image converted at build time from `gfx/images.toml`.
+
+// region: imports and boilerplate
+#![no_std]
+#![no_main]
+
+// Halt if panic
+use panic_halt as _;
+
+// Required traits
+use embedded_hal::digital::v2::OutputPin;
+
+// Hardware
+use badger2040::{Badger2040, Config};
+
+use badger2040::bsp::entry;
+
+// Graphics library
+use embedded_graphics::{image::Image, prelude::*};
+// endregion
+
+use badger2040::graphics_extensions::Centering;
+use badger2040::images::DIST;
+
+#[entry]
+fn main() -> ! {
+    let Badger2040 {
+        mut display,
+        mut led,
+        mut delay,
+        ..
+    } = Badger2040::take(Config::default()).unwrap();
+
+    led.set_high().unwrap();
+
+    let screen_center = Point::new((uc8151::WIDTH / 2) as i32, (uc8151::HEIGHT / 2) as i32);
+    Image::new(&DIST, Point::zero())
+        .center(screen_center)
+        .draw(&mut display)
+        .unwrap();
+    display.update().unwrap();
+
+    led.set_low().unwrap();
+    loop {
+        delay.delay_ms(1000);
+    }
+}
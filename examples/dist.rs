@@ -30,13 +30,12 @@ use embedded_graphics::{
     mono_font::{ascii::FONT_10X20, MonoTextStyle},
     pixelcolor::BinaryColor,
     prelude::*,
-    primitives::{PrimitiveStyleBuilder, StrokeAlignment::Outside},
     text::{Alignment, Text},
 };
 // endregion
 
 // region: embedded_graphics extensions
-use badger2040::graphics_extensions::Centering;
+use badger2040::graphics_extensions::{BoxStyle, Centering, Insets};
 // endregion
 
 #[entry]
@@ -99,12 +98,11 @@ fn main() -> ! {
     let style_fullname = MonoTextStyle::new(&FONT_6X13, BinaryColor::Off);
     let style_black = MonoTextStyle::new(&FONT_10X20, BinaryColor::Off);
     let style_white = MonoTextStyle::new(&FONT_10X20, BinaryColor::On);
-    let box_style = PrimitiveStyleBuilder::new()
-        .stroke_color(BinaryColor::Off)
-        .stroke_width(10)
-        .stroke_alignment(Outside)
-        .fill_color(BinaryColor::On)
-        .build();
+    let box_style = BoxStyle {
+        border: 10,
+        padding: Insets::ZERO,
+        ..BoxStyle::default()
+    };
 
     let screen_center = Point::new((uc8151::WIDTH / 2) as i32, (uc8151::HEIGHT / 2) as i32);
     let split_at = uc8151::WIDTH / 3;
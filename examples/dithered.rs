@@ -0,0 +1,58 @@
+//! # Rust Badge for badger2040
+//!
+//! Draws the same portrait with different dithering methods.
+
+// region: imports and boilerplate
+#![no_std]
+#![no_main]
+
+// Halt if panic
+use panic_halt as _;
+
+// Required traits
+use embedded_hal::digital::v2::OutputPin;
+
+// Hardware
+use badger2040::{Badger2040, Config};
+
+use badger2040::bsp::entry;
+use tinytga::Tga;
+
+// Graphics library
+use embedded_graphics::{image::Image, pixelcolor::Rgb888, prelude::*};
+// endregion
+
+use badger2040::graphics_extensions::{Dither, Dithered};
+
+#[entry]
+fn main() -> ! {
+    let Badger2040 {
+        mut display,
+        mut led,
+        mut delay,
+        ..
+    } = Badger2040::take(Config::default()).unwrap();
+
+    led.set_high().unwrap();
+
+    let portrait: Tga<Rgb888> =
+        Tga::from_slice(include_bytes!("../gfx/dist_portrait.tga")).unwrap();
+    let methods = [
+        Dither::Threshold,
+        Dither::FloydSteinberg,
+        Dither::Atkinson,
+        Dither::Bayer8,
+    ];
+    for (i, method) in methods.into_iter().enumerate() {
+        let image = Dithered::<_>::new(portrait, method);
+        Image::new(&image, Point::new(i as i32 * 74, 0))
+            .draw(&mut display)
+            .unwrap();
+    }
+    display.update().unwrap();
+
+    led.set_low().unwrap();
+    loop {
+        delay.delay_ms(1000);
+    }
+}
@@ -34,7 +34,6 @@ use embedded_graphics::{
     },
     pixelcolor::BinaryColor,
     prelude::*,
-    primitives::{PrimitiveStyleBuilder, StrokeAlignment::Outside},
     text::{Alignment, Text},
 };
 // endregion
@@ -68,8 +67,12 @@ const FONTS: [(&str, &embedded_graphics::mono_font::MonoFont<'static>); 22] = [
     ("FONT_10X20", &FONT_10X20),
 ];
 
+const PROPORTIONAL_FONTS: [(&str, &ProportionalFont<'static>); 2] =
+    [("BOLD_13", &fonts::BOLD_13), ("BOLD_18", &fonts::BOLD_18)];
+
 // region: embedded_graphics extensions
-use badger2040::graphics_extensions::Centering;
+use badger2040::fonts::{self, ProportionalFont, ProportionalTextStyle};
+use badger2040::graphics_extensions::{BoxStyle, Boxed, Centering, Insets};
 // endregion
 
 #[entry]
@@ -128,18 +131,11 @@ fn main() -> ! {
 
     let padding = 4;
     let border = 4;
-    let box_style = PrimitiveStyleBuilder::new()
-        .stroke_color(BinaryColor::Off)
-        .stroke_width(border + padding)
-        .stroke_alignment(Outside)
-        .fill_color(BinaryColor::On)
-        .build();
-    let box_style_clear = PrimitiveStyleBuilder::new()
-        .stroke_color(BinaryColor::On)
-        .stroke_width(padding)
-        .stroke_alignment(Outside)
-        .fill_color(BinaryColor::On)
-        .build();
+    let box_style = BoxStyle {
+        border,
+        padding: Insets::all(padding),
+        ..BoxStyle::default()
+    };
 
     let mut led: Pin<_, Output<PushPull>> = pins.led.into_mode();
     let mut rng = SmallRng::seed_from_u64(12345678);
@@ -159,15 +155,27 @@ fn main() -> ! {
                 screen_center + Point::new(rng.gen_range(-100..100), rng.gen_range(-50..50)),
             );
 
-            text.bounding_box()
-                .into_styled(box_style)
-                .draw(&mut display)
-                .unwrap();
-            text.bounding_box()
-                .into_styled(box_style_clear)
-                .draw(&mut display)
-                .unwrap();
-            text.draw(&mut display).unwrap();
+            Boxed::new(text, box_style).draw(&mut display).unwrap();
+
+            led.set_high().unwrap();
+            display.update().unwrap();
+            led.set_low().unwrap();
+
+            delay.delay_ms(1000);
+        }
+
+        for (font_name, font_type) in PROPORTIONAL_FONTS.iter() {
+            let style_black = ProportionalTextStyle::new(font_type, BinaryColor::Off);
+            let mut s: String<40> = String::new();
+            write!(s, "Proportional {}\nÄijä Šörå", font_name).unwrap();
+
+            let text = Text::with_alignment(&s, Point::new(0, 0), style_black, Alignment::Center);
+
+            let text = text.center(
+                screen_center + Point::new(rng.gen_range(-100..100), rng.gen_range(-50..50)),
+            );
+
+            Boxed::new(text, box_style).draw(&mut display).unwrap();
 
             led.set_high().unwrap();
             display.update().unwrap();
@@ -0,0 +1,65 @@
+//! # Rust Badge for badger2040
+//!
+//! Shows a portrait and a grey scale with four grey levels.
+
+// region: imports and boilerplate
+#![no_std]
+#![no_main]
+
+// Halt if panic
+use panic_halt as _;
+
+// Required traits
+use embedded_hal::digital::v2::OutputPin;
+
+// Hardware
+use badger2040::{Badger2040, Config};
+
+use badger2040::bsp::entry;
+use tinytga::Tga;
+
+// Graphics library
+use embedded_graphics::{
+    image::Image,
+    pixelcolor::Gray2,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+};
+// endregion
+
+use badger2040::display::{GreyFramebuffer, GreyscaleRenderer, Timing};
+
+#[entry]
+fn main() -> ! {
+    let Badger2040 {
+        mut display,
+        mut led,
+        mut delay,
+        ..
+    } = Badger2040::take(Config::default()).unwrap();
+
+    led.set_high().unwrap();
+
+    let mut framebuffer = GreyFramebuffer::new();
+
+    let portrait: Tga<Gray2> = Tga::from_slice(include_bytes!("../gfx/dist_portrait.tga")).unwrap();
+    Image::new(&portrait, Point::zero())
+        .draw(&mut framebuffer)
+        .unwrap();
+
+    for luma in 0..4 {
+        Rectangle::new(Point::new(96 + 48 * luma as i32, 32), Size::new(48, 64))
+            .into_styled(PrimitiveStyle::with_fill(Gray2::new(luma)))
+            .draw(&mut framebuffer)
+            .unwrap();
+    }
+
+    GreyscaleRenderer::new(Timing::default())
+        .render(&mut display, &framebuffer)
+        .unwrap();
+
+    led.set_low().unwrap();
+    loop {
+        delay.delay_ms(1000);
+    }
+}
@@ -0,0 +1,73 @@
+//! # Rust Badge for badger2040
+//!
+//! A name as large as fits the width of the display, scaled up from
+//! `FONT_10X20` with smoothed edges, and a title under it.
+
+// region: imports and boilerplate
+#![no_std]
+#![no_main]
+
+// Halt if panic
+use panic_halt as _;
+
+// Required traits
+use embedded_hal::digital::v2::OutputPin;
+
+// Hardware
+use badger2040::{Badger2040, Config};
+
+use badger2040::bsp::entry;
+
+// Graphics library
+use embedded_graphics::{
+    mono_font::{ascii::FONT_10X20, MonoTextStyle},
+    pixelcolor::BinaryColor,
+    prelude::*,
+    text::{Alignment, Text},
+};
+// endregion
+
+use badger2040::fonts::{ProportionalTextStyle, BOLD_18};
+use badger2040::graphics_extensions::{Centering, Scaled};
+
+const NAME: &str = "Zokol";
+const TITLE: &str = "Hacker";
+
+#[entry]
+fn main() -> ! {
+    let mut board = Badger2040::take(Config::default()).unwrap();
+
+    board.led.set_high().unwrap();
+
+    let mut screen = board.screen();
+    let area = screen.bounding_box();
+    let font = MonoTextStyle::new(&FONT_10X20, BinaryColor::Off);
+
+    // The largest scale that fits, down to the font as it is
+    let name = (1..=4)
+        .rev()
+        .map(|scale| {
+            let style = Scaled::new(font, scale).with_smoothing(true);
+            Text::with_alignment(NAME, Point::zero(), style, Alignment::Center)
+        })
+        .find(|text| text.bounding_box().size.width <= area.size.width)
+        .unwrap_or_else(|| {
+            let style = Scaled::new(font, 1);
+            Text::with_alignment(NAME, Point::zero(), style, Alignment::Center)
+        })
+        .center(Point::new(area.center().x, 48));
+    name.draw(&mut screen).unwrap();
+
+    let title_style = ProportionalTextStyle::new(&BOLD_18, BinaryColor::Off);
+    Text::with_alignment(TITLE, Point::zero(), title_style, Alignment::Center)
+        .center(Point::new(area.center().x, 108))
+        .draw(&mut screen)
+        .unwrap();
+
+    board.display.update().unwrap();
+
+    board.led.set_low().unwrap();
+    loop {
+        board.delay.delay_ms(1000);
+    }
+}
@@ -29,13 +29,12 @@ use embedded_graphics::{
     mono_font::{ascii::FONT_10X20, MonoTextStyle},
     pixelcolor::BinaryColor,
     prelude::*,
-    primitives::{PrimitiveStyleBuilder, StrokeAlignment::Outside},
     text::{Alignment, Text},
 };
 // endregion
 
 // region: embedded_graphics extensions
-use badger2040::graphics_extensions::Centering;
+use badger2040::graphics_extensions::{BoxStyle, Centering, Insets};
 // endregion
 
 use tinytga::{DynamicTga, Tga};
@@ -95,12 +94,11 @@ fn main() -> ! {
     display.setup(&mut delay, uc8151::LUT::Normal).unwrap();
 
     let style_black = MonoTextStyle::new(&FONT_10X20, BinaryColor::Off);
-    let box_style = PrimitiveStyleBuilder::new()
-        .stroke_color(BinaryColor::Off)
-        .stroke_width(10)
-        .stroke_alignment(Outside)
-        .fill_color(BinaryColor::On)
-        .build();
+    let box_style = BoxStyle {
+        border: 10,
+        padding: Insets::ZERO,
+        ..BoxStyle::default()
+    };
 
     let mut led: Pin<_, Output<PushPull>> = pins.led.into_mode();
 
@@ -117,10 +115,10 @@ fn main() -> ! {
 
     led.set_low().unwrap();
 
-    for i in 0..40{
+    for i in 0..40 {
         display.update().unwrap();
     }
-    
+
     loop {
         led.set_high().unwrap();
     }
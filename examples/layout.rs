@@ -0,0 +1,93 @@
+//! # Rust Badge for badger2040
+//!
+//! A title over a grid of boxes, placed with the layout module.
+
+// region: imports and boilerplate
+#![no_std]
+#![no_main]
+
+// Halt if panic
+use panic_halt as _;
+
+// Required traits
+use embedded_hal::digital::v2::OutputPin;
+
+// Hardware
+use badger2040::{Badger2040, Config};
+
+use badger2040::bsp::entry;
+
+// Graphics library
+use embedded_graphics::{
+    geometry::AnchorPoint,
+    mono_font::{
+        ascii::{FONT_10X20, FONT_6X10},
+        MonoTextStyle,
+    },
+    pixelcolor::BinaryColor,
+    prelude::*,
+    primitives::PrimitiveStyle,
+    text::Text,
+};
+// endregion
+
+use badger2040::graphics_extensions::Align;
+use badger2040::layout::{Grid, Length, VStack};
+
+#[entry]
+fn main() -> ! {
+    let mut board = Badger2040::take(Config::default()).unwrap();
+
+    board.led.set_high().unwrap();
+
+    let mut screen = board.screen();
+
+    let title = Text::new(
+        "Kouvosto Telecom",
+        Point::zero(),
+        MonoTextStyle::new(&FONT_10X20, BinaryColor::Off),
+    );
+    let [title_slot, grid_slot] = VStack::new([Length::fit(&title), Length::Fraction(1)])
+        .spacing(6)
+        .padding(4)
+        .layout(&screen.bounding_box());
+    title
+        .align(&title_slot, AnchorPoint::Center)
+        .draw(&mut screen)
+        .unwrap();
+
+    // The first column twice as wide as the others, the bottom left cell
+    // spanning two columns
+    let grid = Grid::new(
+        [
+            Length::Fraction(2),
+            Length::Fraction(1),
+            Length::Fraction(1),
+        ],
+        [Length::Fraction(1); 2],
+    )
+    .spacing(4, 4);
+    let cells = [
+        ("Rust", grid.cell(&grid_slot, 0, 0)),
+        ("RP2040", grid.cell(&grid_slot, 1, 0)),
+        ("UC8151", grid.cell(&grid_slot, 2, 0)),
+        ("embedded-graphics", grid.span(&grid_slot, 0, 1, 2, 1)),
+        ("e-ink", grid.cell(&grid_slot, 2, 1)),
+    ];
+    let style = MonoTextStyle::new(&FONT_6X10, BinaryColor::Off);
+    for (label, cell) in cells {
+        cell.into_styled(PrimitiveStyle::with_stroke(BinaryColor::Off, 1))
+            .draw(&mut screen)
+            .unwrap();
+        Text::new(label, Point::zero(), style)
+            .align(&cell, AnchorPoint::Center)
+            .draw(&mut screen)
+            .unwrap();
+    }
+    board.display.update().unwrap();
+
+    board.led.set_low().unwrap();
+    loop {
+        board.delay.delay_ms(1000);
+    }
+}
@@ -0,0 +1,136 @@
+//! # Rust Badge for badger2040
+//!
+//! One badge, four pages: `A` shows the identity page, `B` the contact QR
+//! code, `C` the schedule and pressed again the about page. `Up` and `Down`
+//! scroll the schedule, which is refreshed with the fast waveform.
+
+// region: imports and boilerplate
+#![no_std]
+#![no_main]
+
+// Halt if panic
+use panic_halt as _;
+
+// Hardware
+use badger2040::{Badger2040, Config};
+
+use badger2040::bsp::entry;
+
+// Graphics library
+use embedded_graphics::{
+    mono_font::{ascii::FONT_6X13, MonoTextStyle},
+    pixelcolor::BinaryColor,
+    prelude::*,
+    primitives::{Line, PrimitiveStyle, Rectangle},
+    text::{Baseline, Text},
+};
+// endregion
+
+use badger2040::badge::{badges::ZOKOL, BadgeSpec, Layout};
+use badger2040::buttons::{self, Buttons};
+use badger2040::contact::CardFormat;
+use badger2040::display::{RefreshConfig, RefreshManager};
+use badger2040::fonts::{ProportionalTextStyle, BOLD_18};
+use badger2040::pages::{Page, Pages};
+use uc8151::LUT;
+
+const HEADER_HEIGHT: u32 = 24;
+const ROW_HEIGHT: u32 = 16;
+/// Rows of the schedule below the header
+const VISIBLE_ROWS: usize = 6;
+
+/// A page with a title and lines that scroll under it
+struct Listing {
+    title: &'static str,
+    lines: &'static [&'static str],
+    lut: LUT,
+}
+
+impl<D: DrawTarget<Color = BinaryColor>> Page<D> for Listing {
+    fn draw(&self, target: &mut D, scroll: u32) -> Result<(), D::Error> {
+        let title = ProportionalTextStyle::new(&BOLD_18, BinaryColor::Off);
+        Text::with_baseline(self.title, Point::new(4, 2), title, Baseline::Top).draw(target)?;
+        let width = target.bounding_box().size.width as i32;
+        let y = HEADER_HEIGHT as i32 - 3;
+        Line::new(Point::new(0, y), Point::new(width - 1, y))
+            .into_styled(PrimitiveStyle::with_stroke(BinaryColor::Off, 1))
+            .draw(target)?;
+
+        let style = MonoTextStyle::new(&FONT_6X13, BinaryColor::Off);
+        let lines = self.lines.iter().skip(scroll as usize).take(VISIBLE_ROWS);
+        for (row, line) in lines.enumerate() {
+            let y = (HEADER_HEIGHT + row as u32 * ROW_HEIGHT) as i32;
+            Text::with_baseline(line, Point::new(4, y), style, Baseline::Top).draw(target)?;
+        }
+        Ok(())
+    }
+
+    fn lut(&self) -> LUT {
+        self.lut
+    }
+
+    fn scroll_steps(&self) -> u32 {
+        self.lines.len().saturating_sub(VISIBLE_ROWS) as u32
+    }
+
+    fn scroll_area(&self, screen: Rectangle) -> Rectangle {
+        Rectangle::new(
+            screen.top_left + Point::new(0, HEADER_HEIGHT as i32),
+            Size::new(screen.size.width, screen.size.height - HEADER_HEIGHT),
+        )
+    }
+}
+
+static SCHEDULE: Listing = Listing {
+    title: "Schedule",
+    lines: &[
+        "09:00 Doors open",
+        "09:30 Opening words",
+        "10:00 Rust on the RP2040",
+        "11:00 E-ink waveforms",
+        "12:00 Lunch",
+        "13:00 Badge hacking",
+        "15:00 Lightning talks",
+        "16:30 Sauna",
+        "18:00 Dinner",
+    ],
+    lut: LUT::Fast,
+};
+
+static ABOUT: Listing = Listing {
+    title: "About",
+    lines: &[
+        "Rust firmware for the badger2040",
+        "by KouvostoTelecom",
+        "",
+        "A/B/C: pages, Up/Down: scroll",
+    ],
+    lut: LUT::Fast,
+};
+
+#[entry]
+fn main() -> ! {
+    let Badger2040 {
+        display,
+        buttons,
+        timer,
+        ..
+    } = Badger2040::take(Config::default()).unwrap();
+
+    let contact = BadgeSpec {
+        layout: Layout::Contact(CardFormat::VCard),
+        ..ZOKOL
+    };
+    let manager = RefreshManager::new(display, RefreshConfig::default());
+    let mut pages = Pages::new(manager, [&ZOKOL, &contact, &SCHEDULE, &ABOUT]);
+    let mut buttons = Buttons::from_board(buttons, buttons::Config::default());
+
+    loop {
+        let now_ms = (timer.get_counter().ticks() / 1000) as u32;
+        buttons.poll(now_ms).unwrap();
+        while let Some(event) = buttons.next_event() {
+            pages.handle(event);
+        }
+        pages.update(now_ms).unwrap();
+    }
+}
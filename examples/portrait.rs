@@ -0,0 +1,71 @@
+//! # Rust Badge for badger2040
+//!
+//! A portrait badge, held with the right edge up.
+
+// region: imports and boilerplate
+#![no_std]
+#![no_main]
+
+// Halt if panic
+use panic_halt as _;
+
+// Required traits
+use embedded_hal::digital::v2::OutputPin;
+
+// Hardware
+use badger2040::{Badger2040, Config};
+
+use badger2040::bsp::entry;
+use tinytga::Tga;
+
+// Graphics library
+use embedded_graphics::{
+    image::Image,
+    mono_font::{ascii::FONT_10X20, MonoTextStyle},
+    pixelcolor::{BinaryColor, Rgb888},
+    prelude::*,
+    text::{Alignment, Text},
+};
+// endregion
+
+use badger2040::graphics_extensions::{Dither, Dithered, Rotation};
+
+#[entry]
+fn main() -> ! {
+    let mut board = Badger2040::take(Config {
+        orientation: Rotation::Deg90,
+        ..Config::default()
+    })
+    .unwrap();
+
+    board.led.set_high().unwrap();
+
+    // 128 pixels wide and 296 high
+    let mut display = board.screen();
+    let width = display.bounding_box().size.width as i32;
+
+    let portrait: Tga<Rgb888> =
+        Tga::from_slice(include_bytes!("../gfx/dist_portrait.tga")).unwrap();
+    let portrait = Dithered::<_>::new(portrait, Dither::Atkinson);
+    Image::new(&portrait, Point::new((width - 64) / 2, 16))
+        .draw(&mut display)
+        .unwrap();
+
+    let style = MonoTextStyle::new(&FONT_10X20, BinaryColor::Off);
+    for (i, line) in ["Dist", "Kouvosto", "Telecom"].iter().enumerate() {
+        Text::with_alignment(
+            line,
+            Point::new(width / 2, 190 + i as i32 * 24),
+            style,
+            Alignment::Center,
+        )
+        .draw(&mut display)
+        .unwrap();
+    }
+    board.display.update().unwrap();
+
+    board.led.set_low().unwrap();
+    loop {
+        board.delay.delay_ms(1000);
+    }
+}
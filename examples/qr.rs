@@ -0,0 +1,83 @@
+//! # Rust Badge for badger2040
+//!
+//! QR codes encoded on the badge, a normal one on the left and an inverted
+//! one on black on the right.
+
+// region: imports and boilerplate
+#![no_std]
+#![no_main]
+
+// Halt if panic
+use panic_halt as _;
+
+// Required traits
+use embedded_hal::digital::v2::OutputPin;
+
+// Hardware
+use badger2040::{Badger2040, Config};
+
+use badger2040::bsp::entry;
+
+// Graphics library
+use embedded_graphics::{
+    geometry::AnchorPoint,
+    mono_font::{ascii::FONT_6X10, MonoTextStyle},
+    pixelcolor::BinaryColor,
+    prelude::*,
+    primitives::PrimitiveStyle,
+    text::Text,
+};
+// endregion
+
+use badger2040::graphics_extensions::Align;
+use badger2040::layout::{HStack, Length};
+use badger2040::qr::{EcLevel, QrCode};
+
+#[entry]
+fn main() -> ! {
+    let Badger2040 {
+        mut display,
+        mut led,
+        mut delay,
+        ..
+    } = Badger2040::take(Config::default()).unwrap();
+
+    led.set_high().unwrap();
+
+    let [left, right] = HStack::new([Length::Fraction(1); 2]).layout(&display.bounding_box());
+    let style = MonoTextStyle::new(&FONT_6X10, BinaryColor::Off);
+
+    // Version 2, 25x25 modules and a quiet zone of 1 is 108 pixels at 4
+    // pixels per module
+    QrCode::encode(b"https://kouvostotelecom.fi", EcLevel::M)
+        .unwrap()
+        .with_module_size(4)
+        .with_quiet_zone(1)
+        .align(&left, AnchorPoint::TopCenter)
+        .draw(&mut display)
+        .unwrap();
+    Text::new("kouvostotelecom.fi", Point::zero(), style)
+        .align(&left, AnchorPoint::BottomCenter)
+        .draw(&mut display)
+        .unwrap();
+
+    // Alphanumeric mode packs upper case text tighter than bytes
+    right
+        .into_styled(PrimitiveStyle::with_fill(BinaryColor::Off))
+        .draw(&mut display)
+        .unwrap();
+    QrCode::encode(b"KOUVOSTO TELECOM", EcLevel::H)
+        .unwrap()
+        .with_module_size(4)
+        .with_quiet_zone(0)
+        .with_inverted(true)
+        .align(&right, AnchorPoint::Center)
+        .draw(&mut display)
+        .unwrap();
+    display.update().unwrap();
+
+    led.set_low().unwrap();
+    loop {
+        delay.delay_ms(1000);
+    }
+}
@@ -0,0 +1,78 @@
+//! # Rust Badge for badger2040
+//!
+//! A seconds counter that only refreshes what changed, with a cleaning
+//! refresh every ten updates.
+
+// region: imports and boilerplate
+#![no_std]
+#![no_main]
+
+// Halt if panic
+use panic_halt as _;
+
+// Required traits
+use core::fmt::Write;
+use embedded_hal::digital::v2::OutputPin;
+
+// Hardware
+use badger2040::{Badger2040, Config};
+
+use badger2040::bsp::entry;
+use heapless::String;
+
+// Graphics library
+use embedded_graphics::{
+    mono_font::{ascii::FONT_10X20, MonoTextStyle},
+    pixelcolor::BinaryColor,
+    prelude::*,
+    text::{Alignment, Text},
+};
+// endregion
+
+use badger2040::display::{RefreshConfig, RefreshManager};
+
+#[entry]
+fn main() -> ! {
+    let Badger2040 {
+        display,
+        mut led,
+        mut delay,
+        timer,
+        ..
+    } = Badger2040::take(Config {
+        lut: uc8151::LUT::Ultrafast,
+        ..Config::default()
+    })
+    .unwrap();
+
+    let now_ms = || (timer.get_counter().ticks() / 1000) as u32;
+    let mut display = RefreshManager::new(display, RefreshConfig::default());
+
+    let style_black = MonoTextStyle::new(&FONT_10X20, BinaryColor::Off);
+    let screen_center = Point::new((uc8151::WIDTH / 2) as i32, (uc8151::HEIGHT / 2) as i32);
+
+    display.clear(BinaryColor::On).unwrap();
+    display.clean().unwrap();
+
+    let mut seconds = 0u32;
+    loop {
+        let mut s: String<16> = String::new();
+        write!(s, "{} s", seconds).unwrap();
+        let text = Text::with_alignment(&s, screen_center, style_black, Alignment::Center);
+
+        // Drawing the new text marks its area for the next refresh
+        text.draw(&mut display).unwrap();
+
+        led.set_high().unwrap();
+        display.refresh(now_ms()).unwrap();
+        led.set_low().unwrap();
+
+        delay.delay_ms(1000);
+
+        // Erase the old text, its area is refreshed together with the new one
+        display
+            .fill_solid(&text.bounding_box(), BinaryColor::On)
+            .unwrap();
+        seconds += 1;
+    }
+}
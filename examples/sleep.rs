@@ -0,0 +1,58 @@
+//! # Deep sleep for badger2040
+//!
+//! Shows which button woke the badge and powers down again. On battery the
+//! image stays on the screen while the board is off.
+
+// region: imports and boilerplate
+#![no_std]
+#![no_main]
+
+// Halt if panic
+use panic_halt as _;
+
+// Required traits
+use core::fmt::Write;
+
+// Hardware
+use badger2040::power::{self, WakeReason};
+use badger2040::{Badger2040, Config};
+
+use badger2040::bsp::entry;
+use heapless::String;
+
+// Graphics library
+use embedded_graphics::{
+    mono_font::{ascii::FONT_10X20, MonoTextStyle},
+    pixelcolor::BinaryColor,
+    prelude::*,
+    text::{Alignment, Text},
+};
+// endregion
+
+#[entry]
+fn main() -> ! {
+    let Badger2040 {
+        mut display,
+        buttons,
+        wake_reason,
+        mut p3v3_en,
+        ..
+    } = Badger2040::take(Config::default()).unwrap();
+
+    let style_black = MonoTextStyle::new(&FONT_10X20, BinaryColor::Off);
+    let screen_center = Point::new((uc8151::WIDTH / 2) as i32, (uc8151::HEIGHT / 2) as i32);
+
+    let mut s: String<30> = String::new();
+    match wake_reason {
+        WakeReason::Button(button) => write!(s, "Woken by\n{:?}", button).unwrap(),
+        WakeReason::Other => write!(s, "Powered up").unwrap(),
+    }
+
+    display.clear(BinaryColor::On).unwrap();
+    Text::with_alignment(&s, screen_center, style_black, Alignment::Center)
+        .draw(&mut display)
+        .unwrap();
+    display.update().unwrap();
+
+    power::sleep_until_button(&mut display, &mut p3v3_en, &buttons, || {})
+}
@@ -0,0 +1,72 @@
+//! # Rust Badge for badger2040
+//!
+//! Fits names of different lengths into the same boxes, picking the largest
+//! font that fits.
+
+// region: imports and boilerplate
+#![no_std]
+#![no_main]
+
+// Halt if panic
+use panic_halt as _;
+
+// Required traits
+use embedded_hal::digital::v2::OutputPin;
+
+// Hardware
+use badger2040::{Badger2040, Config};
+
+use badger2040::bsp::entry;
+
+// Graphics library
+use embedded_graphics::{
+    mono_font::{
+        iso_8859_15::{FONT_10X20, FONT_6X10, FONT_6X13, FONT_9X15},
+        MonoFont,
+    },
+    pixelcolor::BinaryColor,
+    prelude::*,
+    primitives::PrimitiveStyle,
+};
+// endregion
+
+use badger2040::graphics_extensions::TextBox;
+use badger2040::layout::{Grid, Length};
+
+const FONTS: [&MonoFont; 4] = [&FONT_10X20, &FONT_9X15, &FONT_6X13, &FONT_6X10];
+
+#[entry]
+fn main() -> ! {
+    let mut board = Badger2040::take(Config::default()).unwrap();
+
+    board.led.set_high().unwrap();
+
+    let mut screen = board.screen();
+
+    let names = [
+        "dist",
+        "Heikki 'Zokol' Juva",
+        "Taneli Kaivola",
+        "Jääkiekkojoukkueen päävalmentaja",
+        "Kouvosto Telecomin hallituksen puheenjohtajan varahenkilön sijainen",
+        "€",
+    ];
+    let cells = Grid::new([Length::Fraction(1); 3], [Length::Fraction(1); 2])
+        .spacing(4, 4)
+        .padding(4)
+        .layout(&screen.bounding_box());
+    for (name, cell) in names.iter().zip(cells.iter().flatten()) {
+        cell.into_styled(PrimitiveStyle::with_stroke(BinaryColor::Off, 1))
+            .draw(&mut screen)
+            .unwrap();
+        TextBox::new(name, cell.offset(-3), &FONTS)
+            .draw(&mut screen)
+            .unwrap();
+    }
+    board.display.update().unwrap();
+
+    board.led.set_low().unwrap();
+    loop {
+        board.delay.delay_ms(1000);
+    }
+}
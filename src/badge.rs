@@ -0,0 +1,296 @@
+//! Data-driven ID badges
+//!
+//! A [`BadgeSpec`] describes who the badge is for and which images it shows,
+//! drawing it renders the chosen [`Layout`]. Specs for `badges/*.toml` are
+//! generated at build time into [`badges`].
+
+use core::fmt::Write;
+
+use embedded_graphics::{
+    geometry::AnchorPoint,
+    image::{Image, ImageRaw},
+    mono_font::{ascii::FONT_10X20, iso_8859_15, MonoFont, MonoTextStyle},
+    pixelcolor::BinaryColor,
+    prelude::*,
+    primitives::Rectangle,
+    text::{Alignment, Text},
+};
+use heapless::String;
+use tinybmp::Bmp;
+
+use crate::contact::{CardFormat, Contact};
+use crate::graphics_extensions::{Align, BoxStyle, Boxed, Insets, TextBox};
+use crate::layout::{HStack, Length, VStack};
+use crate::qr::{EcLevel, QrCode};
+
+/// Pixels per module of QR codes on badges
+const QR_MODULE_SIZE: u32 = 3;
+
+/// Fonts for the name next to a contact card, largest first
+const CONTACT_FONTS: [&MonoFont; 4] = [
+    &iso_8859_15::FONT_10X20,
+    &iso_8859_15::FONT_9X15,
+    &iso_8859_15::FONT_6X13,
+    &iso_8859_15::FONT_6X10,
+];
+
+/// Badge specs generated from `badges/*.toml`
+pub mod badges {
+    use super::*;
+
+    include!(concat!(env!("OUT_DIR"), "/badges.rs"));
+}
+
+/// An image embedded in the firmware
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Graphic<'a> {
+    /// Contents of a BMP file
+    Bmp(&'a [u8]),
+    /// 1 bit per pixel raw image data, rows padded to whole bytes
+    Raw { data: &'a [u8], width: u32 },
+    /// A QR code of the text, encoded when drawn
+    Qr(&'a str),
+}
+
+impl Graphic<'_> {
+    /// The QR code of a [`Graphic::Qr`], without a quiet zone as badges have
+    /// white around them anyway
+    fn qr_code(data: &str) -> Option<QrCode> {
+        QrCode::encode(data.as_bytes(), EcLevel::M)
+            .ok()
+            .map(|qr| qr.with_module_size(QR_MODULE_SIZE).with_quiet_zone(0))
+    }
+
+    /// Size of the image, zero for BMP data that can't be parsed and QR
+    /// codes with too much text
+    pub fn size(&self) -> Size {
+        match *self {
+            Graphic::Bmp(data) => Bmp::<BinaryColor>::from_slice(data)
+                .map(|bmp| bmp.size())
+                .unwrap_or_default(),
+            Graphic::Raw { data, width } => {
+                let row_bytes = width.div_ceil(8).max(1);
+                Size::new(width, data.len() as u32 / row_bytes)
+            }
+            Graphic::Qr(data) => Self::qr_code(data)
+                .map(|qr| qr.bounding_box().size)
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Draw the image with its top left corner at `at`
+    ///
+    /// BMP data that can't be parsed and QR codes with too much text are
+    /// skipped.
+    pub fn draw_at<D>(&self, at: Point, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        match *self {
+            Graphic::Bmp(data) => match Bmp::<BinaryColor>::from_slice(data) {
+                Ok(bmp) => Image::new(&bmp, at).draw(target),
+                Err(_) => Ok(()),
+            },
+            Graphic::Raw { data, width } => {
+                let raw = ImageRaw::<BinaryColor>::new(data, width);
+                Image::new(&raw, at).draw(target)
+            }
+            Graphic::Qr(data) => match Self::qr_code(data) {
+                Some(qr) => qr.translate(at).draw(target),
+                None => Ok(()),
+            },
+        }
+    }
+}
+
+/// How the parts of a badge are arranged
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Layout {
+    /// Avatar on the left, logo on the right and the name centered over them
+    Logo,
+    /// Avatar on the left, name on top and two QR codes next to each other
+    Qr,
+    /// Avatar on the left, name in the middle and a QR code of the contact
+    /// details as large as fits on the right
+    Contact(CardFormat),
+}
+
+/// Everything that goes on an ID badge
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BadgeSpec<'a> {
+    pub name: &'a str,
+    pub nickname: Option<&'a str>,
+    pub title: Option<&'a str>,
+    pub avatar: Option<Graphic<'a>>,
+    pub qr_codes: [Option<Graphic<'a>>; 2],
+    pub logo: Option<Graphic<'a>>,
+    pub organisation: Option<&'a str>,
+    pub email: Option<&'a str>,
+    pub phone: Option<&'a str>,
+    pub url: Option<&'a str>,
+    pub layout: Layout,
+}
+
+impl<'a> BadgeSpec<'a> {
+    /// Contact details for a contact card
+    pub fn contact(&self) -> Contact<'a> {
+        Contact {
+            name: self.name,
+            nickname: self.nickname,
+            organisation: self.organisation,
+            email: self.email,
+            phone: self.phone,
+            url: self.url,
+        }
+    }
+
+    /// Name, quoted nickname and title, one per line
+    fn text(&self) -> String<128> {
+        let mut text = String::new();
+        // Text that doesn't fit is cut off
+        let _ = write!(text, "{}", self.name);
+        if let Some(nickname) = self.nickname {
+            let _ = write!(text, "\n'{}'", nickname);
+        }
+        if let Some(title) = self.title {
+            let _ = write!(text, "\n{}", title);
+        }
+        text
+    }
+}
+
+impl Drawable for BadgeSpec<'_> {
+    type Color = BinaryColor;
+    type Output = ();
+
+    fn draw<D>(&self, target: &mut D) -> Result<Self::Output, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let style_black = MonoTextStyle::new(&FONT_10X20, BinaryColor::Off);
+
+        let area = target.bounding_box();
+        let size = |graphic: Option<Graphic>| graphic.map(|g| g.size()).unwrap_or_default();
+
+        let lines = self.text();
+        let text = Text::with_alignment(&lines, Point::zero(), style_black, Alignment::Center);
+        let text = match self.layout {
+            Layout::Logo => {
+                let [avatar, _, logo] = HStack::new([
+                    Length::Content(size(self.avatar)),
+                    Length::Fraction(1),
+                    Length::Content(size(self.logo)),
+                ])
+                .layout(&area);
+                if let Some(graphic) = self.avatar {
+                    graphic.draw_at(avatar.top_left, target)?;
+                }
+                if let Some(graphic) = self.logo {
+                    graphic.draw_at(logo.top_left, target)?;
+                }
+                Some(text.align(&area, AnchorPoint::Center))
+            }
+            Layout::Qr => {
+                // Text over the QR codes only, so that a logo in the avatar
+                // stays "complete"
+                let [avatar, right] =
+                    HStack::new([Length::Content(size(self.avatar)), Length::Fraction(1)])
+                        .spacing(8)
+                        .layout(&area);
+                let [text_slot, qr_slot] = VStack::new([Length::fit(&text), Length::Fraction(1)])
+                    .padding(Insets::symmetric(0, 6))
+                    .layout(&right);
+                if let Some(graphic) = self.avatar {
+                    graphic.draw_at(avatar.top_left, target)?;
+                }
+                let cells = HStack::new([Length::Fraction(1); 2]).layout(&qr_slot);
+                for (qr, cell) in self.qr_codes.iter().zip(cells) {
+                    if let Some(qr) = qr {
+                        let at = Rectangle::new(Point::zero(), qr.size())
+                            .align(&cell, AnchorPoint::Center)
+                            .top_left;
+                        qr.draw_at(at, target)?;
+                    }
+                }
+                Some(text.align(&text_slot, AnchorPoint::Center))
+            }
+            Layout::Contact(format) => {
+                // The display is crisp, low error correction keeps the
+                // modules large
+                let square = Rectangle::new(Point::zero(), Size::new_equal(area.size.height));
+                let qr = self
+                    .contact()
+                    .qr_code(format, EcLevel::L)
+                    .ok()
+                    .map(|qr| qr.with_quiet_zone(1).fit_into(&square));
+                let qr_size = qr.map(|qr| qr.bounding_box().size).unwrap_or_default();
+                let [avatar, name, code] = HStack::new([
+                    Length::Content(size(self.avatar)),
+                    Length::Fraction(1),
+                    Length::Content(qr_size),
+                ])
+                .spacing(4)
+                .layout(&area);
+                if let Some(graphic) = self.avatar {
+                    graphic.draw_at(avatar.top_left, target)?;
+                }
+                if let Some(qr) = qr {
+                    qr.align(&code, AnchorPoint::Center).draw(target)?;
+                }
+                TextBox::new(&lines, name, &CONTACT_FONTS).draw(target)?;
+                None
+            }
+        };
+
+        // Clear around the text so that it stays readable over the images
+        if let Some(text) = text {
+            let style = BoxStyle {
+                border: 0,
+                padding: Insets::all(6),
+                ..BoxStyle::default()
+            };
+            Boxed::new(text, style).draw(target)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "sim"))]
+mod tests {
+    use super::badges::{DIST, HASANEN, ZOKOL};
+    use super::*;
+    use crate::sim::SimDisplay;
+
+    fn assert_golden(badge: &BadgeSpec, path: &str) {
+        let mut display = SimDisplay::new();
+        badge.draw(&mut display).unwrap();
+        display.assert_golden(path);
+    }
+
+    #[test]
+    fn dist() {
+        assert_golden(&DIST, "tests/golden/dist.png");
+    }
+
+    #[test]
+    fn hasanen() {
+        assert_golden(&HASANEN, "tests/golden/hasanen.png");
+    }
+
+    #[test]
+    fn zokol() {
+        assert_golden(&ZOKOL, "tests/golden/zokol.png");
+    }
+
+    #[test]
+    fn contact() {
+        let badge = BadgeSpec {
+            title: Some("Hacker"),
+            email: Some("zokol@example.com"),
+            url: Some("https://example.com"),
+            layout: Layout::Contact(CardFormat::MeCard),
+            ..ZOKOL
+        };
+        assert_golden(&badge, "tests/golden/contact.png");
+    }
+}
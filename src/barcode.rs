@@ -0,0 +1,35 @@
+//! Barcodes for tickets and asset tags
+//!
+//! [`Code128`] and [`Ean13`] are linear barcodes with their text printed
+//! under the bars, [`DataMatrix`] is a small square code that fits the
+//! display height better than a QR code. All of them are drawn with whole
+//! pixels per module so that the bars stay crisp on e-ink.
+//!
+//! ```ignore
+//! Code128::encode("KTC-0042")?
+//!     .fit_into(&area)
+//!     .draw(&mut display)?;
+//! ```
+
+mod code128;
+mod data_matrix;
+mod ean13;
+mod linear;
+
+pub use code128::Code128;
+pub use data_matrix::DataMatrix;
+pub use ean13::Ean13;
+pub use linear::LinearStyle;
+
+/// Why data couldn't be encoded
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Too much data for the largest supported code
+    TooLong,
+    /// A character the code can't represent
+    InvalidCharacter,
+    /// Wrong number of digits
+    Length,
+    /// The check digit doesn't match the other digits
+    CheckDigit,
+}
@@ -0,0 +1,290 @@
+//! Code 128, for printable ASCII like ticket and asset numbers
+
+use embedded_graphics::{
+    geometry::AnchorPoint,
+    mono_font::MonoTextStyle,
+    pixelcolor::BinaryColor,
+    prelude::*,
+    primitives::Rectangle,
+    text::{Alignment, Baseline, Text, TextStyleBuilder},
+};
+use heapless::Vec;
+
+use super::linear::{draw_bars, Bars, LinearStyle};
+use super::Error;
+use crate::graphics_extensions::Align;
+
+/// Bar and space widths of the symbols, the stop symbol has a final bar
+const PATTERNS: [&[u8]; 107] = [
+    b"212222", b"222122", b"222221", b"121223", b"121322", b"131222", b"122213", b"122312",
+    b"132212", b"221213", b"221312", b"231212", b"112232", b"122132", b"122231", b"113222",
+    b"123122", b"123221", b"223211", b"221132", b"221231", b"213212", b"223112", b"312131",
+    b"311222", b"321122", b"321221", b"312212", b"322112", b"322211", b"212123", b"212321",
+    b"232121", b"111323", b"131123", b"131321", b"112313", b"132113", b"132311", b"211313",
+    b"231113", b"231311", b"112133", b"112331", b"132131", b"113123", b"113321", b"133121",
+    b"313121", b"211331", b"231131", b"213113", b"213311", b"213131", b"311123", b"311321",
+    b"331121", b"312113", b"312311", b"332111", b"314111", b"221411", b"431111", b"111224",
+    b"111422", b"121124", b"121421", b"141122", b"141221", b"112214", b"112412", b"122114",
+    b"122411", b"142112", b"142211", b"241211", b"221114", b"413111", b"241112", b"134111",
+    b"111242", b"121142", b"121241", b"114212", b"124112", b"124211", b"411212", b"421112",
+    b"421211", b"212141", b"214121", b"412121", b"111143", b"111341", b"131141", b"114113",
+    b"114311", b"411113", b"411311", b"113141", b"114131", b"311141", b"411131", b"211412",
+    b"211214", b"211232", b"2331112",
+];
+
+const CODE_C: u8 = 99;
+const CODE_B: u8 = 100;
+const START_B: u8 = 104;
+const START_C: u8 = 105;
+const STOP: u8 = 106;
+
+/// Symbols in the longest code, including start, check and stop
+const MAX_SYMBOLS: usize = 64;
+
+/// Modules of white on each side
+const QUIET_ZONE: u32 = 10;
+
+/// A Code 128 barcode of printable ASCII with the text under it
+///
+/// Runs of digits are packed two to a symbol, so numbers take about half the
+/// width of letters.
+#[derive(Clone, Copy, Debug)]
+pub struct Code128<'a> {
+    text: &'a str,
+    bars: Bars,
+    top_left: Point,
+    style: LinearStyle<'a>,
+}
+
+/// Length of the run of digits at the start of `bytes`
+fn digits(bytes: &[u8]) -> usize {
+    bytes.iter().take_while(|b| b.is_ascii_digit()).count()
+}
+
+fn push(symbols: &mut Vec<u8, MAX_SYMBOLS>, symbol: u8) -> Result<(), Error> {
+    symbols.push(symbol).map_err(|_| Error::TooLong)
+}
+
+/// Symbol values for `text`, in code set B with runs of digits in code set C
+// `usize::is_multiple_of` would need Rust 1.87
+#[allow(clippy::manual_is_multiple_of)]
+fn symbols(text: &str) -> Result<Vec<u8, MAX_SYMBOLS>, Error> {
+    let bytes = text.as_bytes();
+    if bytes.iter().any(|b| !(b' '..=b'~').contains(b)) {
+        return Err(Error::InvalidCharacter);
+    }
+
+    // Code set C pays off for four digits at the ends, six in the middle or
+    // text of just two digits
+    let worth_c = |at: usize, run: usize| {
+        let at_end = at == 0 || at + run == bytes.len();
+        run >= 6 || (run >= 4 && at_end) || (run == 2 && run == bytes.len())
+    };
+
+    let mut symbols = Vec::new();
+    let mut in_c = worth_c(0, digits(bytes));
+    push(&mut symbols, if in_c { START_C } else { START_B })?;
+    let mut i = 0;
+    while i < bytes.len() {
+        let run = digits(&bytes[i..]);
+        if in_c && run >= 2 {
+            push(&mut symbols, (bytes[i] - b'0') * 10 + bytes[i + 1] - b'0')?;
+            i += 2;
+        } else if in_c {
+            push(&mut symbols, CODE_B)?;
+            in_c = false;
+        } else if worth_c(i, run) && run % 2 == 0 {
+            push(&mut symbols, CODE_C)?;
+            in_c = true;
+        } else {
+            // An odd digit before a switch to code set C goes in code set B
+            push(&mut symbols, bytes[i] - b' ')?;
+            i += 1;
+        }
+    }
+
+    let check = symbols.iter().enumerate().fold(0, |sum, (i, &symbol)| {
+        (sum + i.max(1) as u32 * symbol as u32) % 103
+    });
+    push(&mut symbols, check as u8)?;
+    push(&mut symbols, STOP)?;
+    Ok(symbols)
+}
+
+impl<'a> Code128<'a> {
+    /// Encode `text`, letters, digits and punctuation from space to `~`
+    pub fn encode(text: &'a str) -> Result<Self, Error> {
+        let mut bars = Bars::default();
+        for symbol in symbols(text)? {
+            bars.push_widths(PATTERNS[symbol as usize])?;
+        }
+        Ok(Self {
+            text,
+            bars,
+            top_left: Point::zero(),
+            style: LinearStyle::default(),
+        })
+    }
+
+    #[must_use]
+    pub fn with_style(self, style: LinearStyle<'a>) -> Self {
+        Self { style, ..self }
+    }
+
+    /// The widest bars and the highest code that fit in `area`, centered in
+    /// it
+    #[must_use]
+    pub fn fit_into(self, area: &Rectangle) -> Self {
+        let style = self.style.fit(self.modules(), area);
+        self.with_style(style).align(area, AnchorPoint::Center)
+    }
+
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+
+    /// Width in modules, including the quiet zones
+    pub fn modules(&self) -> u32 {
+        self.bars.modules() + 2 * QUIET_ZONE
+    }
+}
+
+impl Dimensions for Code128<'_> {
+    fn bounding_box(&self) -> Rectangle {
+        Rectangle::new(
+            self.top_left,
+            Size::new(
+                self.modules() * self.style.module_width,
+                self.style.height + self.style.text_height(),
+            ),
+        )
+    }
+}
+
+impl Transform for Code128<'_> {
+    fn translate(&self, by: Point) -> Self {
+        Self {
+            top_left: self.top_left + by,
+            ..*self
+        }
+    }
+
+    fn translate_mut(&mut self, by: Point) -> &mut Self {
+        self.top_left += by;
+        self
+    }
+}
+
+impl Drawable for Code128<'_> {
+    type Color = BinaryColor;
+    type Output = ();
+
+    fn draw<D>(&self, target: &mut D) -> Result<Self::Output, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let area = self.bounding_box();
+        let bars = Rectangle::new(area.top_left, Size::new(area.size.width, self.style.height));
+        draw_bars(
+            target,
+            &bars,
+            self.style.module_width,
+            QUIET_ZONE,
+            |module| self.bars.get(module),
+        )?;
+
+        if let Some(font) = self.style.font {
+            let style = TextStyleBuilder::new()
+                .alignment(Alignment::Center)
+                .baseline(Baseline::Bottom)
+                .build();
+            let at = area.anchor_point(AnchorPoint::BottomCenter);
+            Text::with_text_style(
+                self.text,
+                at,
+                MonoTextStyle::new(font, BinaryColor::Off),
+                style,
+            )
+            .draw(target)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bars(code: &Code128) -> String {
+        (0..code.bars.modules() as i32)
+            .map(|module| if code.bars.get(module) { '#' } else { '.' })
+            .collect()
+    }
+
+    #[test]
+    fn patterns_are_valid() {
+        for (value, pattern) in PATTERNS.iter().enumerate() {
+            let widths = pattern.iter().map(|width| (width - b'0') as u32);
+            let bars: u32 = widths.clone().step_by(2).sum();
+            let total: u32 = widths.sum();
+            let expected = if value == STOP as usize { 13 } else { 11 };
+            assert_eq!(total, expected, "symbol {value}");
+            assert_eq!(bars % 2, 0, "symbol {value}");
+        }
+    }
+
+    #[test]
+    fn code_set_c() {
+        assert_eq!(symbols("123456").unwrap(), [START_C, 12, 34, 56, 44, STOP]);
+        // Start C, 12, 34, 56, check 44 and stop
+        assert_eq!(
+            bars(&Code128::encode("123456").unwrap()),
+            "##.#..###..#.##..###..#...#.##...###...#.##.#...##.###.##...###.#.##"
+        );
+    }
+
+    #[test]
+    fn code_set_b_with_digits() {
+        // Four digits at the end are worth switching to code set C
+        assert_eq!(
+            symbols("KTC-0042").unwrap(),
+            [START_B, 43, 52, 35, 13, CODE_C, 0, 42, 64, STOP]
+        );
+        // An odd run starts in code set B
+        assert_eq!(
+            symbols("A12345").unwrap(),
+            [START_B, 33, 17, CODE_C, 23, 45, 64, STOP]
+        );
+        // Six digits in the middle switch to code set C and back
+        assert_eq!(
+            symbols("A123456B").unwrap(),
+            [START_B, 33, CODE_C, 12, 34, 56, CODE_B, 34, 80, STOP]
+        );
+        assert_eq!(
+            symbols("A1234B").unwrap(),
+            [START_B, 33, 17, 18, 19, 20, 34, 90, STOP]
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            Code128::encode("Hyvää").map(|code| code.modules()),
+            Err(Error::InvalidCharacter)
+        );
+        assert_eq!(
+            Code128::encode("line\nbreak").map(|code| code.modules()),
+            Err(Error::InvalidCharacter)
+        );
+        let long = "A".repeat(MAX_SYMBOLS - 3);
+        assert_eq!(
+            Code128::encode(&long).unwrap().modules(),
+            11 * MAX_SYMBOLS as u32 + 2 + 2 * QUIET_ZONE
+        );
+        let long = "A".repeat(MAX_SYMBOLS - 2);
+        assert_eq!(
+            Code128::encode(&long).map(|code| code.modules()),
+            Err(Error::TooLong)
+        );
+    }
+}
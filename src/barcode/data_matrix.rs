@@ -0,0 +1,515 @@
+//! Data Matrix ECC 200, square codes up to 26x26 modules
+
+use embedded_graphics::{
+    geometry::AnchorPoint, pixelcolor::BinaryColor, prelude::*, primitives::Rectangle,
+};
+
+use super::Error;
+use crate::graphics_extensions::Align;
+
+/// Symbol sizes with data and error correction codewords, all with a single
+/// data region
+const SIZES: [(usize, usize, usize); 9] = [
+    (10, 3, 5),
+    (12, 5, 7),
+    (14, 8, 10),
+    (16, 12, 12),
+    (18, 18, 14),
+    (20, 22, 18),
+    (22, 30, 20),
+    (24, 36, 24),
+    (26, 44, 28),
+];
+
+const MAX_SIZE: usize = 26;
+const MAX_CODEWORDS: usize = 44 + 28;
+
+/// Pad codeword after the data
+const PAD: u8 = 129;
+/// Next codeword is the byte 128 higher
+const UPPER_SHIFT: u8 = 235;
+
+/// Multiply in GF(256) with the Data Matrix polynomial
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    while b != 0 {
+        if b & 1 != 0 {
+            product ^= a;
+        }
+        a = (a << 1) ^ if a & 0x80 != 0 { 0x2d } else { 0 };
+        b >>= 1;
+    }
+    product
+}
+
+/// Reed-Solomon error correction of `data` into `ecc`, with a generator whose
+/// roots start from the first power of 2
+fn reed_solomon(data: &[u8], ecc: &mut [u8]) {
+    // Generator coefficients, highest power first without the leading 1
+    let mut generator = [0u8; 28];
+    let generator = &mut generator[..ecc.len()];
+    generator[ecc.len() - 1] = 1;
+    let mut root = 1;
+    for _ in 0..ecc.len() {
+        root = gf_mul(root, 2);
+        for j in 0..generator.len() {
+            let next = generator.get(j + 1).copied().unwrap_or(0);
+            generator[j] = gf_mul(generator[j], root) ^ next;
+        }
+    }
+
+    ecc.fill(0);
+    for &byte in data {
+        let factor = byte ^ ecc[0];
+        ecc.rotate_left(1);
+        ecc[ecc.len() - 1] = 0;
+        for (e, &g) in ecc.iter_mut().zip(generator.iter()) {
+            *e ^= gf_mul(g, factor);
+        }
+    }
+}
+
+/// `data` in ASCII encodation, digits packed in pairs
+fn encode_ascii(data: &[u8], out: &mut [u8; MAX_CODEWORDS]) -> Result<usize, Error> {
+    let mut n = 0;
+    let mut push = |codeword: u8| -> Result<(), Error> {
+        *out.get_mut(n).ok_or(Error::TooLong)? = codeword;
+        n += 1;
+        Ok(())
+    };
+    let mut i = 0;
+    while i < data.len() {
+        match (data[i], data.get(i + 1)) {
+            (a @ b'0'..=b'9', Some(&b @ b'0'..=b'9')) => {
+                push(130 + (a - b'0') * 10 + (b - b'0'))?;
+                i += 1;
+            }
+            (byte @ 0..=127, _) => push(byte + 1)?,
+            (byte, _) => {
+                push(UPPER_SHIFT)?;
+                push(byte - 127)?;
+            }
+        }
+        i += 1;
+    }
+    Ok(n)
+}
+
+/// Codewords wrapped around the corners, placed when the diagonal sweep
+/// reaches them, rows and columns negative from the bottom and right
+const CORNERS: [[(i32, i32); 8]; 4] = [
+    [
+        (-1, 0),
+        (-1, 1),
+        (-1, 2),
+        (0, -2),
+        (0, -1),
+        (1, -1),
+        (2, -1),
+        (3, -1),
+    ],
+    [
+        (-3, 0),
+        (-2, 0),
+        (-1, 0),
+        (0, -4),
+        (0, -3),
+        (0, -2),
+        (0, -1),
+        (1, -1),
+    ],
+    [
+        (-3, 0),
+        (-2, 0),
+        (-1, 0),
+        (0, -2),
+        (0, -1),
+        (1, -1),
+        (2, -1),
+        (3, -1),
+    ],
+    [
+        (-1, 0),
+        (-1, -1),
+        (0, -3),
+        (0, -2),
+        (0, -1),
+        (1, -3),
+        (1, -2),
+        (1, -1),
+    ],
+];
+
+/// Where the bits of the codewords go in the data region, from the ECC 200
+/// placement algorithm
+struct Placement {
+    size: i32,
+    /// Codeword and bit placed at each module, bit 0 being the most
+    /// significant
+    modules: [[Option<(u8, u8)>; MAX_SIZE - 2]; MAX_SIZE - 2],
+}
+
+impl Placement {
+    fn new(size: usize) -> Self {
+        let mut placement = Self {
+            size: size as i32,
+            modules: [[None; MAX_SIZE - 2]; MAX_SIZE - 2],
+        };
+        placement.place();
+        placement
+    }
+
+    fn module(&mut self, mut row: i32, mut col: i32, codeword: u8, bit: u8) {
+        let size = self.size;
+        if row < 0 {
+            row += size;
+            col += 4 - ((size + 4) % 8);
+        }
+        if col < 0 {
+            col += size;
+            row += 4 - ((size + 4) % 8);
+        }
+        self.modules[row as usize][col as usize] = Some((codeword, bit));
+    }
+
+    /// The usual L shaped codeword with its last bit at `row`, `col`
+    fn utah(&mut self, row: i32, col: i32, codeword: u8) {
+        let bits = [
+            (-2, -2),
+            (-2, -1),
+            (-1, -2),
+            (-1, -1),
+            (-1, 0),
+            (0, -2),
+            (0, -1),
+            (0, 0),
+        ];
+        for (bit, (dr, dc)) in bits.into_iter().enumerate() {
+            self.module(row + dr, col + dc, codeword, bit as u8);
+        }
+    }
+
+    /// A codeword wrapped around the corners
+    fn corner(&mut self, codeword: u8, bits: [(i32, i32); 8]) {
+        let size = self.size;
+        for (bit, (row, col)) in bits.into_iter().enumerate() {
+            let row = if row < 0 { size + row } else { row };
+            let col = if col < 0 { size + col } else { col };
+            self.module(row, col, codeword, bit as u8);
+        }
+    }
+
+    fn is_free(&self, row: i32, col: i32) -> bool {
+        self.modules[row as usize][col as usize].is_none()
+    }
+
+    fn place(&mut self) {
+        let size = self.size;
+        let (mut codeword, mut row, mut col) = (0u8, 4, 0);
+        loop {
+            let corners = [
+                row == size && col == 0,
+                row == size - 2 && col == 0 && size % 4 != 0,
+                row == size - 2 && col == 0 && size % 8 == 4,
+                row == size + 4 && col == 2 && size % 8 == 0,
+            ];
+            for (bits, _) in CORNERS.into_iter().zip(corners).filter(|(_, at)| *at) {
+                self.corner(codeword, bits);
+                codeword += 1;
+            }
+
+            // Up and to the right, then down and to the left
+            loop {
+                if row < size && col >= 0 && self.is_free(row, col) {
+                    self.utah(row, col, codeword);
+                    codeword += 1;
+                }
+                row -= 2;
+                col += 2;
+                if row < 0 || col >= size {
+                    break;
+                }
+            }
+            row += 1;
+            col += 3;
+            loop {
+                if row >= 0 && col < size && self.is_free(row, col) {
+                    self.utah(row, col, codeword);
+                    codeword += 1;
+                }
+                row += 2;
+                col -= 2;
+                if row >= size || col < 0 {
+                    break;
+                }
+            }
+            row += 3;
+            col += 1;
+
+            if row >= size && col >= size {
+                break;
+            }
+        }
+    }
+}
+
+/// A Data Matrix ECC 200 code, drawn as a square of dark and light modules
+///
+/// Smaller than a QR code for the same data, a 26x26 code holds 44 letters
+/// or 88 digits. By default every module is one pixel and the code has the
+/// one module wide quiet zone the standard asks for.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct DataMatrix {
+    /// Rows of modules including the finder pattern, bit `x` is column `x`
+    rows: [u32; MAX_SIZE],
+    size: u32,
+    top_left: Point,
+    module_size: u32,
+    quiet_zone: u32,
+    inverted: bool,
+}
+
+impl DataMatrix {
+    /// Encode `data` in the smallest square code it fits
+    pub fn encode(data: &[u8]) -> Result<Self, Error> {
+        let mut codewords = [0; MAX_CODEWORDS];
+        let len = encode_ascii(data, &mut codewords)?;
+        let &(size, data_len, ecc_len) = SIZES
+            .iter()
+            .find(|&&(_, data_len, _)| len <= data_len)
+            .ok_or(Error::TooLong)?;
+
+        // Pad codewords are scrambled by their position so that they don't
+        // repeat
+        for (i, codeword) in codewords.iter_mut().enumerate().take(data_len).skip(len) {
+            *codeword = if i == len {
+                PAD
+            } else {
+                let pad = PAD as usize + (149 * (i + 1)) % 253 + 1;
+                (if pad > 254 { pad - 254 } else { pad }) as u8
+            };
+        }
+        let (data, ecc) = codewords.split_at_mut(data_len);
+        reed_solomon(data, &mut ecc[..ecc_len]);
+
+        let placement = Placement::new(size - 2);
+        let mut rows = [0; MAX_SIZE];
+        for (y, row) in rows.iter_mut().enumerate().take(size) {
+            for x in 0..size {
+                let dark = if x == 0 || y == size - 1 {
+                    // Solid finder lines on the left and bottom
+                    true
+                } else if y == 0 {
+                    // Alternating clock track on the top and right
+                    x % 2 == 0
+                } else if x == size - 1 {
+                    y % 2 == 1
+                } else {
+                    match placement.modules[y - 1][x - 1] {
+                        Some((i, bit)) => codewords[i as usize] & (0x80 >> bit) != 0,
+                        // The four unused modules in the bottom right corner
+                        // of some sizes
+                        None => (x + y) % 2 == 0,
+                    }
+                };
+                *row |= (dark as u32) << x;
+            }
+        }
+
+        Ok(Self {
+            rows,
+            size: size as u32,
+            top_left: Point::zero(),
+            module_size: 1,
+            quiet_zone: 1,
+            inverted: false,
+        })
+    }
+
+    /// Modules on a side, without the quiet zone
+    pub fn modules(&self) -> u32 {
+        self.size
+    }
+
+    /// Returns true if the module at `x`, `y` is dark, modules outside the
+    /// code are light
+    pub fn module(&self, x: i32, y: i32) -> bool {
+        let size = self.size as i32;
+        (0..size).contains(&x) && (0..size).contains(&y) && self.rows[y as usize] & (1 << x) != 0
+    }
+
+    /// Pixels per module
+    #[must_use]
+    pub fn with_module_size(self, module_size: u32) -> Self {
+        Self {
+            module_size: module_size.max(1),
+            ..self
+        }
+    }
+
+    /// Light modules around the code
+    #[must_use]
+    pub fn with_quiet_zone(self, quiet_zone: u32) -> Self {
+        Self { quiet_zone, ..self }
+    }
+
+    /// Draw dark modules white and light ones black, for codes on black
+    #[must_use]
+    pub fn with_inverted(self, inverted: bool) -> Self {
+        Self { inverted, ..self }
+    }
+
+    /// Pixels on a side, including the quiet zone
+    pub fn pixels(&self) -> u32 {
+        (self.size + 2 * self.quiet_zone) * self.module_size
+    }
+
+    /// The largest module size that fits in `area`, centered in it
+    #[must_use]
+    pub fn fit_into(self, area: &Rectangle) -> Self {
+        let side = area.size.width.min(area.size.height);
+        let module_size = side / (self.size + 2 * self.quiet_zone);
+        self.with_module_size(module_size)
+            .align(area, AnchorPoint::Center)
+    }
+}
+
+/// Draws the code as text, `#` is a dark module
+impl core::fmt::Debug for DataMatrix {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        writeln!(f, "DataMatrix {}x{}", self.size, self.size)?;
+        for y in 0..self.size as i32 {
+            for x in 0..self.size as i32 {
+                f.write_str(if self.module(x, y) { "##" } else { "  " })?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Dimensions for DataMatrix {
+    fn bounding_box(&self) -> Rectangle {
+        Rectangle::new(self.top_left, Size::new_equal(self.pixels()))
+    }
+}
+
+impl Transform for DataMatrix {
+    fn translate(&self, by: Point) -> Self {
+        Self {
+            top_left: self.top_left + by,
+            ..*self
+        }
+    }
+
+    fn translate_mut(&mut self, by: Point) -> &mut Self {
+        self.top_left += by;
+        self
+    }
+}
+
+impl Drawable for DataMatrix {
+    type Color = BinaryColor;
+    type Output = ();
+
+    fn draw<D>(&self, target: &mut D) -> Result<Self::Output, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let area = self.bounding_box();
+        let module_size = self.module_size as i32;
+        let quiet_zone = self.quiet_zone as i32;
+        let colors = area.points().map(|point| {
+            let offset = point - self.top_left;
+            let x = offset.x / module_size - quiet_zone;
+            let y = offset.y / module_size - quiet_zone;
+            // Dark modules are black
+            BinaryColor::from(self.module(x, y) == self.inverted)
+        });
+        target.fill_contiguous(&area, colors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Reference symbols made with the placement algorithm of ISO/IEC 16022
+    // Annex F, `#` is a dark module
+
+    /// The example of the standard, "123456"
+    const DIGITS_10X10: [&str; 10] = [
+        "#.#.#.#.#.",
+        "##..#.##.#",
+        "##.....#..",
+        "##...###.#",
+        "##....#...",
+        "#.....####",
+        "###.##....",
+        "####.##..#",
+        "#..###.#..",
+        "##########",
+    ];
+
+    /// "KTC-0042", with pad codewords
+    const ASSET_14X14: [&str; 14] = [
+        "#.#.#.#.#.#.#.",
+        "#.##..#.#.####",
+        "#.#.#.##..##..",
+        "##.#......#..#",
+        "###..#.##.##..",
+        "#.#.##..##...#",
+        "#.#...#..#.##.",
+        "#..#.....#..##",
+        "#.#.##..#.#...",
+        "#.###.##.###.#",
+        "#..#.#..##..#.",
+        "#..##.#.##...#",
+        "#..#..........",
+        "##############",
+    ];
+
+    fn rows(code: &DataMatrix) -> Vec<String> {
+        let size = code.modules() as i32;
+        (0..size)
+            .map(|y| {
+                (0..size)
+                    .map(|x| if code.module(x, y) { '#' } else { '.' })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn ascii_encodation() {
+        let mut codewords = [0; MAX_CODEWORDS];
+        let len = encode_ascii(b"123456", &mut codewords).unwrap();
+        assert_eq!(codewords[..len], [142, 164, 186]);
+        let len = encode_ascii("A1\u{e9}".as_bytes(), &mut codewords).unwrap();
+        assert_eq!(codewords[..len], [66, 50, UPPER_SHIFT, 68, UPPER_SHIFT, 42]);
+    }
+
+    #[test]
+    fn error_correction_of_the_standard_example() {
+        let mut ecc = [0; 5];
+        reed_solomon(&[142, 164, 186], &mut ecc);
+        assert_eq!(ecc, [114, 25, 5, 88, 102]);
+    }
+
+    #[test]
+    fn reference_symbols() {
+        assert_eq!(rows(&DataMatrix::encode(b"123456").unwrap()), DIGITS_10X10);
+        assert_eq!(rows(&DataMatrix::encode(b"KTC-0042").unwrap()), ASSET_14X14);
+    }
+
+    #[test]
+    fn sizes() {
+        let size = |data: &[u8]| DataMatrix::encode(data).map(|code| code.modules());
+        assert_eq!(size(b""), Ok(10));
+        assert_eq!(size(b"ABC"), Ok(10));
+        assert_eq!(size(b"ABCD"), Ok(12));
+        assert_eq!(size(&[b'7'; 88]), Ok(26));
+        assert_eq!(size(&[b'A'; 44]), Ok(26));
+        assert_eq!(size(&[b'A'; 45]), Err(Error::TooLong));
+    }
+}
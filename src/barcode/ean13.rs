@@ -0,0 +1,272 @@
+//! EAN-13, the product numbers on retail goods
+
+use embedded_graphics::{
+    geometry::AnchorPoint,
+    mono_font::MonoTextStyle,
+    pixelcolor::BinaryColor,
+    prelude::*,
+    primitives::Rectangle,
+    text::{Alignment, Baseline, Text, TextStyleBuilder},
+};
+
+use super::linear::{draw_bars, LinearStyle};
+use super::Error;
+use crate::graphics_extensions::Align;
+
+/// Bars of the digits on the left side with odd parity, the right side uses
+/// the complement and the left side with even parity its reverse
+const L_CODES: [u8; 10] = [
+    0b0001101, 0b0011001, 0b0010011, 0b0111101, 0b0100011, 0b0110001, 0b0101111, 0b0111011,
+    0b0110111, 0b0001011,
+];
+
+/// Parities of the left digits, set bits use even parity, picked by the
+/// first digit that has no bars of its own
+const PARITIES: [u8; 10] = [
+    0b000000, 0b001011, 0b001101, 0b001110, 0b010011, 0b011001, 0b011100, 0b010101, 0b010110,
+    0b011010,
+];
+
+/// Modules of the bars, from the start guard to the end guard
+const MODULES: u32 = 95;
+
+/// Modules of white on the left, wide enough for the first digit
+const QUIET_LEFT: u32 = 11;
+/// Modules of white on the right
+const QUIET_RIGHT: u32 = 7;
+
+/// An EAN-13 barcode with its digits under it
+///
+/// The guard bars at the start, middle and end reach down between the
+/// groups of digits as on printed products.
+#[derive(Clone, Copy, Debug)]
+pub struct Ean13<'a> {
+    /// ASCII digits including the check digit
+    digits: [u8; 13],
+    top_left: Point,
+    style: LinearStyle<'a>,
+}
+
+/// The check digit for the first 12 `digits`
+fn check_digit(digits: &[u8]) -> u8 {
+    let sum: u32 = digits[..12]
+        .iter()
+        .enumerate()
+        .map(|(i, digit)| (digit - b'0') as u32 * if i % 2 == 0 { 1 } else { 3 })
+        .sum();
+    b'0' + ((10 - sum % 10) % 10) as u8
+}
+
+impl<'a> Ean13<'a> {
+    /// Encode 12 digits and a check digit, which is calculated if left out
+    pub fn encode(digits: &str) -> Result<Self, Error> {
+        let bytes = digits.as_bytes();
+        if !bytes.iter().all(u8::is_ascii_digit) {
+            return Err(Error::InvalidCharacter);
+        }
+        if !(12..=13).contains(&bytes.len()) {
+            return Err(Error::Length);
+        }
+
+        let mut digits = [0; 13];
+        digits[..12].copy_from_slice(&bytes[..12]);
+        digits[12] = check_digit(bytes);
+        if bytes.get(12).is_some_and(|&check| check != digits[12]) {
+            return Err(Error::CheckDigit);
+        }
+        Ok(Self {
+            digits,
+            top_left: Point::zero(),
+            style: LinearStyle::default(),
+        })
+    }
+
+    #[must_use]
+    pub fn with_style(self, style: LinearStyle<'a>) -> Self {
+        Self { style, ..self }
+    }
+
+    /// The widest bars and the highest code that fit in `area`, centered in
+    /// it
+    #[must_use]
+    pub fn fit_into(self, area: &Rectangle) -> Self {
+        let style = self.style.fit(self.modules(), area);
+        self.with_style(style).align(area, AnchorPoint::Center)
+    }
+
+    /// All 13 digits
+    pub fn digits(&self) -> &str {
+        core::str::from_utf8(&self.digits).unwrap_or_default()
+    }
+
+    /// Width in modules, including the quiet zones
+    pub fn modules(&self) -> u32 {
+        QUIET_LEFT + MODULES + QUIET_RIGHT
+    }
+
+    /// Returns true if `module` is a bar
+    pub fn bar(&self, module: i32) -> bool {
+        let digit = |i: usize| (self.digits[i] - b'0') as usize;
+        // Bar `bit` of a 7 module digit, counted from the left
+        let bit = |code: u8, at: i32| code & (0x40 >> at) != 0;
+        match module {
+            // Guards, alternating bars and spaces
+            0..=2 | 45..=49 | 92..=94 => module % 2 == 0,
+            3..=44 => {
+                let (i, at) = ((module - 3) / 7, (module - 3) % 7);
+                let code = L_CODES[digit(1 + i as usize)];
+                if PARITIES[digit(0)] & (0x20 >> i) != 0 {
+                    // Even parity, the complement read backwards
+                    !bit(code, 6 - at)
+                } else {
+                    bit(code, at)
+                }
+            }
+            50..=91 => {
+                let (i, at) = ((module - 50) / 7, (module - 50) % 7);
+                !bit(L_CODES[digit(7 + i as usize)], at)
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns true if `module` is a guard bar that reaches into the text
+    fn guard(module: i32) -> bool {
+        matches!(module, 0..=2 | 45..=49 | 92..=94) && module % 2 == 0
+    }
+}
+
+impl Dimensions for Ean13<'_> {
+    fn bounding_box(&self) -> Rectangle {
+        Rectangle::new(
+            self.top_left,
+            Size::new(
+                self.modules() * self.style.module_width,
+                self.style.height + self.style.text_height(),
+            ),
+        )
+    }
+}
+
+impl Transform for Ean13<'_> {
+    fn translate(&self, by: Point) -> Self {
+        Self {
+            top_left: self.top_left + by,
+            ..*self
+        }
+    }
+
+    fn translate_mut(&mut self, by: Point) -> &mut Self {
+        self.top_left += by;
+        self
+    }
+}
+
+impl Drawable for Ean13<'_> {
+    type Color = BinaryColor;
+    type Output = ();
+
+    fn draw<D>(&self, target: &mut D) -> Result<Self::Output, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let area = self.bounding_box();
+        let module_width = self.style.module_width;
+        let bars = Rectangle::new(area.top_left, Size::new(area.size.width, self.style.height));
+        draw_bars(target, &bars, module_width, QUIET_LEFT, |module| {
+            self.bar(module)
+        })?;
+
+        let font = match self.style.font {
+            Some(font) => font,
+            None => return Ok(()),
+        };
+        let text_height = self.style.text_height();
+        let guards = Rectangle::new(
+            bars.anchor_point(AnchorPoint::BottomLeft) + Point::new(0, 1),
+            Size::new(area.size.width, text_height / 2),
+        );
+        draw_bars(target, &guards, module_width, QUIET_LEFT, Self::guard)?;
+
+        // The first digit in the quiet zone, the others centered between the
+        // guards
+        let style = TextStyleBuilder::new()
+            .alignment(Alignment::Center)
+            .baseline(Baseline::Bottom)
+            .build();
+        let font = MonoTextStyle::new(font, BinaryColor::Off);
+        let groups = [
+            (0..1, 0, QUIET_LEFT),
+            (1..7, QUIET_LEFT + 3, 42),
+            (7..13, QUIET_LEFT + 50, 42),
+        ];
+        for (digits, start, width) in groups {
+            let text = core::str::from_utf8(&self.digits[digits]).unwrap_or_default();
+            let group = Rectangle::new(
+                area.top_left + Point::new((start * module_width) as i32, 0),
+                Size::new(width * module_width, area.size.height),
+            );
+            Text::with_text_style(
+                text,
+                group.anchor_point(AnchorPoint::BottomCenter),
+                font,
+                style,
+            )
+            .draw(target)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bars(code: &Ean13) -> String {
+        (0..MODULES as i32)
+            .map(|module| if code.bar(module) { '#' } else { '.' })
+            .collect()
+    }
+
+    #[test]
+    fn check_digits() {
+        for digits in [
+            "5901234123457",
+            "4006381333931",
+            "9780306406157",
+            "0000000000000",
+        ] {
+            assert_eq!(Ean13::encode(&digits[..12]).unwrap().digits(), digits);
+            assert_eq!(Ean13::encode(digits).unwrap().digits(), digits);
+        }
+        assert_eq!(
+            Ean13::encode("5901234123450").map(|code| code.modules()),
+            Err(Error::CheckDigit)
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let error = |digits| Ean13::encode(digits).map(|code| code.modules());
+        assert_eq!(error("59012341234"), Err(Error::Length));
+        assert_eq!(error("59012341234570"), Err(Error::Length));
+        assert_eq!(error("59012341234a"), Err(Error::InvalidCharacter));
+    }
+
+    #[test]
+    fn reference_bars() {
+        // First digit 5, left digits with parities LGGLLG
+        assert_eq!(
+            bars(&Ean13::encode("5901234123457").unwrap()),
+            "#.#...#.##.#..###.##..##..#..##.####.#..###.#.#.#.##..##.##.##..\
+             #....#.#.###..#..###.#...#..#.#"
+        );
+        // First digit 4, left digits with parities LGLLGG
+        assert_eq!(
+            bars(&Ean13::encode("4006381333931").unwrap()),
+            "#.#...##.#.#..###.#.####.####.#...#..#.##..##.#.#.#....#.#....#.\
+             #....#.###.#..#....#.##..##.#.#"
+        );
+        assert!(!Ean13::encode("4006381333931").unwrap().bar(MODULES as i32));
+    }
+}
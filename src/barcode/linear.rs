@@ -0,0 +1,115 @@
+//! What Code 128 and EAN-13 have in common
+
+use embedded_graphics::{
+    mono_font::{ascii::FONT_6X10, MonoFont},
+    pixelcolor::BinaryColor,
+    prelude::*,
+    primitives::Rectangle,
+};
+
+use super::Error;
+
+/// Modules a [`Bars`] can hold, enough for a code wider than the display
+const MAX_MODULES: usize = 768;
+
+/// Pixels between the bars and the text
+const TEXT_GAP: u32 = 1;
+
+/// How a linear barcode is drawn
+#[derive(Clone, Copy, Debug)]
+pub struct LinearStyle<'a> {
+    /// Pixels per module, the width of the narrowest bar
+    pub module_width: u32,
+    /// Height of the bars
+    pub height: u32,
+    /// Font of the text under the bars, `None` for bars only
+    pub font: Option<&'a MonoFont<'a>>,
+}
+
+impl Default for LinearStyle<'_> {
+    fn default() -> Self {
+        Self {
+            module_width: 2,
+            height: 40,
+            font: Some(&FONT_6X10),
+        }
+    }
+}
+
+impl LinearStyle<'_> {
+    /// Height of the text and the gap above it
+    pub fn text_height(&self) -> u32 {
+        self.font
+            .map_or(0, |font| font.character_size.height + TEXT_GAP)
+    }
+
+    /// The largest module width and bar height that fit `modules` and the
+    /// text in `area`
+    pub fn fit(self, modules: u32, area: &Rectangle) -> Self {
+        Self {
+            module_width: (area.size.width / modules.max(1)).max(1),
+            height: area.size.height.saturating_sub(self.text_height()).max(1),
+            ..self
+        }
+    }
+}
+
+/// Modules of a linear barcode from left to right
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Bars {
+    words: [u32; MAX_MODULES / 32],
+    len: u32,
+}
+
+impl Bars {
+    /// Add `width` modules of bar or space
+    pub fn push(&mut self, bar: bool, width: u32) -> Result<(), Error> {
+        if self.len + width > MAX_MODULES as u32 {
+            return Err(Error::TooLong);
+        }
+        for i in self.len..self.len + width {
+            if bar {
+                self.words[i as usize / 32] |= 1 << (i % 32);
+            }
+        }
+        self.len += width;
+        Ok(())
+    }
+
+    /// Add alternating bars and spaces of `widths`, starting with a bar
+    pub fn push_widths(&mut self, widths: &[u8]) -> Result<(), Error> {
+        for (i, width) in widths.iter().enumerate() {
+            self.push(i % 2 == 0, (width - b'0') as u32)?;
+        }
+        Ok(())
+    }
+
+    pub fn modules(&self) -> u32 {
+        self.len
+    }
+
+    /// Returns true if `module` is a bar, modules past the end are spaces
+    pub fn get(&self, module: i32) -> bool {
+        (0..self.len as i32).contains(&module)
+            && self.words[module as usize / 32] & (1 << (module % 32)) != 0
+    }
+}
+
+/// Fill `area` with white and the modules for which `bar` returns true with
+/// black, module 0 starting `quiet_zone` modules from the left edge
+pub fn draw_bars<D>(
+    target: &mut D,
+    area: &Rectangle,
+    module_width: u32,
+    quiet_zone: u32,
+    bar: impl Fn(i32) -> bool,
+) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    let colors = area.points().map(|point| {
+        let module = (point.x - area.top_left.x) / module_width.max(1) as i32;
+        BinaryColor::from(!bar(module - quiet_zone as i32))
+    });
+    target.fill_contiguous(area, colors)
+}
@@ -0,0 +1,234 @@
+//! Board-level setup for the badger2040
+//!
+//! Brings up clocks, SIO, the SPI bus and the e-ink display and hands back the
+//! board peripherals as typed pins, so badges don't have to repeat the setup.
+
+use core::convert::Infallible;
+
+use embedded_graphics::draw_target::{Cropped, DrawTargetExt};
+use embedded_graphics::prelude::Dimensions;
+use embedded_hal::digital::v2::{InputPin, OutputPin};
+use fugit::{HertzU32, RateExtU32};
+use uc8151::{Uc8151, LUT};
+
+use crate::bsp::{self, hal, pac};
+use crate::buttons::{Button, Input};
+use crate::graphics_extensions::{Rotated, Rotation};
+use crate::power::{self, Battery, CurvePoint, WakeReason};
+use hal::clocks::{Clock, ClocksManager};
+
+/// SPI bus the display is connected to
+pub type DisplaySpi = hal::Spi<hal::spi::Enabled, pac::SPI0, 8>;
+
+/// The badger2040 e-ink display
+pub type Display = Uc8151<DisplaySpi, bsp::InkyCs, bsp::InkyDc, bsp::InkyBusy, bsp::InkyReset>;
+
+/// Board configuration used by [`Badger2040::new`]
+#[derive(Clone, Copy)]
+pub struct Config {
+    /// SPI clock for the display
+    pub spi_frequency: HertzU32,
+    /// Refresh speed the display is set up with
+    pub lut: LUT,
+    /// Discharge curve used for the battery percentage
+    pub battery_curve: &'static [CurvePoint],
+    /// How badges are meant to be held, [`Rotation::Deg90`] and
+    /// [`Rotation::Deg270`] for portrait badges
+    pub orientation: Rotation,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            spi_frequency: 10_000_000u32.Hz(),
+            lut: LUT::Normal,
+            battery_curve: power::LIPO,
+            orientation: Rotation::Deg0,
+        }
+    }
+}
+
+/// Errors that can happen while bringing up the board
+#[derive(Debug)]
+pub enum Error {
+    /// The peripherals have already been taken
+    AlreadyTaken,
+    /// Clock or PLL setup failed
+    Clocks,
+    /// Configuring the display failed
+    Display(uc8151::SpiDataError),
+}
+
+/// The front buttons and the user button on the back
+pub struct Buttons {
+    pub a: bsp::SwA,
+    pub b: bsp::SwB,
+    pub c: bsp::SwC,
+    pub up: bsp::SwUp,
+    pub down: bsp::SwDown,
+    pub user: bsp::UserSw,
+}
+
+/// Reads a pin through a reference, so differently typed pins can be read together
+struct PinRef<'a>(&'a dyn InputPin<Error = Infallible>);
+
+impl InputPin for PinRef<'_> {
+    type Error = Infallible;
+
+    fn is_high(&self) -> Result<bool, Self::Error> {
+        self.0.is_high()
+    }
+
+    fn is_low(&self) -> Result<bool, Self::Error> {
+        self.0.is_low()
+    }
+}
+
+impl Buttons {
+    /// The first button currently held down, see [`power::wake_reason`]
+    pub fn wake_reason(&self) -> WakeReason {
+        let inputs = [
+            Input::active_high(Button::A, PinRef(&self.a)),
+            Input::active_high(Button::B, PinRef(&self.b)),
+            Input::active_high(Button::C, PinRef(&self.c)),
+            Input::active_high(Button::Up, PinRef(&self.up)),
+            Input::active_high(Button::Down, PinRef(&self.down)),
+            Input::active_low(Button::User, PinRef(&self.user)),
+        ];
+        match power::wake_reason(&inputs) {
+            Ok(reason) => reason,
+            Err(never) => match never {},
+        }
+    }
+}
+
+/// The badger2040 board with all of its peripherals configured
+pub struct Badger2040 {
+    pub display: Display,
+    pub buttons: Buttons,
+    /// Button held down while the board powered up
+    pub wake_reason: WakeReason,
+    /// Orientation from [`Config::orientation`], see [`Badger2040::screen`]
+    pub orientation: Rotation,
+    /// Activity LED
+    pub led: bsp::Led,
+    /// Keeps the 3V3 rail on when running from battery
+    pub p3v3_en: bsp::P3v3En,
+    /// High when USB power is present
+    pub vbus_detect: bsp::VbusDetect,
+    pub battery: Battery,
+    pub delay: cortex_m::delay::Delay,
+    pub timer: hal::Timer,
+    pub watchdog: hal::Watchdog,
+    pub clocks: ClocksManager,
+}
+
+impl Badger2040 {
+    /// Take the peripheral singletons and set up the board
+    pub fn take(config: Config) -> Result<Self, Error> {
+        let pac = pac::Peripherals::take().ok_or(Error::AlreadyTaken)?;
+        let core = pac::CorePeripherals::take().ok_or(Error::AlreadyTaken)?;
+        Self::new(pac, core, config)
+    }
+
+    /// Set up the board from the given peripherals
+    pub fn new(
+        mut pac: pac::Peripherals,
+        core: pac::CorePeripherals,
+        config: Config,
+    ) -> Result<Self, Error> {
+        let mut watchdog = hal::Watchdog::new(pac.WATCHDOG);
+        let clocks = hal::clocks::init_clocks_and_plls(
+            bsp::XOSC_CRYSTAL_FREQ,
+            pac.XOSC,
+            pac.CLOCKS,
+            pac.PLL_SYS,
+            pac.PLL_USB,
+            &mut pac.RESETS,
+            &mut watchdog,
+        )
+        .map_err(|_| Error::Clocks)?;
+
+        let mut delay = cortex_m::delay::Delay::new(core.SYST, clocks.system_clock.freq().to_Hz());
+
+        let sio = hal::Sio::new(pac.SIO);
+
+        let pins = bsp::Pins::new(
+            pac.IO_BANK0,
+            pac.PADS_BANK0,
+            sio.gpio_bank0,
+            &mut pac.RESETS,
+        );
+
+        // Latch the 3V3 rail on before anything else, on battery the board
+        // is only powered while a button is held until this pin goes high
+        let mut p3v3_en: bsp::P3v3En = pins.p3v3_en.into_mode();
+        // Ignoring return value for set, RP2040 GPIO is infallible
+        let _ = p3v3_en.set_high();
+
+        // Sample the buttons right away, the display setup below takes long
+        // enough for a short wake-up press to be released already
+        let buttons = Buttons {
+            a: pins.sw_a.into_mode(),
+            b: pins.sw_b.into_mode(),
+            c: pins.sw_c.into_mode(),
+            up: pins.sw_up.into_mode(),
+            down: pins.sw_down.into_mode(),
+            user: pins.user_sw.into_mode(),
+        };
+
+        let wake_reason = buttons.wake_reason();
+
+        let _sclk: bsp::Sclk = pins.sclk.into_mode();
+        let _miso: bsp::Miso = pins.miso.into_mode();
+        let _mosi: bsp::Mosi = pins.mosi.into_mode();
+
+        let spi = hal::Spi::<_, _, 8>::new(pac.SPI0).init(
+            &mut pac.RESETS,
+            clocks.peripheral_clock.freq(),
+            config.spi_frequency,
+            &embedded_hal::spi::MODE_0,
+        );
+
+        let mut display = Uc8151::new(
+            spi,
+            pins.inky_cs_gpio.into_mode(),
+            pins.inky_dc.into_mode(),
+            pins.inky_busy.into_mode(),
+            pins.inky_res.into_mode(),
+        );
+
+        display.enable();
+        display
+            .setup(&mut delay, config.lut)
+            .map_err(Error::Display)?;
+
+        Ok(Self {
+            display,
+            buttons,
+            wake_reason,
+            orientation: config.orientation,
+            led: pins.led.into_mode(),
+            p3v3_en,
+            vbus_detect: pins.vbus_detect.into_mode(),
+            battery: Battery::new(
+                hal::Adc::new(pac.ADC, &mut pac.RESETS),
+                pins.vbat_sense.into_mode(),
+                pins.vref_power.into_mode(),
+                pins.vref_1v24.into_mode(),
+                config.battery_curve,
+            ),
+            delay,
+            timer: hal::Timer::new(pac.TIMER, &mut pac.RESETS),
+            watchdog,
+            clocks,
+        })
+    }
+
+    /// The display turned to [`Config::orientation`], update it through
+    /// [`Self::display`] once done drawing
+    pub fn screen(&mut self) -> Rotated<Cropped<'_, Display>> {
+        let area = self.display.bounding_box();
+        Rotated::new(self.display.cropped(&area), self.orientation)
+    }
+}
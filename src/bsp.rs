@@ -43,12 +43,30 @@ hal::bsp_pins!(
             FunctionI2C: I2cScl
         }
     },
-    Gpio10 { name: p3v3_en },
-    Gpio11 { name: sw_down },
-    Gpio12 { name: sw_a },
-    Gpio13 { name: sw_b },
-    Gpio14 { name: sw_c },
-    Gpio15 { name: sw_up },
+    Gpio10 {
+        name: p3v3_en,
+        aliases: { PushPullOutput: P3v3En }
+    },
+    Gpio11 {
+        name: sw_down,
+        aliases: { PullDownInput: SwDown }
+    },
+    Gpio12 {
+        name: sw_a,
+        aliases: { PullDownInput: SwA }
+    },
+    Gpio13 {
+        name: sw_b,
+        aliases: { PullDownInput: SwB }
+    },
+    Gpio14 {
+        name: sw_c,
+        aliases: { PullDownInput: SwC }
+    },
+    Gpio15 {
+        name: sw_up,
+        aliases: { PullDownInput: SwUp }
+    },
     Gpio16 {
         name: miso,
         aliases: {
@@ -85,19 +103,37 @@ hal::bsp_pins!(
         name: inky_res,
         aliases: { PushPullOutput: InkyReset }
     },
-    Gpio23 { name: user_sw },
+    Gpio23 {
+        name: user_sw,
+        aliases: { PullUpInput: UserSw }
+    },
     /// GPIO 24 is connected to vbus_detect of the badger2040.
-    Gpio24 { name: vbus_detect },
+    Gpio24 {
+        name: vbus_detect,
+        aliases: { FloatingInput: VbusDetect }
+    },
     /// GPIO 25 is connected to activity LED of the badger2040.
-    Gpio25 { name: led },
+    Gpio25 {
+        name: led,
+        aliases: { PushPullOutput: Led }
+    },
     Gpio26 {
         name: inky_busy,
         aliases: { FloatingInput: InkyBusy }
     },
-    Gpio27 { name: vref_power },
-    Gpio28 { name: vref_1v24 },
+    Gpio27 {
+        name: vref_power,
+        aliases: { PushPullOutput: VrefPower }
+    },
+    Gpio28 {
+        name: vref_1v24,
+        aliases: { FloatingInput: Vref1v24 }
+    },
     /// GPIO 29 is connected to battery monitor of the badger2040
-    Gpio29 { name: vbat_sense },
+    Gpio29 {
+        name: vbat_sense,
+        aliases: { FloatingInput: VbatSense }
+    },
 );
 
 pub const XOSC_CRYSTAL_FREQ: u32 = 12_000_000;
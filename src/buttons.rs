@@ -0,0 +1,364 @@
+//! Debounced button events for the badger2040
+//!
+//! Call [`Buttons::poll`] regularly with a millisecond tick and read the
+//! resulting [`ButtonEvent`]s with [`Buttons::next_event`].
+
+use embedded_hal::digital::v2::InputPin;
+use heapless::Deque;
+
+use crate::board;
+use crate::bsp::hal::gpio::DynPin;
+
+/// How many events are buffered between calls to [`Buttons::next_event`]
+pub const QUEUE_LEN: usize = 16;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Button {
+    A,
+    B,
+    C,
+    Up,
+    Down,
+    User,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventKind {
+    Press,
+    Release,
+    /// The button has been held for [`Config::long_press_ms`]
+    LongPress,
+    /// Sent every [`Config::repeat_ms`] after a long press while still held
+    Repeat,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ButtonEvent {
+    pub button: Button,
+    pub kind: EventKind,
+}
+
+/// Timing thresholds, all in milliseconds
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    /// How long the pin has to stay at a new level before it is accepted
+    pub debounce_ms: u32,
+    /// Hold time before a [`EventKind::LongPress`] is sent
+    pub long_press_ms: u32,
+    /// Interval of [`EventKind::Repeat`] events, `None` disables repeat
+    pub repeat_ms: Option<u32>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            debounce_ms: 20,
+            long_press_ms: 800,
+            repeat_ms: Some(200),
+        }
+    }
+}
+
+/// Debounce and hold state machine for a single button
+///
+/// Independent of any pin, feed it the raw pressed state and the current time.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Debouncer {
+    pressed: bool,
+    raw: bool,
+    raw_since: u32,
+    last_event_at: u32,
+    long_pressed: bool,
+}
+
+impl Debouncer {
+    pub const fn new() -> Self {
+        Self {
+            pressed: false,
+            raw: false,
+            raw_since: 0,
+            last_event_at: 0,
+            long_pressed: false,
+        }
+    }
+
+    /// Returns the debounced state
+    pub fn is_pressed(&self) -> bool {
+        self.pressed
+    }
+
+    /// Advance the state machine, `now_ms` may wrap around
+    pub fn update(&mut self, raw: bool, now_ms: u32, config: &Config) -> Option<EventKind> {
+        if raw != self.raw {
+            self.raw = raw;
+            self.raw_since = now_ms;
+        }
+
+        if self.raw != self.pressed {
+            if now_ms.wrapping_sub(self.raw_since) < config.debounce_ms {
+                return None;
+            }
+            self.pressed = self.raw;
+            self.last_event_at = now_ms;
+            return if self.pressed {
+                self.long_pressed = false;
+                Some(EventKind::Press)
+            } else {
+                Some(EventKind::Release)
+            };
+        }
+
+        if !self.pressed {
+            return None;
+        }
+
+        let elapsed = now_ms.wrapping_sub(self.last_event_at);
+        if !self.long_pressed {
+            if elapsed >= config.long_press_ms {
+                self.long_pressed = true;
+                self.last_event_at = now_ms;
+                return Some(EventKind::LongPress);
+            }
+        } else if let Some(repeat_ms) = config.repeat_ms {
+            if elapsed >= repeat_ms {
+                self.last_event_at = now_ms;
+                return Some(EventKind::Repeat);
+            }
+        }
+        None
+    }
+}
+
+/// A button pin and its debounce state
+pub struct Input<P> {
+    pub button: Button,
+    pin: P,
+    active_low: bool,
+    debouncer: Debouncer,
+}
+
+impl<P: InputPin> Input<P> {
+    /// Button that reads high while pressed
+    pub fn active_high(button: Button, pin: P) -> Self {
+        Self {
+            button,
+            pin,
+            active_low: false,
+            debouncer: Debouncer::new(),
+        }
+    }
+
+    /// Button that reads low while pressed
+    pub fn active_low(button: Button, pin: P) -> Self {
+        Self {
+            active_low: true,
+            ..Self::active_high(button, pin)
+        }
+    }
+
+    /// Read the pin, without debouncing
+    pub fn read(&self) -> Result<bool, P::Error> {
+        Ok(self.pin.is_high()? != self.active_low)
+    }
+
+    /// Release the pin
+    pub fn free(self) -> P {
+        self.pin
+    }
+}
+
+/// Polls a set of button pins and queues their events
+pub struct Buttons<P, const N: usize> {
+    inputs: [Input<P>; N],
+    config: Config,
+    events: Deque<ButtonEvent, QUEUE_LEN>,
+}
+
+impl<P: InputPin, const N: usize> Buttons<P, N> {
+    pub fn new(inputs: [Input<P>; N], config: Config) -> Self {
+        Self {
+            inputs,
+            config,
+            events: Deque::new(),
+        }
+    }
+
+    /// Sample all pins and queue any new events
+    ///
+    /// Events are dropped when the queue is full.
+    pub fn poll(&mut self, now_ms: u32) -> Result<(), P::Error> {
+        for input in self.inputs.iter_mut() {
+            let raw = input.read()?;
+            if let Some(kind) = input.debouncer.update(raw, now_ms, &self.config) {
+                let _ = self.events.push_back(ButtonEvent {
+                    button: input.button,
+                    kind,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Take the oldest queued event
+    pub fn next_event(&mut self) -> Option<ButtonEvent> {
+        self.events.pop_front()
+    }
+
+    /// Returns the debounced state of `button`
+    pub fn is_pressed(&self, button: Button) -> bool {
+        self.inputs
+            .iter()
+            .any(|input| input.button == button && input.debouncer.is_pressed())
+    }
+
+    /// Release the pins
+    pub fn free(self) -> [Input<P>; N] {
+        self.inputs
+    }
+}
+
+impl Buttons<DynPin, 6> {
+    /// Use all buttons of the board
+    pub fn from_board(buttons: board::Buttons, config: Config) -> Self {
+        Self::new(
+            [
+                Input::active_high(Button::A, buttons.a.into()),
+                Input::active_high(Button::B, buttons.b.into()),
+                Input::active_high(Button::C, buttons.c.into()),
+                Input::active_high(Button::Up, buttons.up.into()),
+                Input::active_high(Button::Down, buttons.down.into()),
+                Input::active_low(Button::User, buttons.user.into()),
+            ],
+            config,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cell::Cell;
+    use core::convert::Infallible;
+
+    /// A pin whose level the test sets
+    struct ScriptedPin<'a>(&'a Cell<bool>);
+
+    impl InputPin for ScriptedPin<'_> {
+        type Error = Infallible;
+
+        fn is_high(&self) -> Result<bool, Infallible> {
+            Ok(self.0.get())
+        }
+
+        fn is_low(&self) -> Result<bool, Infallible> {
+            Ok(!self.0.get())
+        }
+    }
+
+    /// Poll every millisecond from `from` to `to`, returns the events with
+    /// the time they were sent
+    fn run<P: InputPin<Error = Infallible>, const N: usize>(
+        buttons: &mut Buttons<P, N>,
+        from: u32,
+        to: u32,
+    ) -> Vec<(u32, ButtonEvent)> {
+        let mut events = Vec::new();
+        let mut now = from;
+        while now != to {
+            buttons.poll(now).unwrap();
+            while let Some(event) = buttons.next_event() {
+                events.push((now, event));
+            }
+            now = now.wrapping_add(1);
+        }
+        events
+    }
+
+    fn event(button: Button, kind: EventKind) -> ButtonEvent {
+        ButtonEvent { button, kind }
+    }
+
+    #[test]
+    fn bounces_are_ignored() {
+        let config = Config::default();
+        let mut debouncer = Debouncer::new();
+        for (now, raw) in [(0, true), (5, false), (10, true), (15, false)] {
+            assert_eq!(debouncer.update(raw, now, &config), None);
+        }
+        assert_eq!(debouncer.update(false, 100, &config), None);
+        assert!(!debouncer.is_pressed());
+
+        assert_eq!(debouncer.update(true, 200, &config), None);
+        assert_eq!(debouncer.update(true, 219, &config), None);
+        assert_eq!(debouncer.update(true, 220, &config), Some(EventKind::Press));
+        assert!(debouncer.is_pressed());
+    }
+
+    #[test]
+    fn press_long_press_repeat_release() {
+        let level = Cell::new(false);
+        let mut buttons = Buttons::new(
+            [Input::active_high(Button::Up, ScriptedPin(&level))],
+            Config::default(),
+        );
+        level.set(true);
+        let held = run(&mut buttons, 0, 1300);
+        level.set(false);
+        let released = run(&mut buttons, 1300, 1400);
+
+        use EventKind::*;
+        assert_eq!(
+            held,
+            [
+                (20, event(Button::Up, Press)),
+                (820, event(Button::Up, LongPress)),
+                (1020, event(Button::Up, Repeat)),
+                (1220, event(Button::Up, Repeat)),
+            ]
+        );
+        assert_eq!(released, [(1320, event(Button::Up, Release))]);
+    }
+
+    #[test]
+    fn short_press_without_repeat() {
+        let level = Cell::new(true);
+        let config = Config {
+            repeat_ms: None,
+            ..Config::default()
+        };
+        let mut buttons = Buttons::new(
+            [Input::active_low(Button::User, ScriptedPin(&level))],
+            config,
+        );
+        level.set(false);
+        assert_eq!(
+            run(&mut buttons, 0, 100),
+            [(20, event(Button::User, EventKind::Press))]
+        );
+        assert!(buttons.is_pressed(Button::User));
+        let held = run(&mut buttons, 100, 3000);
+        assert_eq!(held, [(820, event(Button::User, EventKind::LongPress))]);
+    }
+
+    #[test]
+    fn timing_across_wrap_of_now() {
+        let level = Cell::new(false);
+        let mut buttons = Buttons::new(
+            [Input::active_high(Button::A, ScriptedPin(&level))],
+            Config::default(),
+        );
+        let start = u32::MAX - 9;
+        run(&mut buttons, start - 100, start);
+        level.set(true);
+        let events = run(&mut buttons, start, 1000);
+
+        use EventKind::*;
+        assert_eq!(
+            events,
+            [
+                (10, event(Button::A, Press)),
+                (810, event(Button::A, LongPress)),
+            ]
+        );
+    }
+}
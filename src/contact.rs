@@ -0,0 +1,217 @@
+//! Contact cards to share as QR codes
+//!
+//! A [`Contact`] is written as a vCard 3.0 or a MeCard, both understood by
+//! the camera apps of most phones. vCards are the more complete format,
+//! MeCards are shorter and give smaller QR codes.
+//!
+//! ```ignore
+//! let contact = Contact {
+//!     name: "Taneli Kaivola",
+//!     nickname: Some("dist"),
+//!     organisation: Some("KouvostoTelecom"),
+//!     ..Contact::default()
+//! };
+//! contact
+//!     .qr_code(CardFormat::VCard, EcLevel::L)?
+//!     .fit_into(&area)
+//!     .draw(&mut display)?;
+//! ```
+
+use core::fmt::{self, Display, Write};
+
+use heapless::String;
+
+use crate::qr::{EcLevel, Error, QrCode};
+
+/// Longest payload, the bytes that fit in a version 10 QR code with low
+/// error correction
+pub const MAX_PAYLOAD: usize = 271;
+
+/// How a [`Contact`] is written
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CardFormat {
+    /// `BEGIN:VCARD`, version 3.0
+    #[default]
+    VCard,
+    /// `MECARD:`, as introduced by NTT Docomo
+    MeCard,
+}
+
+/// Contact details, fields that are `None` are left out of the card
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Contact<'a> {
+    /// Full name, written as the given name as badges don't split names
+    pub name: &'a str,
+    pub nickname: Option<&'a str>,
+    pub organisation: Option<&'a str>,
+    pub email: Option<&'a str>,
+    pub phone: Option<&'a str>,
+    pub url: Option<&'a str>,
+}
+
+/// `text` with the characters that are special in `format` escaped
+struct Escaped<'a>(&'a str, CardFormat);
+
+impl Display for Escaped<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for c in self.0.chars() {
+            match (self.1, c) {
+                (_, '\\' | ';' | ',') | (CardFormat::MeCard, ':') => {
+                    f.write_char('\\')?;
+                    f.write_char(c)?;
+                }
+                (CardFormat::VCard, '\n') => f.write_str("\\n")?,
+                (CardFormat::MeCard, '\n') => f.write_char(' ')?,
+                (_, '\r') => {}
+                _ => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Contact<'_> {
+    /// Write the card in `format` to `out`
+    pub fn write(&self, format: CardFormat, out: &mut impl Write) -> fmt::Result {
+        let text = |text| Escaped(text, format);
+        let fields = [
+            ("NICKNAME", self.nickname),
+            ("ORG", self.organisation),
+            ("EMAIL", self.email),
+            ("TEL", self.phone),
+            ("URL", self.url),
+        ];
+
+        match format {
+            CardFormat::VCard => {
+                write!(out, "BEGIN:VCARD\r\nVERSION:3.0\r\n")?;
+                write!(
+                    out,
+                    "N:;{};;;\r\nFN:{}\r\n",
+                    text(self.name),
+                    text(self.name)
+                )?;
+                for (name, value) in fields {
+                    if let Some(value) = value {
+                        write!(out, "{}:{}\r\n", name, text(value))?;
+                    }
+                }
+                write!(out, "END:VCARD")
+            }
+            CardFormat::MeCard => {
+                write!(out, "MECARD:N:{};", text(self.name))?;
+                for (name, value) in fields {
+                    if let Some(value) = value {
+                        write!(out, "{}:{};", name, text(value))?;
+                    }
+                }
+                write!(out, ";")
+            }
+        }
+    }
+
+    /// The card in `format`, [`Error::TooLong`] if it wouldn't fit in any QR
+    /// code
+    pub fn payload(&self, format: CardFormat) -> Result<String<MAX_PAYLOAD>, Error> {
+        let mut payload = String::new();
+        self.write(format, &mut payload)
+            .map_err(|_| Error::TooLong)?;
+        Ok(payload)
+    }
+
+    /// The card as a QR code, [`Error::TooLong`] if it doesn't fit in a QR
+    /// code with `ec` error correction
+    pub fn qr_code(&self, format: CardFormat, ec: EcLevel) -> Result<QrCode, Error> {
+        QrCode::encode(self.payload(format)?.as_bytes(), ec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIST: Contact = Contact {
+        name: "Taneli Kaivola",
+        nickname: Some("dist"),
+        organisation: Some("KouvostoTelecom"),
+        email: None,
+        phone: Some("+358 40 123 4567"),
+        url: Some("https://kouvostotelecom.fi"),
+    };
+
+    #[test]
+    fn vcard() {
+        assert_eq!(
+            DIST.payload(CardFormat::VCard).unwrap(),
+            "BEGIN:VCARD\r\nVERSION:3.0\r\n\
+             N:;Taneli Kaivola;;;\r\nFN:Taneli Kaivola\r\n\
+             NICKNAME:dist\r\nORG:KouvostoTelecom\r\nTEL:+358 40 123 4567\r\n\
+             URL:https://kouvostotelecom.fi\r\nEND:VCARD"
+        );
+    }
+
+    #[test]
+    fn mecard() {
+        assert_eq!(
+            DIST.payload(CardFormat::MeCard).unwrap(),
+            "MECARD:N:Taneli Kaivola;NICKNAME:dist;ORG:KouvostoTelecom;\
+             TEL:+358 40 123 4567;URL:https\\://kouvostotelecom.fi;;"
+        );
+    }
+
+    #[test]
+    fn escaping() {
+        let contact = Contact {
+            name: r"Juva; Heikki, \Zokol\",
+            organisation: Some("Kouvosto\r\nTelecom: Oy"),
+            ..Contact::default()
+        };
+        assert_eq!(
+            contact.payload(CardFormat::VCard).unwrap(),
+            concat!(
+                "BEGIN:VCARD\r\nVERSION:3.0\r\n",
+                r"N:;Juva\; Heikki\, \\Zokol\\;;;",
+                "\r\n",
+                r"FN:Juva\; Heikki\, \\Zokol\\",
+                "\r\n",
+                r"ORG:Kouvosto\nTelecom: Oy",
+                "\r\nEND:VCARD"
+            )
+        );
+        assert_eq!(
+            contact.payload(CardFormat::MeCard).unwrap(),
+            r"MECARD:N:Juva\; Heikki\, \\Zokol\\;ORG:Kouvosto Telecom\: Oy;;"
+        );
+    }
+
+    #[test]
+    fn payload_limit() {
+        // The MeCard adds 11 bytes around the name
+        let name = "a".repeat(MAX_PAYLOAD - 11);
+        let contact = Contact {
+            name: &name,
+            ..Contact::default()
+        };
+        assert_eq!(
+            contact.payload(CardFormat::MeCard).unwrap().len(),
+            MAX_PAYLOAD
+        );
+        let qr = contact.qr_code(CardFormat::MeCard, EcLevel::L).unwrap();
+        assert_eq!(qr.version(), 10);
+        assert_eq!(
+            contact.qr_code(CardFormat::MeCard, EcLevel::M),
+            Err(Error::TooLong)
+        );
+
+        let name = "a".repeat(MAX_PAYLOAD - 10);
+        let contact = Contact {
+            name: &name,
+            ..Contact::default()
+        };
+        assert_eq!(contact.payload(CardFormat::MeCard), Err(Error::TooLong));
+        assert_eq!(
+            contact.qr_code(CardFormat::VCard, EcLevel::L),
+            Err(Error::TooLong)
+        );
+    }
+}
@@ -0,0 +1,144 @@
+//! Display driving beyond what the `uc8151` driver offers
+//!
+//! These talk to the controller through the public SPI and pin fields of
+//! [`uc8151::Uc8151`], so they work with any pins and with mocks on the host.
+
+use embedded_hal::blocking::spi::Write;
+use embedded_hal::digital::v2::{InputPin, OutputPin};
+use uc8151::{SpiDataError, Uc8151, LUT};
+
+mod greyscale;
+#[cfg(test)]
+mod mock;
+mod refresh;
+
+pub use greyscale::{GreyFramebuffer, GreyscaleRenderer, Luts, Timing};
+pub use refresh::{align, Panel, Refresh, RefreshConfig, RefreshManager};
+
+/// Controller commands not exposed by the `uc8151` driver
+mod instruction {
+    pub const PSR: u8 = 0x00;
+    pub const POF: u8 = 0x02;
+    pub const PON: u8 = 0x04;
+    pub const DTM1: u8 = 0x10;
+    pub const DSP: u8 = 0x11;
+    pub const DRF: u8 = 0x12;
+    pub const DTM2: u8 = 0x13;
+    pub const LUT_VCOM: u8 = 0x20;
+    pub const LUT_WW: u8 = 0x21;
+    pub const LUT_BW: u8 = 0x22;
+    pub const LUT_WB: u8 = 0x23;
+    pub const LUT_BB: u8 = 0x24;
+    pub const PLL: u8 = 0x30;
+    pub const PTOU: u8 = 0x92;
+}
+
+/// Panel setting used by [`Uc8151::setup`], 128x296 black and white
+///
+/// Add [`PSR_LUT_REGISTERS`] to use the uploaded waveforms instead of the
+/// ones in the controller's OTP memory.
+const PSR_BASE: u8 = 0b1001_0111;
+const PSR_LUT_REGISTERS: u8 = 0b0010_0000;
+
+/// Send a command and its data
+fn command<SPI, CS, DC, BUSY, RESET>(
+    display: &mut Uc8151<SPI, CS, DC, BUSY, RESET>,
+    instruction: u8,
+    data: &[u8],
+) -> Result<(), SpiDataError>
+where
+    SPI: Write<u8>,
+    CS: OutputPin,
+    DC: OutputPin,
+{
+    // Ignoring return values for set, RP2040 GPIO is infallible
+    let _ = display.cs.set_low();
+    let _ = display.dc.set_low();
+    let result = display
+        .spi
+        .write(&[instruction])
+        .and_then(|()| {
+            let _ = display.dc.set_high();
+            match data {
+                [] => Ok(()),
+                data => display.spi.write(data),
+            }
+        })
+        .map_err(|_| SpiDataError::SpiError);
+    let _ = display.cs.set_high();
+    result
+}
+
+/// Send more data for the last command
+fn data<SPI, CS, DC, BUSY, RESET>(
+    display: &mut Uc8151<SPI, CS, DC, BUSY, RESET>,
+    data: &[u8],
+) -> Result<(), SpiDataError>
+where
+    SPI: Write<u8>,
+    CS: OutputPin,
+    DC: OutputPin,
+{
+    let _ = display.cs.set_low();
+    let _ = display.dc.set_high();
+    let result = display.spi.write(data).map_err(|_| SpiDataError::SpiError);
+    let _ = display.cs.set_high();
+    result
+}
+
+fn wait_while_busy<SPI, CS, DC, BUSY, RESET>(display: &Uc8151<SPI, CS, DC, BUSY, RESET>)
+where
+    BUSY: InputPin,
+{
+    while display.busy.is_low().unwrap_or(true) {}
+}
+
+/// Upload a set of waveforms
+fn upload<SPI, CS, DC, BUSY, RESET>(
+    display: &mut Uc8151<SPI, CS, DC, BUSY, RESET>,
+    vcom: &[u8],
+    [ww, bw, wb, bb]: [&[u8]; 4],
+) -> Result<(), SpiDataError>
+where
+    SPI: Write<u8>,
+    CS: OutputPin,
+    DC: OutputPin,
+{
+    command(display, instruction::LUT_VCOM, vcom)?;
+    command(display, instruction::LUT_WW, ww)?;
+    command(display, instruction::LUT_BW, bw)?;
+    command(display, instruction::LUT_WB, wb)?;
+    command(display, instruction::LUT_BB, bb)
+}
+
+/// Switch the controller to the waveforms of `lut` without resetting it
+///
+/// Unlike [`Uc8151::setup`] this keeps `display.lut` as it is.
+fn load_lut<SPI, CS, DC, BUSY, RESET>(
+    display: &mut Uc8151<SPI, CS, DC, BUSY, RESET>,
+    lut: LUT,
+) -> Result<(), SpiDataError>
+where
+    SPI: Write<u8>,
+    CS: OutputPin,
+    DC: OutputPin,
+    BUSY: InputPin,
+    RESET: OutputPin,
+{
+    let configured = display.lut;
+    display.lut = lut;
+    let waveforms = display.get_lut();
+    display.lut = configured;
+
+    if lut == LUT::Internal {
+        command(display, instruction::PSR, &[PSR_BASE])?;
+    } else {
+        command(display, instruction::PSR, &[PSR_BASE | PSR_LUT_REGISTERS])?;
+        upload(
+            display,
+            &waveforms.vcom,
+            [&waveforms.ww, &waveforms.bw, &waveforms.wb, &waveforms.bb],
+        )?;
+    }
+    command(display, instruction::PLL, &[waveforms.pll])
+}
@@ -0,0 +1,430 @@
+//! Four grey levels with custom waveforms
+//!
+//! The controller picks one of four waveforms per pixel from the pixel's bit
+//! in each of its two frame memories. Normally the first memory is unused and
+//! only black and white are drawn, here the two bits of a [`Gray2`] pixel go
+//! into the two memories and each waveform darkens the pixel a different
+//! amount.
+
+use core::convert::Infallible;
+
+use embedded_graphics::{
+    pixelcolor::{Gray2, GrayColor},
+    prelude::*,
+};
+use embedded_hal::blocking::spi::Write;
+use embedded_hal::digital::v2::{InputPin, OutputPin};
+use uc8151::{SpiDataError, Uc8151, HEIGHT, WIDTH};
+
+use super::{
+    command, data, instruction, load_lut, upload, wait_while_busy, PSR_BASE, PSR_LUT_REGISTERS,
+};
+
+/// Bytes in one column of a bit plane, as the controller expects them
+const COLUMN_BYTES: usize = (HEIGHT / 8) as usize;
+
+/// Bytes in one row of a [`GreyFramebuffer`]
+const ROW_BYTES: usize = (WIDTH / 4) as usize;
+
+/// Frame rate the waveform timings are given in
+const PLL_100HZ: u8 = 0b00_111_010;
+
+/// Voltages of a waveform phase
+const GROUND: u8 = 0b00;
+const TO_BLACK: u8 = 0b01;
+const TO_WHITE: u8 = 0b10;
+
+/// Lengths of the waveforms, in frames of 10 ms
+///
+/// The defaults give four evenly spaced levels on the badger2040 at room
+/// temperature, colder panels need longer waveforms.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Timing {
+    /// Length of both the black and the white half of the cleaning flash
+    pub clean_frames: u8,
+    /// How long light grey, dark grey and black are driven towards black,
+    /// in increasing order
+    pub grey_frames: [u8; 3],
+}
+
+impl Default for Timing {
+    fn default() -> Self {
+        Self {
+            clean_frames: 40,
+            grey_frames: [8, 18, 40],
+        }
+    }
+}
+
+/// A set of waveforms for the controller's LUT registers
+///
+/// Each waveform is up to seven groups of four phases. `ww`, `bw`, `wb` and
+/// `bb` are picked by the pixel's bits in the first and second frame memory,
+/// `vcom` drives the common electrode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Luts {
+    pub vcom: [u8; 44],
+    pub ww: [u8; 42],
+    pub bw: [u8; 42],
+    pub wb: [u8; 42],
+    pub bb: [u8; 42],
+}
+
+/// One waveform group: the voltages of the four phases, their lengths and a
+/// repeat count of one
+fn group(voltages: [u8; 4], frames: [u8; 4]) -> [u8; 6] {
+    let voltages = voltages
+        .iter()
+        .fold(0, |byte, voltage| (byte << 2) | voltage);
+    [voltages, frames[0], frames[1], frames[2], frames[3], 1]
+}
+
+impl Luts {
+    const EMPTY: Self = Self {
+        vcom: [0; 44],
+        ww: [0; 42],
+        bw: [0; 42],
+        wb: [0; 42],
+        bb: [0; 42],
+    };
+
+    /// Waveforms of the pixels in order of darkness, white first
+    fn by_darkness(&mut self) -> [&mut [u8; 42]; 4] {
+        [&mut self.ww, &mut self.wb, &mut self.bw, &mut self.bb]
+    }
+
+    /// Flash every pixel black and then white, whatever its level
+    pub fn clean(timing: &Timing) -> Self {
+        let frames = [timing.clean_frames, timing.clean_frames, 0, 0];
+        let mut luts = Self::EMPTY;
+        luts.vcom[..6].copy_from_slice(&group([GROUND; 4], frames));
+        for waveform in luts.by_darkness() {
+            waveform[..6].copy_from_slice(&group([TO_BLACK, TO_WHITE, GROUND, GROUND], frames));
+        }
+        luts
+    }
+
+    /// Darken the white pixels by level
+    ///
+    /// Every darker level adds a group during which it and all darker levels
+    /// are driven towards black, so all waveforms share the same timing.
+    pub fn grey(timing: &Timing) -> Self {
+        let mut luts = Self::EMPTY;
+        let mut done = 0;
+        for (i, &total) in timing.grey_frames.iter().enumerate() {
+            let frames = [total.saturating_sub(done), 0, 0, 0];
+            done = done.max(total);
+
+            let range = i * 6..(i + 1) * 6;
+            luts.vcom[range.clone()].copy_from_slice(&group([GROUND; 4], frames));
+            for (darkness, waveform) in luts.by_darkness().into_iter().enumerate() {
+                let voltage = if darkness > i { TO_BLACK } else { GROUND };
+                waveform[range.clone()].copy_from_slice(&group([voltage, 0, 0, 0], frames));
+            }
+        }
+        luts
+    }
+}
+
+/// A 2 bits per pixel framebuffer for the whole screen
+///
+/// Rows are packed most significant bits first like `ImageRaw<Gray2>`.
+pub struct GreyFramebuffer {
+    data: [u8; ROW_BYTES * HEIGHT as usize],
+}
+
+impl GreyFramebuffer {
+    /// A white framebuffer
+    pub const fn new() -> Self {
+        Self {
+            data: [0xff; ROW_BYTES * HEIGHT as usize],
+        }
+    }
+
+    /// The packed pixel data
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Level of the pixel at `x`, `y`, white outside the screen
+    pub fn pixel(&self, x: u32, y: u32) -> Gray2 {
+        if x >= WIDTH || y >= HEIGHT {
+            return Gray2::WHITE;
+        }
+        let byte = self.data[y as usize * ROW_BYTES + x as usize / 4];
+        Gray2::new((byte >> (6 - 2 * (x % 4))) & 0b11)
+    }
+
+    /// Set the pixel at `x`, `y`, pixels outside the screen are ignored
+    pub fn set_pixel(&mut self, x: u32, y: u32, color: Gray2) {
+        if x >= WIDTH || y >= HEIGHT {
+            return;
+        }
+        let byte = &mut self.data[y as usize * ROW_BYTES + x as usize / 4];
+        let shift = 6 - 2 * (x % 4);
+        *byte = (*byte & !(0b11 << shift)) | (color.luma() << shift);
+    }
+
+    /// One column of a bit plane in the controller's format
+    ///
+    /// Plane 1 holds the high bit of the darkness and goes to the first frame
+    /// memory, plane 0 the low bit and goes to the second. A set bit is
+    /// darker, the top pixel is the most significant bit of the first byte.
+    pub fn plane_column(&self, x: u32, plane: u8) -> [u8; COLUMN_BYTES] {
+        let mut column = [0; COLUMN_BYTES];
+        for y in 0..HEIGHT {
+            let darkness = 3 - self.pixel(x, y).luma();
+            if darkness & (1 << plane) != 0 {
+                column[y as usize / 8] |= 0x80 >> (y % 8);
+            }
+        }
+        column
+    }
+}
+
+impl Default for GreyFramebuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OriginDimensions for GreyFramebuffer {
+    fn size(&self) -> Size {
+        Size::new(WIDTH, HEIGHT)
+    }
+}
+
+impl DrawTarget for GreyFramebuffer {
+    type Color = Gray2;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            if point.x >= 0 && point.y >= 0 {
+                self.set_pixel(point.x as u32, point.y as u32, color);
+            }
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        let luma = color.luma();
+        let byte = luma << 6 | luma << 4 | luma << 2 | luma;
+        self.data = [byte; ROW_BYTES * HEIGHT as usize];
+        Ok(())
+    }
+}
+
+/// Shows a [`GreyFramebuffer`] on the display
+///
+/// Rendering takes two full refreshes: one cleans the panel, the second
+/// darkens every pixel to its level. Afterwards the display's own waveforms
+/// are restored, so 1 bit updates keep working.
+///
+/// ```ignore
+/// let mut framebuffer = GreyFramebuffer::new();
+/// let tga: Tga<Gray2> = Tga::from_slice(data).unwrap();
+/// Image::new(&tga, Point::zero()).draw(&mut framebuffer)?;
+/// GreyscaleRenderer::new(Timing::default()).render(&mut display, &framebuffer)?;
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct GreyscaleRenderer {
+    passes: [Luts; 2],
+}
+
+impl GreyscaleRenderer {
+    pub fn new(timing: Timing) -> Self {
+        Self {
+            passes: [Luts::clean(&timing), Luts::grey(&timing)],
+        }
+    }
+
+    /// The waveforms of each refresh, in order
+    pub fn passes(&self) -> &[Luts; 2] {
+        &self.passes
+    }
+
+    pub fn render<SPI, CS, DC, BUSY, RESET>(
+        &self,
+        display: &mut Uc8151<SPI, CS, DC, BUSY, RESET>,
+        framebuffer: &GreyFramebuffer,
+    ) -> Result<(), SpiDataError>
+    where
+        SPI: Write<u8>,
+        CS: OutputPin,
+        DC: OutputPin,
+        BUSY: InputPin,
+        RESET: OutputPin,
+    {
+        wait_while_busy(display);
+        command(display, instruction::PSR, &[PSR_BASE | PSR_LUT_REGISTERS])?;
+        command(display, instruction::PLL, &[PLL_100HZ])?;
+
+        for luts in &self.passes {
+            upload(
+                display,
+                &luts.vcom,
+                [&luts.ww, &luts.bw, &luts.wb, &luts.bb],
+            )?;
+
+            command(display, instruction::PON, &[])?;
+            command(display, instruction::PTOU, &[])?;
+            for (memory, plane) in [(instruction::DTM1, 1), (instruction::DTM2, 0)] {
+                command(display, memory, &[])?;
+                for x in 0..WIDTH {
+                    data(display, &framebuffer.plane_column(x, plane))?;
+                }
+            }
+            command(display, instruction::DSP, &[])?;
+            command(display, instruction::DRF, &[])?;
+            wait_while_busy(display);
+        }
+
+        command(display, instruction::POF, &[])?;
+        // Put back the waveforms the display was set up with
+        load_lut(display, display.lut)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::mock::{Bus, Sent};
+    use super::*;
+    use uc8151::LUT;
+
+    /// `command` and its data as it is sent
+    fn sent(command: u8, data: &[u8]) -> Vec<Sent> {
+        let mut sent = vec![Sent::Command(command)];
+        if !data.is_empty() {
+            sent.push(Sent::Data(data.to_vec()));
+        }
+        sent
+    }
+
+    fn upload(vcom: &[u8], [ww, bw, wb, bb]: [&[u8]; 4]) -> Vec<Sent> {
+        [
+            sent(instruction::LUT_VCOM, vcom),
+            sent(instruction::LUT_WW, ww),
+            sent(instruction::LUT_BW, bw),
+            sent(instruction::LUT_WB, wb),
+            sent(instruction::LUT_BB, bb),
+        ]
+        .concat()
+    }
+
+    /// A framebuffer with all four levels in the first rows of column 5
+    fn levels() -> GreyFramebuffer {
+        let mut framebuffer = GreyFramebuffer::new();
+        for y in 0..8 {
+            framebuffer.set_pixel(5, y, Gray2::new(3 - (y % 4) as u8));
+        }
+        framebuffer
+    }
+
+    #[test]
+    fn plane_columns() {
+        let framebuffer = levels();
+        // White, light grey, dark grey, black from the top, twice
+        let mut high = [0; COLUMN_BYTES];
+        high[0] = 0b0011_0011;
+        let mut low = [0; COLUMN_BYTES];
+        low[0] = 0b0101_0101;
+        assert_eq!(framebuffer.plane_column(5, 1), high);
+        assert_eq!(framebuffer.plane_column(5, 0), low);
+        assert_eq!(framebuffer.plane_column(4, 1), [0; COLUMN_BYTES]);
+        assert_eq!(framebuffer.plane_column(4, 0), [0; COLUMN_BYTES]);
+    }
+
+    #[test]
+    fn pixels_packed_like_image_raw() {
+        let mut framebuffer = GreyFramebuffer::new();
+        framebuffer.clear(Gray2::BLACK).unwrap();
+        for x in 0..4 {
+            framebuffer.set_pixel(x, 0, Gray2::new(x as u8));
+        }
+        assert_eq!(framebuffer.data()[..2], [0b00_01_10_11, 0]);
+        assert_eq!(framebuffer.pixel(2, 0), Gray2::new(2));
+        assert_eq!(framebuffer.pixel(WIDTH, 0), Gray2::WHITE);
+    }
+
+    #[test]
+    fn grey_waveforms() {
+        let luts = Luts::grey(&Timing::default());
+        let groups = |first: u8, second: u8, third: u8| {
+            let mut waveform = [0; 42];
+            waveform[..18].copy_from_slice(&[
+                first, 8, 0, 0, 0, 1, second, 10, 0, 0, 0, 1, third, 22, 0, 0, 0, 1,
+            ]);
+            waveform
+        };
+        assert_eq!(luts.vcom[..18], groups(0, 0, 0)[..18]);
+        assert_eq!(luts.vcom[18..], [0; 26]);
+        assert_eq!(luts.ww, groups(0, 0, 0));
+        assert_eq!(luts.wb, groups(0x40, 0, 0));
+        assert_eq!(luts.bw, groups(0x40, 0x40, 0));
+        assert_eq!(luts.bb, groups(0x40, 0x40, 0x40));
+    }
+
+    #[test]
+    fn render_byte_stream() {
+        let bus = Bus::default();
+        let mut display = bus.display();
+        display.lut = LUT::Fast;
+        let framebuffer = levels();
+        let renderer = GreyscaleRenderer::new(Timing::default());
+        renderer.render(&mut display, &framebuffer).unwrap();
+
+        let plane = |plane| -> Vec<u8> {
+            (0..WIDTH)
+                .flat_map(|x| framebuffer.plane_column(x, plane))
+                .collect()
+        };
+        let mut expected = [
+            sent(instruction::PSR, &[PSR_BASE | PSR_LUT_REGISTERS]),
+            sent(instruction::PLL, &[PLL_100HZ]),
+        ]
+        .concat();
+        for luts in renderer.passes() {
+            expected.extend(upload(&luts.vcom, [&luts.ww, &luts.bw, &luts.wb, &luts.bb]));
+            expected.extend(sent(instruction::PON, &[]));
+            expected.extend(sent(instruction::PTOU, &[]));
+            expected.extend(sent(instruction::DTM1, &plane(1)));
+            expected.extend(sent(instruction::DTM2, &plane(0)));
+            expected.extend(sent(instruction::DSP, &[]));
+            expected.extend(sent(instruction::DRF, &[]));
+        }
+        expected.extend(sent(instruction::POF, &[]));
+
+        // The waveforms of the display are restored at the end
+        let fast = display.get_lut();
+        expected.extend(sent(instruction::PSR, &[PSR_BASE | PSR_LUT_REGISTERS]));
+        expected.extend(upload(&fast.vcom, [&fast.ww, &fast.bw, &fast.wb, &fast.bb]));
+        expected.extend(sent(instruction::PLL, &[fast.pll]));
+
+        assert_eq!(bus.take(), expected);
+        assert!(display.lut == LUT::Fast);
+    }
+
+    #[test]
+    fn render_restores_internal_waveforms() {
+        let bus = Bus::default();
+        let mut display = bus.display();
+        display.lut = LUT::Internal;
+        GreyscaleRenderer::new(Timing::default())
+            .render(&mut display, &GreyFramebuffer::new())
+            .unwrap();
+
+        let all = bus.take();
+        let pll = display.get_lut().pll;
+        let restore = [
+            sent(instruction::POF, &[]),
+            sent(instruction::PSR, &[PSR_BASE]),
+            sent(instruction::PLL, &[pll]),
+        ]
+        .concat();
+        assert_eq!(all[all.len() - restore.len()..], restore);
+    }
+}
@@ -0,0 +1,98 @@
+//! A recording SPI bus and pins, to check what is sent to the controller
+
+use std::cell::{Cell, RefCell};
+use std::convert::Infallible;
+use std::rc::Rc;
+use std::vec::Vec;
+
+use embedded_hal::blocking::spi::Write;
+use embedded_hal::digital::v2::{InputPin, OutputPin};
+use uc8151::Uc8151;
+
+/// What went over the bus
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Sent {
+    Command(u8),
+    /// All data sent after a command, however many writes it took
+    Data(Vec<u8>),
+}
+
+/// The bus shared by the SPI and the data/command pin
+#[derive(Clone, Default)]
+pub struct Bus {
+    data_mode: Rc<Cell<bool>>,
+    sent: Rc<RefCell<Vec<Sent>>>,
+}
+
+pub struct Spi(Bus);
+pub struct DataCommand(Bus);
+/// A pin that ignores what is set and reads high, i.e. never busy
+pub struct Pin;
+
+pub type Display = Uc8151<Spi, Pin, DataCommand, Pin, Pin>;
+
+impl Bus {
+    /// A display on this bus
+    pub fn display(&self) -> Display {
+        Uc8151::new(Spi(self.clone()), Pin, DataCommand(self.clone()), Pin, Pin)
+    }
+
+    /// Everything sent since the last call
+    pub fn take(&self) -> Vec<Sent> {
+        self.sent.take()
+    }
+}
+
+impl Write<u8> for Spi {
+    type Error = Infallible;
+
+    fn write(&mut self, words: &[u8]) -> Result<(), Infallible> {
+        let mut sent = self.0.sent.borrow_mut();
+        if !self.0.data_mode.get() {
+            sent.extend(words.iter().map(|&word| Sent::Command(word)));
+        } else if let Some(Sent::Data(data)) = sent.last_mut() {
+            data.extend_from_slice(words);
+        } else {
+            sent.push(Sent::Data(words.to_vec()));
+        }
+        Ok(())
+    }
+}
+
+impl OutputPin for DataCommand {
+    type Error = Infallible;
+
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        self.0.data_mode.set(false);
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        self.0.data_mode.set(true);
+        Ok(())
+    }
+}
+
+impl OutputPin for Pin {
+    type Error = Infallible;
+
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+impl InputPin for Pin {
+    type Error = Infallible;
+
+    fn is_high(&self) -> Result<bool, Infallible> {
+        Ok(true)
+    }
+
+    fn is_low(&self) -> Result<bool, Infallible> {
+        Ok(false)
+    }
+}
@@ -0,0 +1,566 @@
+//! Choosing between partial, full and cleaning refreshes
+
+use embedded_graphics::{pixelcolor::BinaryColor, prelude::*, primitives::Rectangle};
+use embedded_hal::blocking::spi::Write;
+use embedded_hal::digital::v2::{InputPin, OutputPin};
+use heapless::Vec;
+use uc8151::{SpiDataError, Uc8151, UpdateRegion, LUT};
+
+use super::load_lut;
+use crate::graphics_extensions::Rotated;
+
+/// Dirty areas tracked separately before they are merged into one
+const MAX_DIRTY: usize = 4;
+
+/// A display that [`RefreshManager`] can refresh
+pub trait Panel: DrawTarget<Color = BinaryColor> {
+    /// Refresh the whole screen, `clean` with the slow [`LUT::Normal`]
+    /// waveform that removes ghosting
+    fn refresh(&mut self, clean: bool) -> Result<(), SpiDataError>;
+
+    /// Refresh only `area`, its top and height are multiples of 8
+    fn refresh_area(&mut self, area: Rectangle) -> Result<(), SpiDataError>;
+
+    /// Use the waveform of `lut` for the following refreshes
+    fn set_lut(&mut self, lut: LUT) -> Result<(), SpiDataError>;
+}
+
+impl<SPI, CS, DC, BUSY, RESET> Panel for Uc8151<SPI, CS, DC, BUSY, RESET>
+where
+    SPI: Write<u8>,
+    CS: OutputPin,
+    DC: OutputPin,
+    BUSY: InputPin,
+    RESET: OutputPin,
+{
+    fn refresh(&mut self, clean: bool) -> Result<(), SpiDataError> {
+        if !clean || matches!(self.lut, LUT::Normal | LUT::Internal) {
+            return self.update();
+        }
+        while self.is_busy() {}
+        load_lut(self, LUT::Normal)?;
+        self.update()?;
+        load_lut(self, self.lut)
+    }
+
+    fn refresh_area(&mut self, area: Rectangle) -> Result<(), SpiDataError> {
+        let region = UpdateRegion::try_from(area).map_err(|_| SpiDataError::SpiError)?;
+        self.partial_update(region)
+    }
+
+    fn set_lut(&mut self, lut: LUT) -> Result<(), SpiDataError> {
+        if lut == self.lut {
+            return Ok(());
+        }
+        while self.is_busy() {}
+        load_lut(self, lut)?;
+        self.lut = lut;
+        Ok(())
+    }
+}
+
+/// Lets [`RefreshManager`] track a portrait or upside down screen
+impl<D: Panel> Panel for Rotated<D> {
+    fn refresh(&mut self, clean: bool) -> Result<(), SpiDataError> {
+        self.inner_mut().refresh(clean)
+    }
+
+    fn refresh_area(&mut self, area: Rectangle) -> Result<(), SpiDataError> {
+        let screen = self.inner().bounding_box();
+        let area = self.rotation().rectangle(&area, screen.size);
+        match align(area, screen) {
+            Some(area) => self.inner_mut().refresh_area(area),
+            None => Ok(()),
+        }
+    }
+
+    fn set_lut(&mut self, lut: LUT) -> Result<(), SpiDataError> {
+        self.inner_mut().set_lut(lut)
+    }
+}
+
+/// When [`RefreshManager`] refreshes what
+#[derive(Clone, Copy, Debug)]
+pub struct RefreshConfig {
+    /// Partial or fast refreshes before a cleaning refresh is forced
+    pub max_refreshes: u32,
+    /// Longest time a fast refreshed screen is left uncleaned
+    pub clean_interval_ms: Option<u32>,
+    /// Changes covering more than this share of the screen, in percent, get
+    /// a full refresh instead of partial ones
+    pub full_refresh_percent: u8,
+}
+
+impl Default for RefreshConfig {
+    fn default() -> Self {
+        Self {
+            max_refreshes: 10,
+            clean_interval_ms: Some(10 * 60 * 1000),
+            full_refresh_percent: 50,
+        }
+    }
+}
+
+/// What [`RefreshManager::refresh`] did
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Refresh {
+    /// Nothing changed
+    None,
+    /// This many areas were refreshed
+    Partial(usize),
+    /// The whole screen was refreshed with the configured waveform
+    Full,
+    /// The whole screen was refreshed with [`LUT::Normal`] to clear ghosting
+    Clean,
+}
+
+/// Top and bottom of `area` moved out to multiples of 8, within the screen
+pub fn align(area: Rectangle, screen: Rectangle) -> Option<Rectangle> {
+    let area = area.intersection(&screen);
+    let bottom_right = area.bottom_right()?;
+    let top = area.top_left.y & !7;
+    let bottom = (bottom_right.y | 7) + 1;
+    let aligned = Rectangle::with_corners(
+        Point::new(area.top_left.x, top),
+        Point::new(bottom_right.x, bottom - 1),
+    );
+    Some(aligned.intersection(&screen))
+}
+
+/// Tracks what has been drawn and refreshes only what changed
+///
+/// Draw through the manager like on the display, then call
+/// [`RefreshManager::refresh`]. Small changes get partial refreshes, large ones
+/// a full refresh, and after [`RefreshConfig::max_refreshes`] refreshes or
+/// [`RefreshConfig::clean_interval_ms`] the screen is cleaned.
+pub struct RefreshManager<D> {
+    display: D,
+    config: RefreshConfig,
+    dirty: Vec<Rectangle, MAX_DIRTY>,
+    /// Refreshes since the last cleaning one
+    refreshes: u32,
+    /// Time of the first refresh after the last cleaning one
+    uncleaned_since: Option<u32>,
+}
+
+impl<D: Panel> RefreshManager<D> {
+    pub fn new(display: D, config: RefreshConfig) -> Self {
+        Self {
+            display,
+            config,
+            dirty: Vec::new(),
+            refreshes: 0,
+            uncleaned_since: None,
+        }
+    }
+
+    /// The wrapped display, drawing on it directly is not tracked
+    pub fn display(&mut self) -> &mut D {
+        &mut self.display
+    }
+
+    /// Release the wrapped display
+    pub fn free(self) -> D {
+        self.display
+    }
+
+    /// Areas waiting for a refresh, aligned for partial refreshes
+    pub fn dirty(&self) -> &[Rectangle] {
+        &self.dirty
+    }
+
+    /// Mark `area` as changed
+    ///
+    /// Every partial refresh takes as long as the waveform, so nearby areas
+    /// are merged as long as that doesn't refresh much more than needed. When
+    /// too many areas are tracked they are all merged into one.
+    pub fn mark_dirty(&mut self, area: Rectangle) {
+        let mut area = match align(area, self.display.bounding_box()) {
+            Some(area) => area,
+            None => return,
+        };
+
+        let mut i = 0;
+        while i < self.dirty.len() {
+            let other = self.dirty[i];
+            if worth_merging(&area, &other) {
+                area = union(&area, &other);
+                self.dirty.swap_remove(i);
+                // The grown area may touch ones already checked
+                i = 0;
+            } else {
+                i += 1;
+            }
+        }
+
+        if let Err(area) = self.dirty.push(area) {
+            let merged = self
+                .dirty
+                .iter()
+                .fold(area, |all, other| union(&all, other));
+            self.dirty.clear();
+            let _ = self.dirty.push(merged);
+        }
+    }
+
+    fn clean_due(&self, now_ms: u32) -> bool {
+        let too_many = self.refreshes >= self.config.max_refreshes;
+        let too_long = match (self.uncleaned_since, self.config.clean_interval_ms) {
+            (Some(since), Some(interval)) => now_ms.wrapping_sub(since) >= interval,
+            _ => false,
+        };
+        too_many || too_long
+    }
+
+    /// Refresh the changed areas, or clean the screen when it is due
+    ///
+    /// A clean may be due without anything changing, so call this regularly
+    /// when a clean interval is configured.
+    pub fn refresh(&mut self, now_ms: u32) -> Result<Refresh, SpiDataError> {
+        if self.clean_due(now_ms) {
+            return self.clean();
+        }
+        if self.dirty.is_empty() {
+            return Ok(Refresh::None);
+        }
+
+        let screen = self.display.bounding_box().size;
+        let screen_area = screen.width * screen.height;
+        let dirty_area: u32 = self.dirty.iter().map(area).sum();
+
+        let refresh = if dirty_area * 100 > screen_area * self.config.full_refresh_percent as u32 {
+            self.display.refresh(false)?;
+            Refresh::Full
+        } else {
+            for area in &self.dirty {
+                self.display.refresh_area(*area)?;
+            }
+            Refresh::Partial(self.dirty.len())
+        };
+
+        self.dirty.clear();
+        self.refreshes += 1;
+        self.uncleaned_since.get_or_insert(now_ms);
+        Ok(refresh)
+    }
+
+    /// Refresh the whole screen with [`LUT::Normal`] right away
+    pub fn clean(&mut self) -> Result<Refresh, SpiDataError> {
+        self.display.refresh(true)?;
+        self.dirty.clear();
+        self.refreshes = 0;
+        self.uncleaned_since = None;
+        Ok(Refresh::Clean)
+    }
+}
+
+fn union(a: &Rectangle, b: &Rectangle) -> Rectangle {
+    match (a.bottom_right(), b.bottom_right()) {
+        (Some(a_end), Some(b_end)) => Rectangle::with_corners(
+            a.top_left.component_min(b.top_left),
+            a_end.component_max(b_end),
+        ),
+        (Some(_), None) => *a,
+        _ => *b,
+    }
+}
+
+fn area(rectangle: &Rectangle) -> u32 {
+    rectangle.size.width * rectangle.size.height
+}
+
+/// Touching, or close enough that their union is at most twice their area
+fn worth_merging(a: &Rectangle, b: &Rectangle) -> bool {
+    let grown = Rectangle::new(a.top_left - Point::new(1, 1), a.size + Size::new(2, 2));
+    let touching = !grown.intersection(b).is_zero_sized();
+    touching || area(&union(a, b)) <= 2 * (area(a) + area(b))
+}
+
+impl<D: Panel> Dimensions for RefreshManager<D> {
+    fn bounding_box(&self) -> Rectangle {
+        self.display.bounding_box()
+    }
+}
+
+impl<D: Panel> DrawTarget for RefreshManager<D> {
+    type Color = BinaryColor;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let mut corners: Option<(Point, Point)> = None;
+        let pixels = pixels.into_iter().inspect(|Pixel(point, _)| {
+            corners = Some(match corners {
+                Some((min, max)) => (min.component_min(*point), max.component_max(*point)),
+                None => (*point, *point),
+            });
+        });
+        self.display.draw_iter(pixels)?;
+
+        if let Some((min, max)) = corners {
+            self.mark_dirty(Rectangle::with_corners(min, max));
+        }
+        Ok(())
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        self.mark_dirty(*area);
+        self.display.fill_contiguous(area, colors)
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.mark_dirty(*area);
+        self.display.fill_solid(area, color)
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.mark_dirty(self.display.bounding_box());
+        self.display.clear(color)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::Infallible;
+    use uc8151::{HEIGHT, WIDTH};
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    enum Call {
+        Refresh { clean: bool },
+        Area(Rectangle),
+    }
+
+    /// A panel that records the refreshes asked for
+    #[derive(Default)]
+    struct Recorder {
+        calls: std::vec::Vec<Call>,
+    }
+
+    impl OriginDimensions for Recorder {
+        fn size(&self) -> Size {
+            Size::new(WIDTH, HEIGHT)
+        }
+    }
+
+    impl DrawTarget for Recorder {
+        type Color = BinaryColor;
+        type Error = Infallible;
+
+        fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Pixel<Self::Color>>,
+        {
+            pixels.into_iter().for_each(drop);
+            Ok(())
+        }
+    }
+
+    impl Panel for Recorder {
+        fn refresh(&mut self, clean: bool) -> Result<(), SpiDataError> {
+            self.calls.push(Call::Refresh { clean });
+            Ok(())
+        }
+
+        fn refresh_area(&mut self, area: Rectangle) -> Result<(), SpiDataError> {
+            self.calls.push(Call::Area(area));
+            Ok(())
+        }
+
+        fn set_lut(&mut self, _lut: LUT) -> Result<(), SpiDataError> {
+            Ok(())
+        }
+    }
+
+    fn rectangle(x: i32, y: i32, width: u32, height: u32) -> Rectangle {
+        Rectangle::new(Point::new(x, y), Size::new(width, height))
+    }
+
+    fn manager(config: RefreshConfig) -> RefreshManager<Recorder> {
+        RefreshManager::new(Recorder::default(), config)
+    }
+
+    /// Take the refreshes the manager asked for
+    fn calls(manager: &mut RefreshManager<Recorder>) -> std::vec::Vec<Call> {
+        core::mem::take(&mut manager.display().calls)
+    }
+
+    #[test]
+    fn align_rounds_to_rows_of_8() {
+        let screen = rectangle(0, 0, WIDTH, HEIGHT);
+        assert_eq!(
+            align(rectangle(10, 3, 5, 2), screen),
+            Some(rectangle(10, 0, 5, 8))
+        );
+        assert_eq!(
+            align(rectangle(10, 6, 5, 4), screen),
+            Some(rectangle(10, 0, 5, 16))
+        );
+        assert_eq!(
+            align(rectangle(0, 8, 5, 8), screen),
+            Some(rectangle(0, 8, 5, 8))
+        );
+        assert_eq!(
+            align(rectangle(0, 17, 5, 16), screen),
+            Some(rectangle(0, 16, 5, 24))
+        );
+        // Clipped to the screen
+        assert_eq!(
+            align(rectangle(290, 120, 20, 20), screen),
+            Some(rectangle(290, 120, 6, 8))
+        );
+        assert_eq!(
+            align(rectangle(-10, -5, 20, 10), screen),
+            Some(rectangle(0, 0, 10, 8))
+        );
+        assert_eq!(align(rectangle(300, 0, 5, 5), screen), None);
+        assert_eq!(align(rectangle(10, 10, 0, 5), screen), None);
+    }
+
+    #[test]
+    fn nearby_areas_are_merged() {
+        let mut manager = manager(RefreshConfig::default());
+        manager.mark_dirty(rectangle(10, 10, 20, 4));
+        manager.mark_dirty(rectangle(30, 12, 10, 4));
+        assert_eq!(manager.dirty(), [rectangle(10, 8, 30, 8)]);
+
+        // Far away, merging would refresh much more than needed
+        manager.mark_dirty(rectangle(200, 100, 10, 10));
+        assert_eq!(
+            manager.dirty(),
+            [rectangle(10, 8, 30, 8), rectangle(200, 96, 10, 16)]
+        );
+
+        // Growing the first area to touch the second merges all three
+        manager.mark_dirty(rectangle(40, 20, 160, 80));
+        assert_eq!(manager.dirty(), [rectangle(10, 8, 200, 104)]);
+    }
+
+    #[test]
+    fn too_many_areas_are_merged_into_one() {
+        let mut manager = manager(RefreshConfig::default());
+        for i in 0..MAX_DIRTY as i32 {
+            manager.mark_dirty(rectangle(i * 70, (i % 2) * 100, 4, 4));
+        }
+        assert_eq!(manager.dirty().len(), MAX_DIRTY);
+        manager.mark_dirty(rectangle(290, 50, 4, 4));
+        assert_eq!(manager.dirty(), [rectangle(0, 0, 294, 104)]);
+    }
+
+    #[test]
+    fn drawing_marks_dirty() {
+        let mut manager = manager(RefreshConfig::default());
+        Pixel(Point::new(5, 9), BinaryColor::Off)
+            .draw(&mut manager)
+            .unwrap();
+        manager
+            .fill_solid(&rectangle(100, 60, 8, 3), BinaryColor::On)
+            .unwrap();
+        assert_eq!(
+            manager.dirty(),
+            [rectangle(5, 8, 1, 8), rectangle(100, 56, 8, 8)]
+        );
+    }
+
+    #[test]
+    fn small_changes_refresh_partially() {
+        let mut manager = manager(RefreshConfig::default());
+        assert_eq!(manager.refresh(0).unwrap(), Refresh::None);
+        manager.mark_dirty(rectangle(10, 10, 20, 4));
+        manager.mark_dirty(rectangle(200, 100, 10, 10));
+        assert_eq!(manager.refresh(0).unwrap(), Refresh::Partial(2));
+        assert_eq!(
+            calls(&mut manager),
+            [
+                Call::Area(rectangle(10, 8, 20, 8)),
+                Call::Area(rectangle(200, 96, 10, 16))
+            ]
+        );
+        assert!(manager.dirty().is_empty());
+
+        manager.mark_dirty(rectangle(0, 0, 200, 100));
+        assert_eq!(manager.refresh(0).unwrap(), Refresh::Full);
+        assert_eq!(calls(&mut manager), [Call::Refresh { clean: false }]);
+    }
+
+    #[test]
+    fn clean_after_max_refreshes() {
+        let mut manager = manager(RefreshConfig {
+            max_refreshes: 3,
+            clean_interval_ms: None,
+            ..RefreshConfig::default()
+        });
+        for now in 0..3 {
+            manager.mark_dirty(rectangle(0, 0, 8, 8));
+            assert_eq!(manager.refresh(now).unwrap(), Refresh::Partial(1));
+        }
+        manager.mark_dirty(rectangle(0, 0, 8, 8));
+        assert_eq!(manager.refresh(3).unwrap(), Refresh::Clean);
+        assert_eq!(calls(&mut manager)[3..], [Call::Refresh { clean: true }]);
+        assert!(manager.dirty().is_empty());
+
+        // Counting starts over
+        manager.mark_dirty(rectangle(0, 0, 8, 8));
+        assert_eq!(manager.refresh(4).unwrap(), Refresh::Partial(1));
+    }
+
+    #[test]
+    fn clean_after_interval() {
+        let mut manager = manager(RefreshConfig {
+            clean_interval_ms: Some(1000),
+            ..RefreshConfig::default()
+        });
+        let start = u32::MAX - 500;
+        // Nothing refreshed yet, nothing to clean
+        assert_eq!(manager.refresh(start + 100).unwrap(), Refresh::None);
+        manager.mark_dirty(rectangle(0, 0, 8, 8));
+        assert_eq!(manager.refresh(start).unwrap(), Refresh::Partial(1));
+        assert_eq!(
+            manager.refresh(start.wrapping_add(999)).unwrap(),
+            Refresh::None
+        );
+        // Due without any change, across the wrap of the tick
+        assert_eq!(
+            manager.refresh(start.wrapping_add(1000)).unwrap(),
+            Refresh::Clean
+        );
+        assert_eq!(
+            manager.refresh(start.wrapping_add(5000)).unwrap(),
+            Refresh::None
+        );
+    }
+
+    #[test]
+    fn clean_refresh_with_fast_waveform() {
+        use super::super::mock::{Bus, Sent};
+        use super::super::{instruction, PSR_BASE, PSR_LUT_REGISTERS};
+
+        let bus = Bus::default();
+        let mut display = bus.display();
+        display.lut = LUT::Fast;
+        Panel::refresh(&mut display, true).unwrap();
+
+        let sent = bus.take();
+        let psr = Sent::Command(instruction::PSR);
+        let luts = Sent::Data(std::vec![PSR_BASE | PSR_LUT_REGISTERS]);
+        // Normal waveforms for the refresh, then the fast ones again
+        assert_eq!(sent.iter().filter(|&sent| *sent == psr).count(), 2);
+        assert_eq!(sent[..2], [psr.clone(), luts.clone()]);
+        assert!(display.lut == LUT::Fast);
+        let fast = display.get_lut().vcom;
+        display.lut = LUT::Normal;
+        let normal = display.get_lut().vcom;
+        display.lut = LUT::Fast;
+        assert_eq!(sent[3], Sent::Data(normal.to_vec()));
+        assert!(sent[4..].contains(&Sent::Data(fast.to_vec())));
+        assert_eq!(
+            sent.last(),
+            Some(&Sent::Data(std::vec![display.get_lut().pll]))
+        );
+    }
+}
@@ -0,0 +1,300 @@
+//! Proportional bitmap fonts converted at build time from `gfx/fonts.toml`
+//!
+//! The `MonoFont`s of embedded-graphics only cover one code page and give
+//! every letter the same width. A [`ProportionalFont`] keeps any Unicode
+//! characters of a BDF font, cropped to their ink and packed bit by bit, and
+//! advances by the width of each glyph. Draw it with a
+//! [`ProportionalTextStyle`] like a `MonoTextStyle`:
+//!
+//! ```ignore
+//! let style = ProportionalTextStyle::new(&fonts::BOLD_18, BinaryColor::Off);
+//! Text::with_alignment("Jyväskylä", Point::zero(), style, Alignment::Center)
+//!     .center(screen_center)
+//!     .draw(&mut display)?;
+//! ```
+
+use embedded_graphics::{
+    prelude::*,
+    primitives::Rectangle,
+    text::{
+        renderer::{CharacterStyle, TextMetrics, TextRenderer},
+        Baseline,
+    },
+};
+
+include!(concat!(env!("OUT_DIR"), "/fonts.rs"));
+
+/// A character of a [`ProportionalFont`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Glyph {
+    pub c: char,
+    /// Bit of the font bitmap the top left pixel is in
+    pub offset: u32,
+    /// Size of the ink, rows follow each other without padding
+    pub width: u8,
+    pub height: u8,
+    /// Position of the ink from the top left of the character cell
+    pub x: i8,
+    pub y: i8,
+    /// Pen movement to the next character, without the character spacing
+    pub advance: u8,
+}
+
+/// A bitmap font with a width per glyph
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProportionalFont<'a> {
+    /// Bits of all glyphs, MSB first, set bits are ink
+    pub bitmap: &'a [u8],
+    /// Glyphs sorted by character
+    pub glyphs: &'a [Glyph],
+    pub line_height: u32,
+    /// Row of the character cell the letters stand on
+    pub baseline: u32,
+    /// Pixels between characters
+    pub character_spacing: u32,
+    /// Drawn for characters the font doesn't have
+    pub replacement: char,
+}
+
+impl<'a> ProportionalFont<'a> {
+    pub fn glyph(&self, c: char) -> Option<&'a Glyph> {
+        let glyphs = self.glyphs;
+        glyphs
+            .binary_search_by_key(&c, |glyph| glyph.c)
+            .ok()
+            .map(|i| &glyphs[i])
+    }
+
+    /// The glyph of `c`, or of the replacement character if `c` is missing
+    fn glyph_or_replacement(&self, c: char) -> Option<&'a Glyph> {
+        self.glyph(c).or_else(|| self.glyph(self.replacement))
+    }
+
+    /// Pen movement of `c` including the character spacing
+    fn advance(&self, c: char) -> u32 {
+        self.glyph_or_replacement(c)
+            .map_or(0, |glyph| glyph.advance as u32 + self.character_spacing)
+    }
+
+    /// Width of `text` on one line, without spacing after the last character
+    pub fn width(&self, text: &str) -> u32 {
+        text.chars()
+            .map(|c| self.advance(c))
+            .sum::<u32>()
+            .saturating_sub(self.character_spacing)
+    }
+
+    /// Points of the ink of `glyph`, relative to the top left of its cell
+    fn ink(&self, glyph: &Glyph) -> impl Iterator<Item = Point> + 'a {
+        let bitmap = self.bitmap;
+        let (offset, width, height) = (glyph.offset, glyph.width as u32, glyph.height as u32);
+        let origin = Point::new(glyph.x as i32, glyph.y as i32);
+        (0..width * height).filter_map(move |i| {
+            let bit = (offset + i) as usize;
+            let set = bitmap
+                .get(bit / 8)
+                .is_some_and(|byte| byte & (0x80 >> (bit % 8)) != 0);
+            set.then(|| origin + Point::new((i % width) as i32, (i / width) as i32))
+        })
+    }
+}
+
+/// Text style for drawing a [`ProportionalFont`] with `Text`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProportionalTextStyle<'a, C> {
+    pub font: &'a ProportionalFont<'a>,
+    pub text_color: Option<C>,
+    pub background_color: Option<C>,
+}
+
+impl<'a, C: PixelColor> ProportionalTextStyle<'a, C> {
+    pub fn new(font: &'a ProportionalFont<'a>, text_color: C) -> Self {
+        Self {
+            font,
+            text_color: Some(text_color),
+            background_color: None,
+        }
+    }
+
+    /// Rows from the top of the cell to `baseline`, as for a `MonoFont`
+    fn baseline_offset(&self, baseline: Baseline) -> i32 {
+        let height = self.font.line_height.saturating_sub(1);
+        (match baseline {
+            Baseline::Top => 0,
+            Baseline::Bottom => height,
+            Baseline::Middle => height / 2,
+            Baseline::Alphabetic => self.font.baseline,
+        }) as i32
+    }
+
+    fn fill_background<D>(
+        &self,
+        top_left: Point,
+        width: u32,
+        target: &mut D,
+    ) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        match self.background_color {
+            Some(color) if width > 0 => target.fill_solid(
+                &Rectangle::new(top_left, Size::new(width, self.font.line_height)),
+                color,
+            ),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<C: PixelColor> TextRenderer for ProportionalTextStyle<'_, C> {
+    type Color = C;
+
+    fn draw_string<D>(
+        &self,
+        text: &str,
+        position: Point,
+        baseline: Baseline,
+        target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let mut top_left = position - Point::new(0, self.baseline_offset(baseline));
+        for c in text.chars() {
+            let advance = self.font.advance(c);
+            self.fill_background(top_left, advance, target)?;
+            if let (Some(glyph), Some(color)) = (self.font.glyph_or_replacement(c), self.text_color)
+            {
+                let pixels = self
+                    .font
+                    .ink(glyph)
+                    .map(|point| Pixel(top_left + point, color));
+                target.draw_iter(pixels)?;
+            }
+            top_left.x += advance as i32;
+        }
+        Ok(Point::new(top_left.x, position.y))
+    }
+
+    fn draw_whitespace<D>(
+        &self,
+        width: u32,
+        position: Point,
+        baseline: Baseline,
+        target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let top_left = position - Point::new(0, self.baseline_offset(baseline));
+        self.fill_background(top_left, width, target)?;
+        Ok(position + Point::new(width as i32, 0))
+    }
+
+    fn measure_string(&self, text: &str, position: Point, baseline: Baseline) -> TextMetrics {
+        let top_left = position - Point::new(0, self.baseline_offset(baseline));
+        let size = Size::new(self.font.width(text), self.font.line_height);
+        TextMetrics {
+            bounding_box: Rectangle::new(top_left, size),
+            next_position: position + size.x_axis(),
+        }
+    }
+
+    fn line_height(&self) -> u32 {
+        self.font.line_height
+    }
+}
+
+impl<C: PixelColor> CharacterStyle for ProportionalTextStyle<'_, C> {
+    type Color = C;
+
+    fn set_text_color(&mut self, text_color: Option<Self::Color>) {
+        self.text_color = text_color;
+    }
+
+    fn set_background_color(&mut self, background_color: Option<Self::Color>) {
+        self.background_color = background_color;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_graphics::{mock_display::MockDisplay, pixelcolor::BinaryColor, text::Text};
+
+    /// `text` drawn from the top left, ink `#` on a `.` background
+    fn draw(text: &str, font: &ProportionalFont) -> MockDisplay<BinaryColor> {
+        let style = ProportionalTextStyle {
+            background_color: Some(BinaryColor::Off),
+            ..ProportionalTextStyle::new(font, BinaryColor::On)
+        };
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        Text::with_baseline(text, Point::zero(), style, Baseline::Top)
+            .draw(&mut display)
+            .unwrap();
+        display
+    }
+
+    #[test]
+    fn glyphs_match_the_bdf_bitmaps() {
+        draw("Aä€", &BOLD_13).assert_pattern(&[
+            ".....................",
+            ".....................",
+            "..##....##.##...###..",
+            ".####...##.##..#####.",
+            "##..##.........##....",
+            "##..##..####..####...",
+            "##..##.....##..##....",
+            "######..#####.####...",
+            "##..##.##..##..##....",
+            "##..##.##.###..#####.",
+            "##..##..##.##...###..",
+            ".....................",
+            ".....................",
+        ]);
+    }
+
+    #[test]
+    fn advance_fits_the_ink() {
+        draw("!i", &BOLD_13).assert_pattern(&[
+            "........", //
+            "........", //
+            "##..##..", //
+            "##..##..", //
+            "##......", //
+            "##.###..", //
+            "##..##..", //
+            "##..##..", //
+            "##..##..", //
+            "....##..", //
+            "##.####.", //
+            "........", //
+            "........", //
+        ]);
+        assert_eq!(BOLD_13.width("!i"), 7);
+        assert_eq!(BOLD_13.width(""), 0);
+    }
+
+    #[test]
+    fn missing_characters_are_replaced() {
+        assert!(BOLD_13.glyph('→').is_none());
+        assert_eq!(draw("→", &BOLD_13), draw("?", &BOLD_13));
+        assert_eq!(BOLD_13.width("→"), BOLD_13.width("?"));
+    }
+
+    #[test]
+    fn ranges_are_kept() {
+        for font in [&BOLD_13, &BOLD_18] {
+            for c in ['A', '~', '\u{a0}', 'ž', '‘', '„', '€'] {
+                assert!(font.glyph(c).is_some(), "{c:?}");
+            }
+            for c in ['\u{7f}', 'ƀ', '‟', '₭'] {
+                assert!(font.glyph(c).is_none(), "{c:?}");
+            }
+            assert!(font.glyphs.windows(2).all(|pair| pair[0].c < pair[1].c));
+        }
+        assert_eq!((BOLD_13.line_height, BOLD_13.baseline), (13, 10));
+        assert_eq!((BOLD_18.line_height, BOLD_18.baseline), (18, 13));
+    }
+}
@@ -0,0 +1,348 @@
+//! Button chords and sequences
+//!
+//! Feed the [`ButtonEvent`]s from [`crate::buttons::Buttons`] into a
+//! [`Recognizer`] together with a static table of [`Gesture`]s, e.g.
+//!
+//! ```ignore
+//! static GESTURES: [Gesture; 2] = [
+//!     Gesture {
+//!         name: "factory-reset",
+//!         pattern: Pattern::Chord { buttons: &[Button::A, Button::C], hold_ms: 3000 },
+//!     },
+//!     Gesture {
+//!         name: "debug",
+//!         pattern: Pattern::Sequence {
+//!             buttons: &[Button::Up, Button::Up, Button::Down, Button::Down],
+//!             timeout_ms: 1000,
+//!         },
+//!     },
+//! ];
+//! ```
+
+use crate::buttons::{Button, ButtonEvent, EventKind};
+
+#[derive(Clone, Copy, Debug)]
+pub enum Pattern {
+    /// Exactly these buttons held down together for `hold_ms`
+    Chord {
+        buttons: &'static [Button],
+        hold_ms: u32,
+    },
+    /// These buttons pressed in order, at most `timeout_ms` apart
+    Sequence {
+        buttons: &'static [Button],
+        timeout_ms: u32,
+    },
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Gesture {
+    pub name: &'static str,
+    pub pattern: Pattern,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct State {
+    /// Chord: held since, Sequence: time of the last matching press
+    since: Option<u32>,
+    /// Chord: already reported for this hold, Sequence: completed together
+    /// with an earlier gesture and not reported yet
+    fired: bool,
+    /// Sequence: number of buttons matched so far
+    progress: usize,
+}
+
+/// Recognizes the gestures of a static table from a stream of button events
+pub struct Recognizer<const N: usize> {
+    gestures: &'static [Gesture; N],
+    state: [State; N],
+    held: u8,
+}
+
+fn mask(button: Button) -> u8 {
+    1 << button as u8
+}
+
+fn chord_mask(buttons: &[Button]) -> u8 {
+    buttons.iter().fold(0, |acc, &button| acc | mask(button))
+}
+
+/// Length of the longest prefix of `pattern` that ends the input, given that
+/// the previous input matched `pattern[..progress]` and `button` came next
+fn advance(pattern: &[Button], progress: usize, button: Button) -> usize {
+    for len in (1..=progress + 1).rev() {
+        let start = progress + 1 - len;
+        if pattern[len - 1] == button && pattern[..len - 1] == pattern[start..progress] {
+            return len;
+        }
+    }
+    0
+}
+
+impl<const N: usize> Recognizer<N> {
+    pub fn new(gestures: &'static [Gesture; N]) -> Self {
+        Self {
+            gestures,
+            state: [State::default(); N],
+            held: 0,
+        }
+    }
+
+    /// Process a button event, returns a recognized gesture
+    ///
+    /// If several gestures complete at once the first one in the table is
+    /// returned, the next calls to [`Self::tick`] return the others.
+    pub fn handle(&mut self, event: ButtonEvent, now_ms: u32) -> Option<&'static str> {
+        match event.kind {
+            EventKind::Press => self.held |= mask(event.button),
+            EventKind::Release => self.held &= !mask(event.button),
+            EventKind::LongPress | EventKind::Repeat => return self.tick(now_ms),
+        }
+
+        let mut found = None;
+        for (gesture, state) in self.gestures.iter().zip(self.state.iter_mut()) {
+            match gesture.pattern {
+                Pattern::Chord { buttons, .. } => {
+                    if self.held == chord_mask(buttons) {
+                        if state.since.is_none() {
+                            state.since = Some(now_ms);
+                            state.fired = false;
+                        }
+                    } else {
+                        state.since = None;
+                    }
+                }
+                Pattern::Sequence {
+                    buttons,
+                    timeout_ms,
+                } => {
+                    if event.kind != EventKind::Press || buttons.is_empty() {
+                        continue;
+                    }
+                    if let Some(since) = state.since {
+                        if now_ms.wrapping_sub(since) > timeout_ms {
+                            state.progress = 0;
+                        }
+                    }
+                    state.progress = advance(buttons, state.progress, event.button);
+                    state.since = Some(now_ms);
+                    if state.progress == buttons.len() {
+                        state.progress = 0;
+                        state.since = None;
+                        if found.is_none() {
+                            found = Some(gesture.name);
+                        } else {
+                            state.fired = true;
+                        }
+                    }
+                }
+            }
+        }
+
+        found.or_else(|| self.tick(now_ms))
+    }
+
+    /// Check chord hold times, call regularly while no events arrive
+    ///
+    /// Returns one gesture per call, if several are held long enough or
+    /// completed at once the next calls return the others in table order.
+    pub fn tick(&mut self, now_ms: u32) -> Option<&'static str> {
+        for (gesture, state) in self.gestures.iter().zip(self.state.iter_mut()) {
+            match gesture.pattern {
+                Pattern::Chord { hold_ms, .. } => match state.since {
+                    Some(since) if !state.fired && now_ms.wrapping_sub(since) >= hold_ms => {
+                        state.fired = true;
+                        return Some(gesture.name);
+                    }
+                    _ => {}
+                },
+                Pattern::Sequence { .. } if state.fired => {
+                    state.fired = false;
+                    return Some(gesture.name);
+                }
+                Pattern::Sequence { .. } => {}
+            }
+        }
+        None
+    }
+
+    /// Forget all held buttons and partial sequences
+    pub fn reset(&mut self) {
+        self.state = [State::default(); N];
+        self.held = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static GESTURES: [Gesture; 4] = [
+        Gesture {
+            name: "debug",
+            pattern: Pattern::Chord {
+                buttons: &[Button::A, Button::C],
+                hold_ms: 1000,
+            },
+        },
+        Gesture {
+            name: "factory-reset",
+            pattern: Pattern::Chord {
+                buttons: &[Button::A, Button::C],
+                hold_ms: 3000,
+            },
+        },
+        Gesture {
+            name: "also-debug",
+            pattern: Pattern::Chord {
+                buttons: &[Button::C, Button::A],
+                hold_ms: 1000,
+            },
+        },
+        Gesture {
+            name: "konami",
+            pattern: Pattern::Sequence {
+                buttons: &[Button::Up, Button::Up, Button::Down, Button::Down],
+                timeout_ms: 1000,
+            },
+        },
+    ];
+
+    fn press(button: Button) -> ButtonEvent {
+        ButtonEvent {
+            button,
+            kind: EventKind::Press,
+        }
+    }
+
+    fn release(button: Button) -> ButtonEvent {
+        ButtonEvent {
+            button,
+            kind: EventKind::Release,
+        }
+    }
+
+    /// Feed a timeline of events and ticks, returns the gestures with the
+    /// time they were recognized
+    fn run(timeline: &[(u32, Option<ButtonEvent>)]) -> Vec<(u32, &'static str)> {
+        let mut recognizer = Recognizer::new(&GESTURES);
+        let mut found = Vec::new();
+        for &(now, event) in timeline {
+            let mut gesture = match event {
+                Some(event) => recognizer.handle(event, now),
+                None => recognizer.tick(now),
+            };
+            while let Some(name) = gesture {
+                found.push((now, name));
+                gesture = recognizer.tick(now);
+            }
+        }
+        found
+    }
+
+    #[test]
+    fn overlapping_chords_all_fire() {
+        let found = run(&[
+            (0, Some(press(Button::A))),
+            (50, Some(press(Button::C))),
+            (500, None),
+            (1050, None),
+            (2000, None),
+            (3050, None),
+            (4000, None),
+        ]);
+        assert_eq!(
+            found,
+            [
+                (1050, "debug"),
+                (1050, "also-debug"),
+                (3050, "factory-reset")
+            ]
+        );
+    }
+
+    #[test]
+    fn chord_fires_once_per_hold() {
+        let found = run(&[
+            (0, Some(press(Button::A))),
+            (0, Some(press(Button::C))),
+            (1500, None),
+            (1600, Some(release(Button::C))),
+            (1700, Some(press(Button::C))),
+            (2000, None),
+            (2700, None),
+        ]);
+        assert_eq!(
+            found,
+            [
+                (1500, "debug"),
+                (1500, "also-debug"),
+                (2700, "debug"),
+                (2700, "also-debug")
+            ]
+        );
+    }
+
+    #[test]
+    fn extra_button_breaks_chord() {
+        let found = run(&[
+            (0, Some(press(Button::A))),
+            (0, Some(press(Button::C))),
+            (500, Some(press(Button::B))),
+            (1500, None),
+            (1600, Some(release(Button::B))),
+            (2000, None),
+            (2600, None),
+        ]);
+        assert_eq!(found, [(2600, "debug"), (2600, "also-debug")]);
+    }
+
+    #[test]
+    fn sequence_with_timeout() {
+        let mut timeline = Vec::new();
+        for (now, button) in [
+            (0, Button::Up),
+            (300, Button::Up),
+            (600, Button::Up),
+            (900, Button::Down),
+            (1200, Button::Down),
+            (5000, Button::Up),
+            (5500, Button::Up),
+            (7000, Button::Down),
+            (7300, Button::Down),
+        ] {
+            timeline.push((now, Some(press(button))));
+            timeline.push((now + 100, Some(release(button))));
+        }
+        assert_eq!(run(&timeline), [(1200, "konami")]);
+    }
+
+    #[test]
+    fn sequences_completing_together_all_fire() {
+        static SEQUENCES: [Gesture; 2] = [
+            Gesture {
+                name: "up-down-down",
+                pattern: Pattern::Sequence {
+                    buttons: &[Button::Up, Button::Down, Button::Down],
+                    timeout_ms: 1000,
+                },
+            },
+            Gesture {
+                name: "down-down",
+                pattern: Pattern::Sequence {
+                    buttons: &[Button::Down, Button::Down],
+                    timeout_ms: 1000,
+                },
+            },
+        ];
+        let mut recognizer = Recognizer::new(&SEQUENCES);
+        assert_eq!(recognizer.handle(press(Button::Up), 0), None);
+        assert_eq!(recognizer.handle(press(Button::Down), 100), None);
+        assert_eq!(
+            recognizer.handle(press(Button::Down), 200),
+            Some("up-down-down")
+        );
+        assert_eq!(recognizer.tick(200), Some("down-down"));
+        assert_eq!(recognizer.tick(300), None);
+    }
+}
@@ -1,30 +1,326 @@
+use core::fmt::Write;
+
 use embedded_graphics::{
-    image::Image,
+    geometry::AnchorPoint,
+    mono_font::{ascii::FONT_6X10, MonoTextStyle},
+    pixelcolor::BinaryColor,
     prelude::*,
-    text::{renderer::TextRenderer, Text},
+    primitives::{PrimitiveStyle, Rectangle, Triangle},
+    text::{Alignment, Baseline, Text, TextStyleBuilder},
 };
+use heapless::String;
+
+mod align;
+mod boxed;
+mod dither;
+mod rotated;
+mod rotated_text;
+mod scaled;
+mod text_box;
 
+pub use align::{Align, Insets};
+pub use boxed::{BoxStyle, Boxed, Label};
+pub use dither::{Dither, Dithered, Ditherer};
+pub use rotated::{Rotated, Rotation};
+pub use rotated_text::{RotatedText, StackedText};
+pub use scaled::Scaled;
+pub use text_box::{Fit, Lines, TextBox};
+
+/// Centering on a point, see [`Align`] for other anchors
 pub trait Centering {
     #[must_use]
     fn center(&self, at: Point) -> Self;
     fn center_mut(&mut self, at: Point) -> &mut Self;
 }
 
-impl<S: Clone + TextRenderer> Centering for Text<'_, S> {
+impl<T: Align> Centering for T {
     fn center(&self, at: Point) -> Self {
-        self.translate(at - self.bounding_box().center())
+        self.anchor(AnchorPoint::Center, at)
     }
+
     fn center_mut(&mut self, at: Point) -> &mut Self {
-        self.translate_mut(at - self.bounding_box().center())
+        self.anchor_mut(AnchorPoint::Center, at)
     }
 }
 
-impl<T: embedded_graphics::geometry::OriginDimensions> Centering for Image<'_, T> {
-    fn center(&self, at: Point) -> Self {
-        self.translate(at - self.bounding_box().center())
+/// Battery status for a 16 px high status bar
+///
+/// Draws a charging bolt when `charging`, a battery outline filled to
+/// `percent` and a percentage label, black on white.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BatteryIndicator {
+    pub top_left: Point,
+    pub percent: u8,
+    pub charging: bool,
+}
+
+impl BatteryIndicator {
+    /// Size of the indicator, it is always the same to keep it in place
+    pub const SIZE: Size = Size::new(62, 16);
+
+    pub fn new(top_left: Point, percent: u8, charging: bool) -> Self {
+        Self {
+            top_left,
+            percent: percent.min(100),
+            charging,
+        }
     }
+}
 
-    fn center_mut(&mut self, at: Point) -> &mut Self {
-        self.translate_mut(at - self.bounding_box().center())
+impl Dimensions for BatteryIndicator {
+    fn bounding_box(&self) -> Rectangle {
+        Rectangle::new(self.top_left, Self::SIZE)
+    }
+}
+
+impl Transform for BatteryIndicator {
+    fn translate(&self, by: Point) -> Self {
+        Self {
+            top_left: self.top_left + by,
+            ..*self
+        }
+    }
+
+    fn translate_mut(&mut self, by: Point) -> &mut Self {
+        self.top_left += by;
+        self
+    }
+}
+
+impl Drawable for BatteryIndicator {
+    type Color = BinaryColor;
+    type Output = ();
+
+    fn draw<D>(&self, target: &mut D) -> Result<Self::Output, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let at = self.top_left;
+        let black = PrimitiveStyle::with_fill(BinaryColor::Off);
+
+        self.bounding_box()
+            .into_styled(PrimitiveStyle::with_fill(BinaryColor::On))
+            .draw(target)?;
+
+        if self.charging {
+            Triangle::new(Point::new(5, 2), Point::new(0, 9), Point::new(4, 9))
+                .translate(at)
+                .into_styled(black)
+                .draw(target)?;
+            Triangle::new(Point::new(2, 7), Point::new(6, 7), Point::new(1, 14))
+                .translate(at)
+                .into_styled(black)
+                .draw(target)?;
+        }
+
+        Rectangle::new(at + Point::new(9, 2), Size::new(24, 12))
+            .into_styled(PrimitiveStyle::with_stroke(BinaryColor::Off, 1))
+            .draw(target)?;
+        Rectangle::new(at + Point::new(33, 6), Size::new(2, 4))
+            .into_styled(black)
+            .draw(target)?;
+        Rectangle::new(
+            at + Point::new(11, 4),
+            Size::new(20 * self.percent as u32 / 100, 8),
+        )
+        .into_styled(black)
+        .draw(target)?;
+
+        let mut label: String<4> = String::new();
+        // Can't fail, at most "100%" fits the buffer
+        let _ = write!(label, "{}%", self.percent);
+        Text::with_baseline(
+            &label,
+            at + Point::new(38, 8),
+            MonoTextStyle::new(&FONT_6X10, BinaryColor::Off),
+            Baseline::Middle,
+        )
+        .draw(target)?;
+
+        Ok(())
+    }
+}
+
+/// A line of white on black text filling `area`, used for notices over a badge
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Banner<'a> {
+    pub text: &'a str,
+    pub area: Rectangle,
+}
+
+impl<'a> Banner<'a> {
+    pub fn new(text: &'a str, area: Rectangle) -> Self {
+        Self { text, area }
+    }
+}
+
+impl Dimensions for Banner<'_> {
+    fn bounding_box(&self) -> Rectangle {
+        self.area
+    }
+}
+
+impl Transform for Banner<'_> {
+    fn translate(&self, by: Point) -> Self {
+        Self {
+            area: self.area.translate(by),
+            ..*self
+        }
+    }
+
+    fn translate_mut(&mut self, by: Point) -> &mut Self {
+        self.area.translate_mut(by);
+        self
+    }
+}
+
+impl Drawable for Banner<'_> {
+    type Color = BinaryColor;
+    type Output = ();
+
+    fn draw<D>(&self, target: &mut D) -> Result<Self::Output, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        self.area
+            .into_styled(PrimitiveStyle::with_fill(BinaryColor::Off))
+            .draw(target)?;
+        Text::with_text_style(
+            self.text,
+            self.area.center(),
+            MonoTextStyle::new(&FONT_6X10, BinaryColor::On),
+            TextStyleBuilder::new()
+                .alignment(Alignment::Center)
+                .baseline(Baseline::Middle)
+                .build(),
+        )
+        .draw(&mut target.clipped(&self.area))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_graphics::mock_display::MockDisplay;
+
+    fn draw(drawable: &impl Drawable<Color = BinaryColor>) -> MockDisplay<BinaryColor> {
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        drawable.draw(&mut display).unwrap();
+        display
+    }
+
+    fn count(display: &MockDisplay<BinaryColor>, area: Rectangle, color: BinaryColor) -> usize {
+        area.points()
+            .filter(|&p| display.get_pixel(p) == Some(color))
+            .count()
+    }
+
+    /// Bounding box of the pixels drawn in `color`
+    fn extent(display: &MockDisplay<BinaryColor>, color: BinaryColor) -> Rectangle {
+        let points = display
+            .bounding_box()
+            .points()
+            .filter(|&p| display.get_pixel(p) == Some(color));
+        let (min, max) = points.fold(
+            (
+                Point::new(i32::MAX, i32::MAX),
+                Point::new(i32::MIN, i32::MIN),
+            ),
+            |(min, max), p| (min.component_min(p), max.component_max(p)),
+        );
+        Rectangle::with_corners(min, max)
+    }
+
+    #[test]
+    fn battery_fits_status_bar() {
+        for (percent, charging) in [(0, false), (100, true), (255, true)] {
+            let battery = BatteryIndicator::new(Point::new(1, 2), percent, charging);
+            assert!(battery.bounding_box().size.height <= 16);
+            assert_eq!(draw(&battery).affected_area(), battery.bounding_box());
+        }
+        assert_eq!(
+            BatteryIndicator::new(Point::zero(), 255, false).percent,
+            100
+        );
+    }
+
+    #[test]
+    fn battery_fill_follows_percent() {
+        let inside = Rectangle::new(Point::new(11, 4), Size::new(20, 8));
+        for (percent, columns) in [(0, 0), (50, 10), (100, 20)] {
+            let display = draw(&BatteryIndicator::new(Point::zero(), percent, false));
+            assert_eq!(
+                count(&display, inside, BinaryColor::Off),
+                columns * 8,
+                "{percent}%"
+            );
+        }
+    }
+
+    #[test]
+    fn battery_bolt_only_when_charging() {
+        let bolt = Rectangle::new(Point::zero(), Size::new(8, 16));
+        let charging = draw(&BatteryIndicator::new(Point::zero(), 50, true));
+        let idle = draw(&BatteryIndicator::new(Point::zero(), 50, false));
+        assert!(count(&charging, bolt, BinaryColor::Off) > 0);
+        assert_eq!(count(&idle, bolt, BinaryColor::Off), 0);
+    }
+
+    #[test]
+    fn battery_label_shows_percent() {
+        let label = Rectangle::new(Point::new(36, 0), Size::new(26, 16));
+        for percent in [0, 50, 100] {
+            let display = draw(&BatteryIndicator::new(Point::zero(), percent, false));
+            let mut expected = MockDisplay::new();
+            expected.set_allow_overdraw(true);
+            label
+                .into_styled(PrimitiveStyle::with_fill(BinaryColor::On))
+                .draw(&mut expected)
+                .unwrap();
+            let mut text: String<4> = String::new();
+            write!(text, "{percent}%").unwrap();
+            Text::with_baseline(
+                &text,
+                Point::new(38, 8),
+                MonoTextStyle::new(&FONT_6X10, BinaryColor::Off),
+                Baseline::Middle,
+            )
+            .draw(&mut expected)
+            .unwrap();
+            for p in label.points() {
+                assert_eq!(
+                    display.get_pixel(p),
+                    expected.get_pixel(p),
+                    "{percent}% {p:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn banner_fills_area_and_centers_text() {
+        let area = Rectangle::new(Point::new(2, 3), Size::new(40, 14));
+        let banner = Banner::new("Hi", area);
+        assert_eq!(banner.bounding_box(), area);
+
+        let display = draw(&banner);
+        assert_eq!(display.affected_area(), area);
+        let text = extent(&display, BinaryColor::On);
+        let (center, expected) = (text.center(), area.center());
+        assert!((center.x - expected.x).abs() <= 1, "{text:?}");
+        assert!((center.y - expected.y).abs() <= 1, "{text:?}");
+    }
+
+    #[test]
+    fn banner_clips_long_text() {
+        let area = Rectangle::new(Point::new(10, 10), Size::new(20, 8));
+        let display = draw(&Banner::new("Much too long for the banner", area));
+        assert_eq!(display.affected_area(), area);
+        assert_eq!(
+            extent(&display, BinaryColor::On).top_left.x,
+            area.top_left.x
+        );
     }
 }
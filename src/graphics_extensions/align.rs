@@ -0,0 +1,282 @@
+//! Placing drawables by their anchor points
+
+use embedded_graphics::{geometry::AnchorPoint, prelude::*, primitives::Rectangle};
+
+/// Space left free at each edge of an area, used for margins and padding
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Insets {
+    pub top: u32,
+    pub right: u32,
+    pub bottom: u32,
+    pub left: u32,
+}
+
+impl Insets {
+    pub const ZERO: Self = Self::all(0);
+
+    /// The same space at every edge
+    pub const fn all(inset: u32) -> Self {
+        Self {
+            top: inset,
+            right: inset,
+            bottom: inset,
+            left: inset,
+        }
+    }
+
+    /// `horizontal` at the left and right edges, `vertical` at the top and
+    /// bottom
+    pub const fn symmetric(horizontal: u32, vertical: u32) -> Self {
+        Self {
+            top: vertical,
+            right: horizontal,
+            bottom: vertical,
+            left: horizontal,
+        }
+    }
+
+    /// Total width and height taken by the insets
+    pub const fn size(&self) -> Size {
+        Size::new(self.left + self.right, self.top + self.bottom)
+    }
+
+    /// What is left of `area` inside the insets, empty if they don't fit
+    pub fn shrink(&self, area: &Rectangle) -> Rectangle {
+        Rectangle::new(
+            area.top_left + Point::new(self.left as i32, self.top as i32),
+            area.size.saturating_sub(self.size()),
+        )
+    }
+
+    /// `area` grown by the insets
+    pub fn grow(&self, area: &Rectangle) -> Rectangle {
+        Rectangle::new(
+            area.top_left - Point::new(self.left as i32, self.top as i32),
+            area.size + self.size(),
+        )
+    }
+}
+
+impl From<u32> for Insets {
+    fn from(inset: u32) -> Self {
+        Self::all(inset)
+    }
+}
+
+/// Moving anything with a bounding box by its anchor points
+///
+/// Implemented for everything that can be translated: text, images,
+/// primitives, styled primitives and the drawables in this module.
+///
+/// ```ignore
+/// let screen = display.bounding_box();
+/// Text::new("Hello", Point::zero(), style)
+///     .align_with_margin(&screen, AnchorPoint::BottomRight, 4)
+///     .draw(&mut display)?;
+/// ```
+pub trait Align: Transform + Dimensions + Sized {
+    /// Move so that the `anchor` point of the bounding box is at `at`
+    #[must_use]
+    fn anchor(&self, anchor: AnchorPoint, at: Point) -> Self {
+        self.translate(at - self.bounding_box().anchor_point(anchor))
+    }
+
+    /// Move so that the `anchor` point of the bounding box is at `at`
+    fn anchor_mut(&mut self, anchor: AnchorPoint, at: Point) -> &mut Self {
+        self.translate_mut(at - self.bounding_box().anchor_point(anchor))
+    }
+
+    /// Move into `area`, e.g. to its top right corner or its center
+    ///
+    /// Centered objects that can't be centered exactly are placed a pixel up
+    /// and to the left.
+    #[must_use]
+    fn align(&self, area: &Rectangle, anchor: AnchorPoint) -> Self {
+        self.align_with_margin(area, anchor, Insets::ZERO)
+    }
+
+    /// Move into `area`, e.g. to its top right corner or its center
+    fn align_mut(&mut self, area: &Rectangle, anchor: AnchorPoint) -> &mut Self {
+        self.align_with_margin_mut(area, anchor, Insets::ZERO)
+    }
+
+    /// Move into `area` keeping `margin` away from its edges
+    #[must_use]
+    fn align_with_margin(
+        &self,
+        area: &Rectangle,
+        anchor: AnchorPoint,
+        margin: impl Into<Insets>,
+    ) -> Self {
+        self.translate(offset(self, area, anchor, margin.into()))
+    }
+
+    /// Move into `area` keeping `margin` away from its edges
+    fn align_with_margin_mut(
+        &mut self,
+        area: &Rectangle,
+        anchor: AnchorPoint,
+        margin: impl Into<Insets>,
+    ) -> &mut Self {
+        let by = offset(self, area, anchor, margin.into());
+        self.translate_mut(by)
+    }
+}
+
+impl<T: Transform + Dimensions> Align for T {}
+
+/// How far `object` has to move to be at `anchor` in `area`
+fn offset<T: Dimensions>(
+    object: &T,
+    area: &Rectangle,
+    anchor: AnchorPoint,
+    margin: Insets,
+) -> Point {
+    let bounding_box = object.bounding_box();
+    let area = margin.shrink(area);
+    let free = Point::new(
+        area.size.width as i32 - bounding_box.size.width as i32,
+        area.size.height as i32 - bounding_box.size.height as i32,
+    );
+    // Halves of the free space before the object, rounded down so that
+    // centering is off to the top left also when the object doesn't fit
+    let (x, y) = match anchor {
+        AnchorPoint::TopLeft => (0, 0),
+        AnchorPoint::TopCenter => (1, 0),
+        AnchorPoint::TopRight => (2, 0),
+        AnchorPoint::CenterLeft => (0, 1),
+        AnchorPoint::Center => (1, 1),
+        AnchorPoint::CenterRight => (2, 1),
+        AnchorPoint::BottomLeft => (0, 2),
+        AnchorPoint::BottomCenter => (1, 2),
+        AnchorPoint::BottomRight => (2, 2),
+    };
+    let before = Point::new((free.x * x).div_euclid(2), (free.y * y).div_euclid(2));
+    area.top_left + before - bounding_box.top_left
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rectangle(x: i32, y: i32, width: u32, height: u32) -> Rectangle {
+        Rectangle::new(Point::new(x, y), Size::new(width, height))
+    }
+
+    /// Top left of a `size` square centered in a `space` square at the origin
+    fn centered(size: u32, space: u32) -> Point {
+        rectangle(50, 50, size, size)
+            .align(&rectangle(0, 0, space, space), AnchorPoint::Center)
+            .top_left
+    }
+
+    #[test]
+    fn center_rounds_up_and_left() {
+        // Even space, even and odd objects
+        assert_eq!(centered(4, 10), Point::new(3, 3));
+        assert_eq!(centered(3, 10), Point::new(3, 3));
+        // Odd space, odd and even objects
+        assert_eq!(centered(3, 9), Point::new(3, 3));
+        assert_eq!(centered(4, 9), Point::new(2, 2));
+        // Larger than the space
+        assert_eq!(centered(12, 10), Point::new(-1, -1));
+        assert_eq!(centered(13, 10), Point::new(-2, -2));
+    }
+
+    #[test]
+    fn center_edges_round_the_same() {
+        let area = rectangle(10, 20, 9, 9);
+        let object = rectangle(0, 0, 4, 4);
+        assert_eq!(
+            object.align(&area, AnchorPoint::TopCenter).top_left,
+            Point::new(12, 20)
+        );
+        assert_eq!(
+            object.align(&area, AnchorPoint::CenterLeft).top_left,
+            Point::new(10, 22)
+        );
+        assert_eq!(
+            object.align(&area, AnchorPoint::BottomCenter).top_left,
+            Point::new(12, 25)
+        );
+        assert_eq!(
+            object.align(&area, AnchorPoint::CenterRight).top_left,
+            Point::new(15, 22)
+        );
+    }
+
+    #[test]
+    fn corners_with_margin() {
+        let area = rectangle(0, 0, 296, 128);
+        let object = rectangle(7, 9, 21, 11);
+        let margin = Insets {
+            top: 1,
+            right: 2,
+            bottom: 3,
+            left: 4,
+        };
+        let aligned = |anchor| object.align_with_margin(&area, anchor, margin).top_left;
+        assert_eq!(aligned(AnchorPoint::TopLeft), Point::new(4, 1));
+        assert_eq!(aligned(AnchorPoint::TopRight), Point::new(273, 1));
+        assert_eq!(aligned(AnchorPoint::BottomLeft), Point::new(4, 114));
+        assert_eq!(aligned(AnchorPoint::BottomRight), Point::new(273, 114));
+
+        let mut moved = object;
+        moved.align_with_margin_mut(&area, AnchorPoint::BottomRight, 5);
+        assert_eq!(moved, rectangle(270, 112, 21, 11));
+    }
+
+    #[test]
+    fn anchor_at_point() {
+        let object = rectangle(0, 0, 5, 4);
+        assert_eq!(
+            object.anchor(AnchorPoint::Center, Point::new(10, 10)),
+            rectangle(8, 9, 5, 4)
+        );
+        assert_eq!(
+            object.anchor(AnchorPoint::BottomRight, Point::new(10, 10)),
+            rectangle(6, 7, 5, 4)
+        );
+    }
+
+    #[test]
+    fn insets() {
+        let area = rectangle(10, 10, 20, 8);
+        let insets = Insets::symmetric(3, 2);
+        assert_eq!(insets.size(), Size::new(6, 4));
+        assert_eq!(insets.shrink(&area), rectangle(13, 12, 14, 4));
+        assert_eq!(insets.grow(&insets.shrink(&area)), area);
+        assert_eq!(Insets::all(5).shrink(&area), rectangle(15, 15, 10, 0));
+        assert_eq!(Insets::from(2), Insets::all(2));
+    }
+
+    #[test]
+    fn every_anchor_with_odd_and_even_sizes() {
+        use AnchorPoint::*;
+        for (space, size, center) in [(10, 4, 3), (10, 3, 3), (9, 3, 3), (9, 4, 2), (10, 13, -2)] {
+            let area = rectangle(100, 200, space, space);
+            let end = space as i32 - size as i32;
+            for (anchor, x, y) in [
+                (TopLeft, 0, 0),
+                (TopCenter, center, 0),
+                (TopRight, end, 0),
+                (CenterLeft, 0, center),
+                (Center, center, center),
+                (CenterRight, end, center),
+                (BottomLeft, 0, end),
+                (BottomCenter, center, end),
+                (BottomRight, end, end),
+            ] {
+                let aligned = rectangle(-7, 3, size, size).align(&area, anchor);
+                assert_eq!(
+                    aligned.top_left,
+                    Point::new(100 + x, 200 + y),
+                    "{:?} of {} in {}",
+                    anchor,
+                    size,
+                    space
+                );
+            }
+        }
+    }
+}
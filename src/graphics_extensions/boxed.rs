@@ -0,0 +1,268 @@
+//! Frames around text, images and other drawables
+
+use embedded_graphics::{
+    mono_font::{MonoFont, MonoTextStyle},
+    pixelcolor::BinaryColor,
+    prelude::*,
+    primitives::{PrimitiveStyleBuilder, Rectangle, RoundedRectangle, StrokeAlignment},
+    text::{Baseline, Text},
+};
+
+use super::Insets;
+
+/// How [`Boxed`] frames its content
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BoxStyle {
+    /// Width of the border, black or white when inverted, 0 for none
+    pub border: u32,
+    /// Space between the border and the content
+    pub padding: Insets,
+    /// Radius of the corners, 0 for square ones
+    pub radius: u32,
+    /// Clear what is under the box before drawing the content
+    pub fill: bool,
+    /// Black and white swapped in the content and fill, for white text on
+    /// black
+    pub inverted: bool,
+    /// Offset of a black shadow to the bottom right, 0 for none
+    pub shadow: u32,
+}
+
+impl Default for BoxStyle {
+    fn default() -> Self {
+        Self {
+            border: 1,
+            padding: Insets::all(4),
+            radius: 0,
+            fill: true,
+            inverted: false,
+            shadow: 0,
+        }
+    }
+}
+
+/// Drawable content with a border, padding and background
+///
+/// The bounding box covers the whole box including the shadow, so a boxed
+/// drawable can be centered, aligned and placed in layouts like its content.
+///
+/// ```ignore
+/// let style = BoxStyle { radius: 4, shadow: 2, ..BoxStyle::default() };
+/// Label::text("Hello", &FONT_10X20, style)
+///     .align(&display.bounding_box(), AnchorPoint::Center)
+///     .draw(&mut display)?;
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Boxed<T> {
+    pub content: T,
+    pub style: BoxStyle,
+}
+
+/// Text in a box
+pub type Label<'a> = Boxed<Text<'a, MonoTextStyle<'a, BinaryColor>>>;
+
+impl<T> Boxed<T> {
+    pub fn new(content: T, style: BoxStyle) -> Self {
+        Self { content, style }
+    }
+}
+
+impl<'a> Label<'a> {
+    /// `text` in `font` with its box at the origin
+    pub fn text(text: &'a str, font: &'a MonoFont<'a>, style: BoxStyle) -> Self {
+        let font = MonoTextStyle::new(font, BinaryColor::Off);
+        let text = Text::with_baseline(text, Point::zero(), font, Baseline::Top);
+        let inset = Point::new(
+            (style.border + style.padding.left) as i32,
+            (style.border + style.padding.top) as i32,
+        );
+        Self::new(text.translate(inset), style)
+    }
+}
+
+impl<T: Dimensions> Boxed<T> {
+    /// The border and everything inside it, without the shadow
+    pub fn frame(&self) -> Rectangle {
+        let border = Insets::all(self.style.border);
+        border.grow(&self.style.padding.grow(&self.content.bounding_box()))
+    }
+}
+
+impl<T: Dimensions> Dimensions for Boxed<T> {
+    fn bounding_box(&self) -> Rectangle {
+        let frame = self.frame();
+        Rectangle::new(
+            frame.top_left,
+            frame.size + Size::new_equal(self.style.shadow),
+        )
+    }
+}
+
+impl<T: Transform> Transform for Boxed<T> {
+    fn translate(&self, by: Point) -> Self {
+        Self {
+            content: self.content.translate(by),
+            style: self.style,
+        }
+    }
+
+    fn translate_mut(&mut self, by: Point) -> &mut Self {
+        self.content.translate_mut(by);
+        self
+    }
+}
+
+impl<T> Drawable for Boxed<T>
+where
+    T: Drawable<Color = BinaryColor> + Dimensions,
+{
+    type Color = BinaryColor;
+    type Output = T::Output;
+
+    fn draw<D>(&self, target: &mut D) -> Result<Self::Output, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let style = &self.style;
+        let frame = self.frame();
+        let corners = Size::new_equal(style.radius);
+        let outline = RoundedRectangle::with_equal_corners(frame, corners);
+
+        if style.shadow > 0 {
+            // Only where it sticks out, without fill whatever is under the
+            // box has to stay visible
+            let shadow = outline.translate(Point::new_equal(style.shadow as i32));
+            target.draw_iter(
+                shadow
+                    .points()
+                    .filter(|&point| !outline.contains(point))
+                    .map(|point| Pixel(point, BinaryColor::Off)),
+            )?;
+        }
+
+        let mut box_style = PrimitiveStyleBuilder::new()
+            .stroke_color(BinaryColor::from(style.inverted))
+            .stroke_width(style.border)
+            .stroke_alignment(StrokeAlignment::Inside);
+        if style.fill {
+            box_style = box_style.fill_color(BinaryColor::from(!style.inverted));
+        }
+        outline.into_styled(box_style.build()).draw(target)?;
+
+        if style.inverted {
+            self.content.draw(&mut Inverted(target))
+        } else {
+            self.content.draw(target)
+        }
+    }
+}
+
+/// Draws black as white and white as black
+struct Inverted<'a, D>(&'a mut D);
+
+impl<D: Dimensions> Dimensions for Inverted<'_, D> {
+    fn bounding_box(&self) -> Rectangle {
+        self.0.bounding_box()
+    }
+}
+
+impl<D: DrawTarget<Color = BinaryColor>> DrawTarget for Inverted<'_, D> {
+    type Color = BinaryColor;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        self.0.draw_iter(
+            pixels
+                .into_iter()
+                .map(|Pixel(point, color)| Pixel(point, color.invert())),
+        )
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        self.0
+            .fill_contiguous(area, colors.into_iter().map(|color| color.invert()))
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.0.fill_solid(area, color.invert())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_graphics::{mock_display::MockDisplay, primitives::PrimitiveStyle};
+
+    /// A two pixel black bar boxed with `style`
+    fn draw(style: BoxStyle) -> MockDisplay<BinaryColor> {
+        let bar = Rectangle::new(Point::new(2, 2), Size::new(2, 1))
+            .into_styled(PrimitiveStyle::with_fill(BinaryColor::Off));
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        Boxed::new(bar, style).draw(&mut display).unwrap();
+        display
+    }
+
+    const STYLE: BoxStyle = BoxStyle {
+        border: 1,
+        padding: Insets::all(1),
+        radius: 0,
+        fill: true,
+        inverted: false,
+        shadow: 0,
+    };
+
+    #[test]
+    fn black_border_on_white() {
+        let boxed = Boxed::new(Rectangle::new(Point::new(2, 2), Size::new(2, 1)), STYLE);
+        assert_eq!(
+            boxed.frame(),
+            Rectangle::new(Point::zero(), Size::new(6, 5))
+        );
+        draw(STYLE).assert_pattern(&[
+            "......", //
+            ".####.", //
+            ".#..#.", //
+            ".####.", //
+            "......", //
+        ]);
+    }
+
+    #[test]
+    fn inverted_border_shows() {
+        let style = BoxStyle {
+            inverted: true,
+            ..STYLE
+        };
+        draw(style).assert_pattern(&[
+            "######", //
+            "#....#", //
+            "#.##.#", //
+            "#....#", //
+            "######", //
+        ]);
+    }
+
+    #[test]
+    fn shadow_without_fill() {
+        let style = BoxStyle {
+            fill: false,
+            shadow: 1,
+            ..STYLE
+        };
+        draw(style).assert_pattern(&[
+            "...... ", //
+            ".    ..", //
+            ". .. ..", //
+            ".    ..", //
+            ".......", //
+            " ......", //
+        ]);
+    }
+}
@@ -0,0 +1,304 @@
+//! Dithering greyscale and color images onto the black and white panel
+
+use core::marker::PhantomData;
+
+use embedded_graphics::{
+    image::ImageDrawable,
+    pixelcolor::{BinaryColor, Gray8},
+    prelude::*,
+    primitives::Rectangle,
+};
+
+mod ditherer;
+
+pub use ditherer::{Dither, Ditherer};
+
+/// Draws a greyscale or color image on a [`BinaryColor`] target with dithering
+///
+/// Wraps any image whose colors convert to [`Gray8`], like `Rgb888` BMP and
+/// TGA images, and is drawn with [`embedded_graphics::image::Image`]. Only a
+/// few rows of `W` pixels are buffered, the default fits the whole screen width.
+///
+/// ```ignore
+/// let tga: Tga<Rgb888> = Tga::from_slice(data).unwrap();
+/// let portrait = Dithered::<_>::new(tga, Dither::Atkinson);
+/// Image::new(&portrait, Point::zero()).draw(&mut display)?;
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Dithered<I, const W: usize = { uc8151::WIDTH as usize }> {
+    image: I,
+    method: Dither,
+}
+
+impl<I, const W: usize> Dithered<I, W> {
+    pub fn new(image: I, method: Dither) -> Self {
+        Self { image, method }
+    }
+
+    /// Release the wrapped image
+    pub fn into_inner(self) -> I {
+        self.image
+    }
+}
+
+impl<I: OriginDimensions, const W: usize> OriginDimensions for Dithered<I, W> {
+    fn size(&self) -> Size {
+        self.image.size()
+    }
+}
+
+/// Why drawing into [`Capture`] stopped
+enum Stop<E> {
+    /// The image didn't send its pixels row by row
+    OutOfOrder,
+    Target(E),
+}
+
+/// Collects the pixels of a row and draws it dithered once complete
+struct Capture<'a, D, C, const W: usize> {
+    target: &'a mut D,
+    ditherer: Ditherer<W>,
+    size: Size,
+    row: [u8; W],
+    /// Next pixel expected when streaming, the row being collected otherwise
+    next: Point,
+    /// Accept pixels of the current row in any order
+    any_order: bool,
+    color: PhantomData<C>,
+}
+
+impl<'a, D, C, const W: usize> Capture<'a, D, C, W>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    fn new(target: &'a mut D, method: Dither, size: Size, any_order: bool) -> Self {
+        Self {
+            target,
+            ditherer: Ditherer::new(method),
+            size,
+            row: [255; W],
+            next: Point::zero(),
+            any_order,
+            color: PhantomData,
+        }
+    }
+
+    fn color(level: u8) -> BinaryColor {
+        BinaryColor::from(level >= 128)
+    }
+
+    fn flush_row(&mut self) -> Result<(), D::Error> {
+        let width = (self.size.width as usize).min(W);
+        let y = self.next.y;
+        self.ditherer.dither_row(y as u32, &mut self.row[..width]);
+        let pixels = self.row[..width]
+            .iter()
+            .enumerate()
+            .map(|(x, &level)| Pixel(Point::new(x as i32, y), Self::color(level)));
+        self.target.draw_iter(pixels)?;
+        self.row = [255; W];
+        self.next = Point::new(0, y + 1);
+        Ok(())
+    }
+
+    fn store(&mut self, point: Point, luma: u8) -> Result<(), D::Error> {
+        match point.x as usize {
+            x if x < W => self.row[x] = luma,
+            // Too wide to buffer, fall back to a plain threshold
+            _ => self.target.draw_iter([Pixel(point, Self::color(luma))])?,
+        }
+        Ok(())
+    }
+}
+
+impl<D, C, const W: usize> Dimensions for Capture<'_, D, C, W> {
+    fn bounding_box(&self) -> Rectangle {
+        Rectangle::new(Point::zero(), self.size)
+    }
+}
+
+impl<D, C, const W: usize> DrawTarget for Capture<'_, D, C, W>
+where
+    D: DrawTarget<Color = BinaryColor>,
+    C: PixelColor + Into<Gray8>,
+{
+    type Color = C;
+    type Error = Stop<D::Error>;
+
+    fn draw_iter<P>(&mut self, pixels: P) -> Result<(), Self::Error>
+    where
+        P: IntoIterator<Item = Pixel<C>>,
+    {
+        let area = self.bounding_box();
+        for Pixel(point, color) in pixels {
+            if !area.contains(point) {
+                continue;
+            }
+            let luma = color.into().luma();
+
+            if self.any_order {
+                if point.y == 0 {
+                    self.store(point, luma).map_err(Stop::Target)?;
+                }
+                continue;
+            }
+
+            if point != self.next {
+                return Err(Stop::OutOfOrder);
+            }
+            self.store(point, luma).map_err(Stop::Target)?;
+            self.next.x += 1;
+            if self.next.x == self.size.width as i32 {
+                self.flush_row().map_err(Stop::Target)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<I, const W: usize> ImageDrawable for Dithered<I, W>
+where
+    I: ImageDrawable,
+    I::Color: Into<Gray8>,
+{
+    type Color = BinaryColor;
+
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let size = self.image.size();
+
+        // Most images send their pixels row by row, dither them as they come
+        let mut capture = Capture::<_, I::Color, W>::new(target, self.method, size, false);
+        match self.image.draw(&mut capture) {
+            Ok(()) => return Ok(()),
+            Err(Stop::Target(err)) => return Err(err),
+            Err(Stop::OutOfOrder) => {}
+        }
+
+        // Otherwise draw the image again one row at a time
+        let mut capture = Capture::<_, I::Color, W>::new(target, self.method, size, true);
+        for y in 0..size.height as i32 {
+            capture.next = Point::new(0, y);
+            let row = Rectangle::new(Point::new(0, y), Size::new(size.width, 1));
+            match self.image.draw_sub_image(&mut capture, &row) {
+                Ok(()) | Err(Stop::OutOfOrder) => {}
+                Err(Stop::Target(err)) => return Err(err),
+            }
+            capture.flush_row()?;
+        }
+        Ok(())
+    }
+
+    fn draw_sub_image<D>(&self, target: &mut D, area: &Rectangle) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        // Dithering the whole image keeps the pattern the same as in `draw`
+        self.draw(&mut target.translated(-area.top_left).clipped(area))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_graphics::{
+        image::{Image, ImageRaw},
+        mock_display::MockDisplay,
+    };
+
+    /// A 16 by 4 gradient from black to white, left to right
+    const GRADIENT: [u8; 64] = {
+        let mut data = [0; 64];
+        let mut i = 0;
+        while i < 64 {
+            data[i] = (i % 16 * 17) as u8;
+            i += 1;
+        }
+        data
+    };
+
+    /// Draw the dithered gradient, `#` is white and `.` black
+    fn assert_snapshot(method: Dither, expected: &[&str]) {
+        let gradient = ImageRaw::<Gray8>::new(&GRADIENT, 16);
+        let mut display = MockDisplay::new();
+        Image::new(&Dithered::<_, 16>::new(gradient, method), Point::zero())
+            .draw(&mut display)
+            .unwrap();
+        display.assert_pattern(expected);
+    }
+
+    #[test]
+    fn threshold() {
+        assert_snapshot(
+            Dither::Threshold,
+            &[
+                "........########",
+                "........########",
+                "........########",
+                "........########",
+            ],
+        );
+    }
+
+    #[test]
+    fn floyd_steinberg() {
+        assert_snapshot(
+            Dither::FloydSteinberg,
+            &[
+                "......#.#.######",
+                "....#..#.##.####",
+                ".....#.#.#.#####",
+                "...#..#.####.###",
+            ],
+        );
+    }
+
+    #[test]
+    fn atkinson() {
+        assert_snapshot(
+            Dither::Atkinson,
+            &[
+                ".......##.######",
+                ".....#..########",
+                ".....##..#.#####",
+                ".......###.#####",
+            ],
+        );
+    }
+
+    #[test]
+    fn bayer4() {
+        assert_snapshot(
+            Dither::Bayer4,
+            &[
+                "....#.#.########",
+                ".....#.#.#.#####",
+                "..#.#.#.#.######",
+                ".......#.#.#.###",
+            ],
+        );
+    }
+
+    #[test]
+    fn bayer8() {
+        assert_snapshot(
+            Dither::Bayer8,
+            &[
+                "..#.#.#.########",
+                ".....#.#.#.#####",
+                "..#.#.#.#.######",
+                ".......#.#.#.###",
+            ],
+        );
+    }
+
+    #[test]
+    fn rows_wider_than_buffer_are_thresholded() {
+        let mut ditherer = Ditherer::<4>::new(Dither::FloydSteinberg);
+        let mut row = [100, 100, 100, 100, 100, 127, 128, 200];
+        ditherer.dither_row(0, &mut row);
+        assert_eq!(&row[4..], [0, 0, 255, 255]);
+    }
+}
@@ -0,0 +1,110 @@
+//! Dithering of luma rows, shared by [`super::Dithered`] and the images
+//! converted by `build.rs`, which includes this file, so both give the same
+//! output
+
+/// How grey levels are turned into black and white pixels
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Dither {
+    /// Everything at least half bright is white
+    #[default]
+    Threshold,
+    /// Error diffusion, smooth gradients
+    FloydSteinberg,
+    /// Error diffusion that drops part of the error, more contrast
+    Atkinson,
+    /// Ordered 4x4 pattern
+    Bayer4,
+    /// Ordered 8x8 pattern, more grey levels than [`Dither::Bayer4`]
+    Bayer8,
+}
+
+/// Where the error of a pixel goes: x offset, rows below and weight in 1/16
+type Diffusion = &'static [(i8, u8, i16)];
+
+const FLOYD_STEINBERG: Diffusion = &[(1, 0, 7), (-1, 1, 3), (0, 1, 5), (1, 1, 1)];
+const ATKINSON: Diffusion = &[
+    (1, 0, 2),
+    (2, 0, 2),
+    (-1, 1, 2),
+    (0, 1, 2),
+    (1, 1, 2),
+    (0, 2, 2),
+];
+
+/// Threshold of the `n` by `n` Bayer matrix, a power of two, at `x`, `y`
+fn bayer(n: u32, x: u32, y: u32) -> i16 {
+    // The finest level of the recursion is the most significant
+    let mut value = 0;
+    let mut weight = 1;
+    let mut size = n;
+    let (mut x, mut y) = (x % n, y % n);
+    while size > 1 {
+        size /= 2;
+        let quadrant = match (x >= size, y >= size) {
+            (false, false) => 0,
+            (true, true) => 1,
+            (true, false) => 2,
+            (false, true) => 3,
+        };
+        value += quadrant * weight;
+        weight *= 4;
+        x %= size;
+        y %= size;
+    }
+    ((2 * value + 1) * 128 / (n * n)) as i16
+}
+
+/// Dithers an image row by row, keeping only the error of the next rows
+///
+/// Rows up to `W` pixels wide are supported, pixels past that are thresholded.
+pub struct Ditherer<const W: usize> {
+    method: Dither,
+    /// Error carried into the current row and the two below it
+    errors: [[i16; W]; 3],
+}
+
+impl<const W: usize> Ditherer<W> {
+    pub fn new(method: Dither) -> Self {
+        Self {
+            method,
+            errors: [[0; W]; 3],
+        }
+    }
+
+    /// Dither the next row, replacing its luma values with 0 or 255
+    ///
+    /// Rows must be passed from top to bottom, `y` positions ordered patterns.
+    pub fn dither_row(&mut self, y: u32, row: &mut [u8]) {
+        let diffusion = match self.method {
+            Dither::FloydSteinberg => FLOYD_STEINBERG,
+            Dither::Atkinson => ATKINSON,
+            _ => &[],
+        };
+
+        for x in 0..row.len() {
+            let carried = if x < W { self.errors[0][x] } else { 0 };
+            let level = row[x] as i16 + carried;
+            let threshold = match self.method {
+                Dither::Bayer4 => bayer(4, x as u32, y),
+                Dither::Bayer8 => bayer(8, x as u32, y),
+                _ => 128,
+            };
+            let white = level >= threshold;
+            row[x] = if white { 255 } else { 0 };
+
+            if x >= W {
+                continue;
+            }
+            let error = level - row[x] as i16;
+            for &(dx, dy, weight) in diffusion {
+                let nx = x as isize + dx as isize;
+                if nx >= 0 && (nx as usize) < W.min(row.len()) {
+                    self.errors[dy as usize][nx as usize] += error * weight / 16;
+                }
+            }
+        }
+
+        self.errors.rotate_left(1);
+        self.errors[2] = [0; W];
+    }
+}
@@ -0,0 +1,294 @@
+//! Drawing in portrait or upside down
+
+use embedded_graphics::{prelude::*, primitives::Rectangle};
+
+/// Clockwise rotation of what is drawn
+///
+/// With [`Rotation::Deg90`] hold the badge with its right edge up, with
+/// [`Rotation::Deg270`] with its left edge up.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Rotation {
+    #[default]
+    Deg0,
+    Deg90,
+    Deg180,
+    Deg270,
+}
+
+impl Rotation {
+    /// Returns true if width and height are swapped
+    pub fn is_portrait(self) -> bool {
+        matches!(self, Rotation::Deg90 | Rotation::Deg270)
+    }
+
+    /// Size of a `size` target after rotating
+    pub fn size(self, size: Size) -> Size {
+        if self.is_portrait() {
+            Size::new(size.height, size.width)
+        } else {
+            size
+        }
+    }
+
+    /// Where `point` ends up on a target of `size`
+    pub fn point(self, point: Point, size: Size) -> Point {
+        let (width, height) = (size.width as i32, size.height as i32);
+        match self {
+            Rotation::Deg0 => point,
+            Rotation::Deg90 => Point::new(width - 1 - point.y, point.x),
+            Rotation::Deg180 => Point::new(width - 1 - point.x, height - 1 - point.y),
+            Rotation::Deg270 => Point::new(point.y, height - 1 - point.x),
+        }
+    }
+
+    /// Where `area` ends up on a target of `size`
+    pub fn rectangle(self, area: &Rectangle, size: Size) -> Rectangle {
+        match area.bottom_right() {
+            Some(bottom_right) => Rectangle::with_corners(
+                self.point(area.top_left, size),
+                self.point(bottom_right, size),
+            ),
+            None => Rectangle::new(self.point(area.top_left, size), Size::zero()),
+        }
+    }
+}
+
+/// A draw target turned by a [`Rotation`]
+///
+/// ```ignore
+/// let mut portrait = Rotated::new(display, Rotation::Deg90);
+/// // 128 pixels wide, 296 high
+/// Text::new("Hello", Point::new(10, 20), style).draw(&mut portrait)?;
+/// portrait.inner_mut().update()?;
+/// ```
+pub struct Rotated<D> {
+    target: D,
+    rotation: Rotation,
+}
+
+impl<D: DrawTarget> Rotated<D> {
+    pub fn new(target: D, rotation: Rotation) -> Self {
+        Self { target, rotation }
+    }
+
+    pub fn rotation(&self) -> Rotation {
+        self.rotation
+    }
+
+    /// Change the rotation, what has already been drawn stays as it is
+    pub fn set_rotation(&mut self, rotation: Rotation) {
+        self.rotation = rotation;
+    }
+
+    /// The wrapped target, drawing on it is not rotated
+    pub fn inner(&self) -> &D {
+        &self.target
+    }
+
+    /// The wrapped target, e.g. to update the display
+    pub fn inner_mut(&mut self) -> &mut D {
+        &mut self.target
+    }
+
+    /// Release the wrapped target
+    pub fn into_inner(self) -> D {
+        self.target
+    }
+
+    fn target_size(&self) -> Size {
+        self.target.bounding_box().size
+    }
+}
+
+impl<D: DrawTarget> Dimensions for Rotated<D> {
+    fn bounding_box(&self) -> Rectangle {
+        Rectangle::new(Point::zero(), self.rotation.size(self.target_size()))
+    }
+}
+
+impl<D: DrawTarget> DrawTarget for Rotated<D> {
+    type Color = D::Color;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let (rotation, size) = (self.rotation, self.target_size());
+        self.target.draw_iter(
+            pixels
+                .into_iter()
+                .map(|Pixel(point, color)| Pixel(rotation.point(point, size), color)),
+        )
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let (rotation, size) = (self.rotation, self.target_size());
+        let mut colors = colors.into_iter();
+        let width = area.size.width as usize;
+
+        match rotation {
+            Rotation::Deg0 => self.target.fill_contiguous(area, colors),
+            // Rows become top to bottom columns
+            Rotation::Deg90 => {
+                for row in area.rows() {
+                    let row = Rectangle::new(
+                        Point::new(area.top_left.x, row),
+                        Size::new(area.size.width, 1),
+                    );
+                    let column = rotation.rectangle(&row, size);
+                    self.target
+                        .fill_contiguous(&column, colors.by_ref().take(width))?;
+                }
+                Ok(())
+            }
+            // Rows run backwards, fill runs of the same color instead
+            Rotation::Deg180 | Rotation::Deg270 => {
+                for y in area.rows() {
+                    let mut run: Option<(i32, Self::Color)> = None;
+                    for (x, color) in area.columns().zip(colors.by_ref()) {
+                        match run {
+                            Some((_, run_color)) if run_color == color => {}
+                            _ => {
+                                if let Some((start, run_color)) = run {
+                                    self.fill_run(start, x, y, run_color)?;
+                                }
+                                run = Some((x, color));
+                            }
+                        }
+                    }
+                    if let Some((start, run_color)) = run {
+                        self.fill_run(start, area.columns().end, y, run_color)?;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let area = self.rotation.rectangle(area, self.target_size());
+        self.target.fill_solid(&area, color)
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.target.clear(color)
+    }
+}
+
+impl<D: DrawTarget> Rotated<D> {
+    /// Fill the pixels `start..end` of row `y`
+    fn fill_run(&mut self, start: i32, end: i32, y: i32, color: D::Color) -> Result<(), D::Error> {
+        let run = Rectangle::new(Point::new(start, y), Size::new((end - start) as u32, 1));
+        let run = self.rotation.rectangle(&run, self.target_size());
+        self.target.fill_solid(&run, color)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_graphics::{
+        draw_target::Cropped, mock_display::MockDisplay, pixelcolor::BinaryColor,
+    };
+
+    /// Three by two pixels, the top row and the left column set
+    const SHAPE: [BinaryColor; 6] = {
+        use BinaryColor::{Off, On};
+        [On, On, On, On, Off, Off]
+    };
+
+    /// The shape as it ends up on the display for each rotation
+    const EXPECTED: [(Rotation, &[&str]); 4] = [
+        (Rotation::Deg0, &["###", "#.."]),
+        (Rotation::Deg90, &["##", ".#", ".#"]),
+        (Rotation::Deg180, &["..#", "###"]),
+        (Rotation::Deg270, &["#.", "#.", "##"]),
+    ];
+
+    fn shape_area() -> Rectangle {
+        Rectangle::new(Point::zero(), Size::new(3, 2))
+    }
+
+    /// Draw the shape with `draw` on a target exactly as large as it
+    fn assert_drawn(draw: impl Fn(&mut Rotated<Cropped<MockDisplay<BinaryColor>>>, &Rectangle)) {
+        for (rotation, expected) in EXPECTED {
+            let mut display = MockDisplay::new();
+            let screen = Rectangle::new(Point::zero(), rotation.size(shape_area().size));
+            let mut rotated = Rotated::new(display.cropped(&screen), rotation);
+            assert_eq!(rotated.bounding_box(), shape_area());
+            draw(&mut rotated, &shape_area());
+            display.assert_pattern(expected);
+        }
+    }
+
+    #[test]
+    fn point_mapping() {
+        let size = Size::new(296, 128);
+        let corner = Point::new(10, 2);
+        assert_eq!(Rotation::Deg0.point(corner, size), Point::new(10, 2));
+        assert_eq!(Rotation::Deg90.point(corner, size), Point::new(293, 10));
+        assert_eq!(Rotation::Deg180.point(corner, size), Point::new(285, 125));
+        assert_eq!(Rotation::Deg270.point(corner, size), Point::new(2, 117));
+    }
+
+    #[test]
+    fn bounding_boxes() {
+        let size = Size::new(296, 128);
+        let area = Rectangle::new(Point::new(10, 2), Size::new(20, 5));
+        let rotated = |rotation: Rotation| rotation.rectangle(&area, size);
+        assert_eq!(rotated(Rotation::Deg0), area);
+        assert_eq!(
+            rotated(Rotation::Deg90),
+            Rectangle::new(Point::new(289, 10), Size::new(5, 20))
+        );
+        assert_eq!(
+            rotated(Rotation::Deg180),
+            Rectangle::new(Point::new(266, 121), Size::new(20, 5))
+        );
+        assert_eq!(
+            rotated(Rotation::Deg270),
+            Rectangle::new(Point::new(2, 98), Size::new(5, 20))
+        );
+        let empty = Rectangle::new(Point::new(10, 2), Size::zero());
+        assert_eq!(
+            Rotation::Deg90.rectangle(&empty, size),
+            Rectangle::new(Point::new(293, 10), Size::zero())
+        );
+
+        let mut display = MockDisplay::<BinaryColor>::new();
+        let screen = Rectangle::new(Point::zero(), Size::new(6, 4));
+        for rotation in [Rotation::Deg90, Rotation::Deg270] {
+            let portrait = Rotated::new(display.cropped(&screen), rotation);
+            assert_eq!(portrait.bounding_box().size, Size::new(4, 6));
+        }
+    }
+
+    #[test]
+    fn draw_iter() {
+        assert_drawn(|target, area| {
+            let pixels = area.points().zip(SHAPE).map(|(p, c)| Pixel(p, c));
+            target.draw_iter(pixels).unwrap();
+        });
+    }
+
+    #[test]
+    fn fill_contiguous() {
+        assert_drawn(|target, area| target.fill_contiguous(area, SHAPE).unwrap());
+    }
+
+    #[test]
+    fn fill_solid() {
+        assert_drawn(|target, _| {
+            let row = Rectangle::new(Point::zero(), Size::new(3, 1));
+            let column = Rectangle::new(Point::new(0, 1), Size::new(1, 1));
+            let rest = Rectangle::new(Point::new(1, 1), Size::new(2, 1));
+            target.fill_solid(&row, BinaryColor::On).unwrap();
+            target.fill_solid(&column, BinaryColor::On).unwrap();
+            target.fill_solid(&rest, BinaryColor::Off).unwrap();
+        });
+    }
+}
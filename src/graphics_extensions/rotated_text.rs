@@ -0,0 +1,326 @@
+//! Text running up, down or upside down, for banners along the edges
+
+use embedded_graphics::{
+    prelude::*,
+    primitives::Rectangle,
+    text::{renderer::TextRenderer, Baseline, Text},
+};
+
+use super::{Rotated, Rotation};
+
+/// Text turned clockwise by a [`Rotation`]
+///
+/// With [`Rotation::Deg90`] the text reads from top to bottom, with
+/// [`Rotation::Deg270`] from bottom to top. Lines are laid out as by `Text`
+/// and the whole block is turned, the bounding box is the turned block so
+/// [`Align`](super::Align) and [`Centering`](super::Centering) work as for
+/// other drawables.
+///
+/// ```ignore
+/// let style = MonoTextStyle::new(&FONT_10X20, BinaryColor::Off);
+/// RotatedText::new("KouvostoTelecom", Point::zero(), style, Rotation::Deg270)
+///     .align(&column, AnchorPoint::Center)
+///     .draw(&mut display)?;
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RotatedText<'a, S> {
+    pub text: &'a str,
+    pub top_left: Point,
+    pub style: S,
+    pub rotation: Rotation,
+}
+
+impl<'a, S: TextRenderer + Clone> RotatedText<'a, S> {
+    pub fn new(text: &'a str, top_left: Point, style: S, rotation: Rotation) -> Self {
+        Self {
+            text,
+            top_left,
+            style,
+            rotation,
+        }
+    }
+
+    /// The text before turning, with the top left of its block at the origin
+    fn unrotated(&self) -> Text<'a, S> {
+        let text = Text::with_baseline(self.text, Point::zero(), self.style.clone(), Baseline::Top);
+        let top_left = text.bounding_box().top_left;
+        text.translate(-top_left)
+    }
+}
+
+impl<S: TextRenderer + Clone> Dimensions for RotatedText<'_, S> {
+    fn bounding_box(&self) -> Rectangle {
+        let size = self.unrotated().bounding_box().size;
+        Rectangle::new(self.top_left, self.rotation.size(size))
+    }
+}
+
+impl<S: Clone> Transform for RotatedText<'_, S> {
+    fn translate(&self, by: Point) -> Self {
+        Self {
+            top_left: self.top_left + by,
+            ..self.clone()
+        }
+    }
+
+    fn translate_mut(&mut self, by: Point) -> &mut Self {
+        self.top_left += by;
+        self
+    }
+}
+
+impl<S: TextRenderer + Clone> Drawable for RotatedText<'_, S> {
+    type Color = S::Color;
+    type Output = ();
+
+    fn draw<D>(&self, target: &mut D) -> Result<Self::Output, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        // The block is drawn on a target the size of the turned block
+        let mut turned = Rotated::new(target.cropped(&self.bounding_box()), self.rotation);
+        self.unrotated().draw(&mut turned)?;
+        Ok(())
+    }
+}
+
+/// Text with its letters stacked from top to bottom, each centered in a
+/// column as wide as the widest letter
+///
+/// ```ignore
+/// StackedText::new("KTC", Point::new(4, 4), style).draw(&mut display)?;
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StackedText<'a, S> {
+    pub text: &'a str,
+    pub top_left: Point,
+    pub style: S,
+}
+
+impl<'a, S: TextRenderer + Clone> StackedText<'a, S> {
+    pub fn new(text: &'a str, top_left: Point, style: S) -> Self {
+        Self {
+            text,
+            top_left,
+            style,
+        }
+    }
+
+    /// The letters of the text, one `&str` each
+    fn letters(&self) -> impl Iterator<Item = &'a str> {
+        let text = self.text;
+        text.char_indices()
+            .map(move |(i, c)| &text[i..i + c.len_utf8()])
+    }
+
+    fn letter_width(&self, letter: &str) -> u32 {
+        let metrics = self
+            .style
+            .measure_string(letter, Point::zero(), Baseline::Top);
+        metrics.bounding_box.size.width
+    }
+
+    fn column_width(&self) -> u32 {
+        self.letters()
+            .map(|letter| self.letter_width(letter))
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+impl<S: TextRenderer + Clone> Dimensions for StackedText<'_, S> {
+    fn bounding_box(&self) -> Rectangle {
+        let height = self.letters().count() as u32 * self.style.line_height();
+        Rectangle::new(self.top_left, Size::new(self.column_width(), height))
+    }
+}
+
+impl<S: Clone> Transform for StackedText<'_, S> {
+    fn translate(&self, by: Point) -> Self {
+        Self {
+            top_left: self.top_left + by,
+            ..self.clone()
+        }
+    }
+
+    fn translate_mut(&mut self, by: Point) -> &mut Self {
+        self.top_left += by;
+        self
+    }
+}
+
+impl<S: TextRenderer + Clone> Drawable for StackedText<'_, S> {
+    type Color = S::Color;
+    type Output = ();
+
+    fn draw<D>(&self, target: &mut D) -> Result<Self::Output, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let line_height = self.style.line_height() as i32;
+        let column_width = self.column_width();
+        for (row, letter) in self.letters().enumerate() {
+            let width = self.letter_width(letter);
+            let at = self.top_left
+                + Point::new(
+                    ((column_width - width) / 2) as i32,
+                    row as i32 * line_height,
+                );
+            Text::with_baseline(letter, at, self.style.clone(), Baseline::Top).draw(target)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fonts::{ProportionalTextStyle, BOLD_13};
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X10, MonoTextStyle, MonoTextStyleBuilder},
+        pixelcolor::BinaryColor,
+    };
+
+    /// White letters on black cells, so that every pixel of the block is
+    /// drawn
+    fn style() -> MonoTextStyle<'static, BinaryColor> {
+        MonoTextStyleBuilder::new()
+            .font(&FONT_6X10)
+            .text_color(BinaryColor::On)
+            .background_color(BinaryColor::Off)
+            .build()
+    }
+
+    #[test]
+    fn rotated_bounding_boxes() {
+        let top_left = Point::new(3, 5);
+        let landscape = Rectangle::new(top_left, Size::new(12, 10));
+        let portrait = Rectangle::new(top_left, Size::new(10, 12));
+        for (rotation, expected) in [
+            (Rotation::Deg0, landscape),
+            (Rotation::Deg90, portrait),
+            (Rotation::Deg180, landscape),
+            (Rotation::Deg270, portrait),
+        ] {
+            let text = RotatedText::new("Ab", top_left, style(), rotation);
+            assert_eq!(text.bounding_box(), expected);
+        }
+
+        let text = RotatedText::new("Ab\nCde", Point::zero(), style(), Rotation::Deg90);
+        assert_eq!(text.bounding_box().size, Size::new(20, 18));
+        let moved = text.translate(Point::new(2, 1));
+        assert_eq!(moved.bounding_box().top_left, Point::new(2, 1));
+    }
+
+    fn draw(rotation: Rotation) -> MockDisplay<BinaryColor> {
+        let mut display = MockDisplay::new();
+        RotatedText::new("Ab", Point::zero(), style(), rotation)
+            .draw(&mut display)
+            .unwrap();
+        display
+    }
+
+    #[test]
+    fn draws_turned_text() {
+        draw(Rotation::Deg0).assert_pattern(&[
+            "............", //
+            "..#...#.....", //
+            ".#.#..#.....", //
+            "#...#.#.##..", //
+            "#...#.##..#.", //
+            "#####.#...#.", //
+            "#...#.##..#.", //
+            "#...#.#.##..", //
+            "............", //
+            "............", //
+        ]);
+        // Clockwise, read from top to bottom
+        draw(Rotation::Deg90).assert_pattern(&[
+            "..#####...", //
+            "....#..#..", //
+            "....#...#.", //
+            "....#..#..", //
+            "..#####...", //
+            "..........", //
+            "..#######.", //
+            "...#.#....", //
+            "..#...#...", //
+            "..#...#...", //
+            "...###....", //
+            "..........", //
+        ]);
+        draw(Rotation::Deg180).assert_pattern(&[
+            "............", //
+            "............", //
+            "..##.#.#...#", //
+            ".#..##.#...#", //
+            ".#...#.#####", //
+            ".#..##.#...#", //
+            "..##.#.#...#", //
+            ".....#..#.#.", //
+            ".....#...#..", //
+            "............", //
+        ]);
+        // Counterclockwise, read from bottom to top
+        draw(Rotation::Deg270).assert_pattern(&[
+            "..........", //
+            "....###...", //
+            "...#...#..", //
+            "...#...#..", //
+            "....#.#...", //
+            ".#######..", //
+            "..........", //
+            "...#####..", //
+            "..#..#....", //
+            ".#...#....", //
+            "..#..#....", //
+            "...#####..", //
+        ]);
+    }
+
+    #[test]
+    fn draws_at_top_left() {
+        let at_origin = draw(Rotation::Deg90);
+        let mut moved = MockDisplay::new();
+        RotatedText::new("Ab", Point::new(3, 5), style(), Rotation::Deg90)
+            .draw(&mut moved)
+            .unwrap();
+        assert_eq!(moved.affected_area().top_left, Point::new(3, 5));
+        for p in at_origin.affected_area().points() {
+            assert_eq!(
+                moved.get_pixel(p + Point::new(3, 5)),
+                at_origin.get_pixel(p)
+            );
+        }
+    }
+
+    #[test]
+    fn stacked_letters_are_centered() {
+        let style = ProportionalTextStyle::new(&BOLD_13, BinaryColor::On);
+        let text = StackedText::new("Wi!", Point::new(1, 2), style);
+        assert_eq!(
+            text.bounding_box(),
+            Rectangle::new(Point::new(1, 2), Size::new(6, 39))
+        );
+
+        let mut display = MockDisplay::new();
+        text.draw(&mut display).unwrap();
+
+        // W is 6 pixels wide, i 4 and ! 2
+        let mut expected = MockDisplay::new();
+        for (letter, at) in [
+            ("W", Point::new(1, 2)),
+            ("i", Point::new(2, 15)),
+            ("!", Point::new(3, 28)),
+        ] {
+            Text::with_baseline(letter, at, style, Baseline::Top)
+                .draw(&mut expected)
+                .unwrap();
+        }
+        assert_eq!(display, expected);
+
+        let empty = StackedText::new("", Point::zero(), style);
+        assert_eq!(empty.bounding_box().size, Size::zero());
+    }
+}
@@ -0,0 +1,544 @@
+//! Text drawn at a multiple of the size of its font
+
+use core::convert::Infallible;
+
+use embedded_graphics::{
+    pixelcolor::BinaryColor,
+    prelude::*,
+    primitives::Rectangle,
+    text::{
+        renderer::{CharacterStyle, TextMetrics, TextRenderer},
+        Baseline, DecorationColor,
+    },
+};
+
+/// Largest line a [`Canvas`] holds before scaling, wider than the display
+/// at twice the size
+const CANVAS_WIDTH: usize = 256;
+const CANVAS_HEIGHT: usize = 32;
+
+/// The pixels a text style draws on one line, before they are scaled
+struct Canvas {
+    drawn: [u32; CANVAS_WIDTH * CANVAS_HEIGHT / 32],
+    on: [u32; CANVAS_WIDTH * CANVAS_HEIGHT / 32],
+    /// Columns up to the rightmost pixel drawn
+    width: usize,
+}
+
+impl Canvas {
+    fn new() -> Self {
+        Self {
+            drawn: [0; CANVAS_WIDTH * CANVAS_HEIGHT / 32],
+            on: [0; CANVAS_WIDTH * CANVAS_HEIGHT / 32],
+            width: 0,
+        }
+    }
+
+    fn index(point: Point) -> Option<usize> {
+        let (x, y) = (point.x as usize, point.y as usize);
+        (point.x >= 0 && point.y >= 0 && x < CANVAS_WIDTH && y < CANVAS_HEIGHT)
+            .then(|| y * CANVAS_WIDTH + x)
+    }
+
+    /// The color drawn at `point`, `None` where nothing was drawn
+    fn get(&self, point: Point) -> Option<BinaryColor> {
+        let i = Self::index(point)?;
+        let bit = 1 << (i % 32);
+        (self.drawn[i / 32] & bit != 0).then(|| BinaryColor::from(self.on[i / 32] & bit != 0))
+    }
+
+    /// Color of pixel `(i, j)` of the `scale` by `scale` block drawn for
+    /// `point`
+    ///
+    /// With `smooth` the corners of the block between two sides of the same
+    /// color take that color, as in the EPX pixel art scaler, which turns
+    /// staircases into diagonals.
+    fn scaled(
+        &self,
+        point: Point,
+        i: i32,
+        j: i32,
+        scale: i32,
+        smooth: bool,
+    ) -> Option<BinaryColor> {
+        let center = self.get(point);
+        if !smooth {
+            return center;
+        }
+        let (left, right) = (
+            self.get(point - Point::new(1, 0)),
+            self.get(point + Point::new(1, 0)),
+        );
+        let (above, below) = (
+            self.get(point - Point::new(0, 1)),
+            self.get(point + Point::new(0, 1)),
+        );
+        let (side, other_side) = if 2 * i + 1 < scale {
+            (left, right)
+        } else {
+            (right, left)
+        };
+        let (end, other_end) = if 2 * j + 1 < scale {
+            (above, below)
+        } else {
+            (below, above)
+        };
+
+        // Cut off by the line between the middles of the two sides
+        let corner = 2 * (i.min(scale - 1 - i) + j.min(scale - 1 - j) + 1) <= scale;
+        if corner && side == end && side != other_end && end != other_side {
+            side
+        } else {
+            center
+        }
+    }
+}
+
+impl OriginDimensions for Canvas {
+    fn size(&self) -> Size {
+        Size::new(CANVAS_WIDTH as u32, CANVAS_HEIGHT as u32)
+    }
+}
+
+impl DrawTarget for Canvas {
+    type Color = BinaryColor;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            if let Some(i) = Self::index(point) {
+                let bit = 1 << (i % 32);
+                self.drawn[i / 32] |= bit;
+                if color.is_on() {
+                    self.on[i / 32] |= bit;
+                } else {
+                    self.on[i / 32] &= !bit;
+                }
+                self.width = self.width.max(point.x as usize + 1);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A text style drawn `scale` times larger, for names that fill the badge
+///
+/// Each pixel of `style` becomes a square block, [`Scaled::with_smoothing`]
+/// cuts the corners of the blocks along diagonal edges. Lines up to 256
+/// pixels wide and 32 high before scaling are drawn, enough for the
+/// embedded-graphics fonts at 2x and more. Longer lines and taller fonts are
+/// cut off on the right and at the bottom, although they are measured at
+/// their full size.
+///
+/// ```ignore
+/// let style = Scaled::new(MonoTextStyle::new(&FONT_10X20, BinaryColor::Off), 3);
+/// Text::with_alignment("Zokol", Point::zero(), style, Alignment::Center)
+///     .center(Point::new(148, 64))
+///     .draw(&mut display)?;
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Scaled<S> {
+    pub style: S,
+    pub scale: u32,
+    pub smooth: bool,
+}
+
+impl<S> Scaled<S> {
+    pub fn new(style: S, scale: u32) -> Self {
+        Self {
+            style,
+            scale: scale.max(1),
+            smooth: false,
+        }
+    }
+
+    #[must_use]
+    pub fn with_smoothing(self, smooth: bool) -> Self {
+        Self { smooth, ..self }
+    }
+}
+
+impl<S: TextRenderer<Color = BinaryColor>> Scaled<S> {
+    /// Rows from the top of the scaled line to `baseline`
+    fn baseline_offset(&self, baseline: Baseline) -> i32 {
+        let height = self.line_height().saturating_sub(1) as i32;
+        let scale = self.scale as i32;
+        match baseline {
+            Baseline::Top => 0,
+            Baseline::Bottom => height,
+            Baseline::Middle => height / 2,
+            // The letters stand on the last row of the scaled baseline
+            Baseline::Alphabetic => {
+                let metrics = self
+                    .style
+                    .measure_string("", Point::zero(), Baseline::Alphabetic);
+                -metrics.bounding_box.top_left.y * scale + scale - 1
+            }
+        }
+    }
+
+    /// Draw with `draw` on a canvas and from there scaled at `top_left`,
+    /// returns the column the style continues from before scaling
+    fn draw_scaled<D>(
+        &self,
+        top_left: Point,
+        target: &mut D,
+        draw: impl FnOnce(&mut Canvas) -> Result<Point, Infallible>,
+    ) -> Result<i32, D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let mut canvas = Canvas::new();
+        let next = draw(&mut canvas).map_or(0, |next| next.x);
+
+        let scale = self.scale as i32;
+        let rows = (self.style.line_height() as i32).min(CANVAS_HEIGHT as i32);
+        // Smoothing can reach one column past the last pixel drawn
+        let columns = canvas.width as i32 + 1;
+        let canvas = &canvas;
+        let pixels = (0..rows * scale).flat_map(|y| {
+            (0..columns * scale).filter_map(move |x| {
+                let point = Point::new(x / scale, y / scale);
+                canvas
+                    .scaled(point, x % scale, y % scale, scale, self.smooth)
+                    .map(|color| Pixel(top_left + Point::new(x, y), color))
+            })
+        });
+        target.draw_iter(pixels)?;
+        Ok(next)
+    }
+}
+
+impl<S: TextRenderer<Color = BinaryColor>> TextRenderer for Scaled<S> {
+    type Color = BinaryColor;
+
+    fn draw_string<D>(
+        &self,
+        text: &str,
+        position: Point,
+        baseline: Baseline,
+        target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let top_left = position - Point::new(0, self.baseline_offset(baseline));
+        let next = self.draw_scaled(top_left, target, |canvas| {
+            self.style
+                .draw_string(text, Point::zero(), Baseline::Top, canvas)
+        })?;
+        Ok(position + Point::new(next * self.scale as i32, 0))
+    }
+
+    fn draw_whitespace<D>(
+        &self,
+        width: u32,
+        position: Point,
+        baseline: Baseline,
+        target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let top_left = position - Point::new(0, self.baseline_offset(baseline));
+        let area = Rectangle::new(top_left, Size::new(width, self.line_height()));
+        self.draw_scaled(top_left, &mut target.clipped(&area), |canvas| {
+            let width = width.div_ceil(self.scale);
+            self.style
+                .draw_whitespace(width, Point::zero(), Baseline::Top, canvas)
+        })?;
+        Ok(position + Point::new(width as i32, 0))
+    }
+
+    fn measure_string(&self, text: &str, position: Point, baseline: Baseline) -> TextMetrics {
+        let top_left = position - Point::new(0, self.baseline_offset(baseline));
+        let metrics = self
+            .style
+            .measure_string(text, Point::zero(), Baseline::Top);
+        let scale = self.scale as i32;
+        TextMetrics {
+            bounding_box: Rectangle::new(
+                top_left + metrics.bounding_box.top_left * scale,
+                metrics.bounding_box.size * self.scale,
+            ),
+            next_position: position + Point::new(metrics.next_position.x * scale, 0),
+        }
+    }
+
+    fn line_height(&self) -> u32 {
+        self.style.line_height() * self.scale
+    }
+}
+
+impl<S: CharacterStyle> CharacterStyle for Scaled<S> {
+    type Color = S::Color;
+
+    fn set_text_color(&mut self, text_color: Option<Self::Color>) {
+        self.style.set_text_color(text_color);
+    }
+
+    fn set_background_color(&mut self, background_color: Option<Self::Color>) {
+        self.style.set_background_color(background_color);
+    }
+
+    fn set_underline_color(&mut self, underline_color: DecorationColor<Self::Color>) {
+        self.style.set_underline_color(underline_color);
+    }
+
+    fn set_strikethrough_color(&mut self, strikethrough_color: DecorationColor<Self::Color>) {
+        self.style.set_strikethrough_color(strikethrough_color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics_extensions::Centering;
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X10, MonoTextStyle, MonoTextStyleBuilder},
+        text::{Alignment, Text},
+    };
+
+    /// Draws `rows` for any text, `#` white, `.` black and nothing for spaces
+    #[derive(Clone, Copy)]
+    struct Pattern<'a>(&'a [&'a str]);
+
+    impl TextRenderer for Pattern<'_> {
+        type Color = BinaryColor;
+
+        fn draw_string<D>(
+            &self,
+            _text: &str,
+            position: Point,
+            _baseline: Baseline,
+            target: &mut D,
+        ) -> Result<Point, D::Error>
+        where
+            D: DrawTarget<Color = BinaryColor>,
+        {
+            let pixels = self.0.iter().enumerate().flat_map(|(y, row)| {
+                row.chars().enumerate().filter_map(move |(x, c)| {
+                    let point = position + Point::new(x as i32, y as i32);
+                    match c {
+                        '#' => Some(Pixel(point, BinaryColor::On)),
+                        '.' => Some(Pixel(point, BinaryColor::Off)),
+                        _ => None,
+                    }
+                })
+            });
+            target.draw_iter(pixels)?;
+            Ok(position + Point::new(self.width() as i32, 0))
+        }
+
+        fn draw_whitespace<D>(
+            &self,
+            width: u32,
+            position: Point,
+            _baseline: Baseline,
+            _target: &mut D,
+        ) -> Result<Point, D::Error>
+        where
+            D: DrawTarget<Color = BinaryColor>,
+        {
+            Ok(position + Point::new(width as i32, 0))
+        }
+
+        fn measure_string(&self, _text: &str, position: Point, _baseline: Baseline) -> TextMetrics {
+            let size = Size::new(self.width(), self.line_height());
+            TextMetrics {
+                bounding_box: Rectangle::new(position, size),
+                next_position: position + size.x_axis(),
+            }
+        }
+
+        fn line_height(&self) -> u32 {
+            self.0.len() as u32
+        }
+    }
+
+    impl Pattern<'_> {
+        fn width(&self) -> u32 {
+            self.0.iter().map(|row| row.len() as u32).max().unwrap_or(0)
+        }
+    }
+
+    fn draw(style: Scaled<Pattern>) -> MockDisplay<BinaryColor> {
+        let mut display = MockDisplay::new();
+        style
+            .draw_string("", Point::zero(), Baseline::Top, &mut display)
+            .unwrap();
+        display
+    }
+
+    #[test]
+    fn pixels_become_blocks() {
+        let style = Scaled::new(Pattern(&["#.", " #"]), 2);
+        draw(style).assert_pattern(&[
+            "##..", //
+            "##..", //
+            "  ##", //
+            "  ##", //
+        ]);
+        assert_eq!(style.line_height(), 4);
+        let metrics = style.measure_string("", Point::new(1, 2), Baseline::Top);
+        assert_eq!(
+            metrics.bounding_box,
+            Rectangle::new(Point::new(1, 2), Size::new(4, 4))
+        );
+        assert_eq!(metrics.next_position, Point::new(5, 2));
+        assert_eq!(Scaled::new(Pattern(&["#"]), 0).scale, 1);
+    }
+
+    /// The outer corners are cut where nothing is drawn around them, the
+    /// inner corner is filled
+    #[test]
+    fn smoothing_rounds_corners() {
+        let style = Scaled::new(Pattern(&["#.", "##"]), 3);
+        draw(style).assert_pattern(&[
+            "###...", //
+            "###...", //
+            "###...", //
+            "######", //
+            "######", //
+            "######", //
+        ]);
+        draw(style.with_smoothing(true)).assert_pattern(&[
+            " ##.. ", //
+            "###...", //
+            "####..", //
+            "######", //
+            "######", //
+            " #### ", //
+        ]);
+    }
+
+    /// The blocks of a diagonal stay connected
+    #[test]
+    fn smoothing_cuts_diagonals() {
+        let style = Scaled::new(Pattern(&["#..", ".#.", "..#"]), 4).with_smoothing(true);
+        draw(style).assert_pattern(&[
+            "  ##......  ", //
+            " ###....... ", //
+            "###.#.......", //
+            "##..##......", //
+            "..######....", //
+            "...#####....", //
+            "....#####...", //
+            "....######..", //
+            "......##..##", //
+            ".......#.###", //
+            " .......### ", //
+            "  ......##  ", //
+        ]);
+    }
+
+    /// The largest x and y drawn
+    struct Extent(Point);
+
+    impl OriginDimensions for Extent {
+        fn size(&self) -> Size {
+            Size::new(1024, 1024)
+        }
+    }
+
+    impl DrawTarget for Extent {
+        type Color = BinaryColor;
+        type Error = Infallible;
+
+        fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Pixel<Self::Color>>,
+        {
+            for Pixel(point, _) in pixels {
+                self.0 = self.0.component_max(point);
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn large_lines_are_cut_off() {
+        let row = "#".repeat(300);
+        let rows = vec![row.as_str(); 40];
+        let style = Scaled::new(Pattern(&rows), 2);
+        let mut extent = Extent(Point::zero());
+        style
+            .draw_string("", Point::zero(), Baseline::Top, &mut extent)
+            .unwrap();
+        assert_eq!(
+            extent.0,
+            Point::new(2 * CANVAS_WIDTH as i32 - 1, 2 * CANVAS_HEIGHT as i32 - 1)
+        );
+
+        let metrics = style.measure_string("", Point::zero(), Baseline::Top);
+        assert_eq!(metrics.bounding_box.size, Size::new(600, 80));
+    }
+
+    /// Area drawn by `text`, its bounding box and the area of the glyphs
+    /// without the background
+    fn hg(text: &Text<Scaled<MonoTextStyle<BinaryColor>>>) -> (Rectangle, Rectangle, Rectangle) {
+        let mut display = MockDisplay::new();
+        text.draw(&mut display).unwrap();
+        let drawn = display.affected_area();
+
+        let mut glyphs = *text;
+        glyphs.character_style.style.background_color = None;
+        let mut display = MockDisplay::new();
+        glyphs.draw(&mut display).unwrap();
+        (drawn, text.bounding_box(), display.affected_area())
+    }
+
+    #[test]
+    fn font_bounding_box_matches_pixels() {
+        let font = MonoTextStyleBuilder::new()
+            .font(&FONT_6X10)
+            .text_color(BinaryColor::Off)
+            .background_color(BinaryColor::On)
+            .build();
+        let at = Point::new(4, 30);
+        for scale in [2, 3] {
+            let style = Scaled::new(font, scale);
+            let height = 10 * scale;
+            for baseline in [
+                Baseline::Top,
+                Baseline::Bottom,
+                Baseline::Middle,
+                Baseline::Alphabetic,
+            ] {
+                let text = Text::with_baseline("Hg", at, style, baseline);
+                let (drawn, bounding_box, glyphs) = hg(&text);
+                assert_eq!(drawn, bounding_box, "{scale}x {baseline:?}");
+                assert_eq!(bounding_box.size, Size::new(12 * scale, height));
+                let top = match baseline {
+                    Baseline::Top => at.y,
+                    Baseline::Bottom => at.y - height as i32 + 1,
+                    Baseline::Middle => at.y - (height as i32 - 1) / 2,
+                    // The baseline is the eighth row of the font
+                    Baseline::Alphabetic => at.y - (8 * scale as i32 - 1),
+                };
+                assert_eq!(bounding_box.top_left, Point::new(at.x, top), "{baseline:?}");
+                if baseline == Baseline::Alphabetic {
+                    // "H" stands on the baseline, "g" reaches below it
+                    let (_, _, h) = hg(&Text::with_baseline("H", at, style, baseline));
+                    assert_eq!(h.bottom_right().unwrap().y, at.y, "{scale}x");
+                    assert!(glyphs.bottom_right().unwrap().y > at.y);
+                }
+            }
+
+            let text = Text::with_alignment("Hg", Point::new(32, 30), style, Alignment::Center);
+            let (drawn, bounding_box, _) = hg(&text);
+            assert_eq!(drawn, bounding_box, "{scale}x centered");
+            // Like unscaled text the extra column of an even width goes right
+            assert_eq!(bounding_box.top_left.x, 32 - (12 * scale as i32 - 1) / 2);
+
+            let text = text.center(Point::new(32, 32));
+            let (drawn, bounding_box, _) = hg(&text);
+            assert_eq!(drawn, bounding_box, "{scale}x centered on a point");
+            assert_eq!(bounding_box.center(), Point::new(32, 32));
+        }
+    }
+}
@@ -0,0 +1,421 @@
+//! Word wrapped text fitted into a rectangle
+
+use embedded_graphics::{
+    geometry::AnchorPoint,
+    mono_font::{MonoFont, MonoTextStyle},
+    pixelcolor::BinaryColor,
+    prelude::*,
+    primitives::Rectangle,
+    text::{Baseline, Text},
+};
+use heapless::String;
+
+/// Appended to the last line when the text doesn't fit
+const ELLIPSIS: &str = "...";
+
+/// Lines of `text` at most `max_chars` characters long
+///
+/// Breaks at `\n` and between words, words longer than a line are broken
+/// where the line ends. Characters are counted, not bytes, so ISO-8859-15
+/// text like "Hyvää päivää" wraps the same as ASCII.
+#[derive(Clone, Debug)]
+pub struct Lines<'a> {
+    rest: &'a str,
+    max_chars: usize,
+    /// A word had to be broken in the middle
+    broke_word: bool,
+}
+
+impl<'a> Lines<'a> {
+    pub fn new(text: &'a str, max_chars: usize) -> Self {
+        Self {
+            rest: text,
+            max_chars,
+            broke_word: false,
+        }
+    }
+
+    /// Returns true if a word has been broken in the middle so far
+    pub fn broke_word(&self) -> bool {
+        self.broke_word
+    }
+}
+
+fn is_space(c: char) -> bool {
+    c == ' ' || c == '\t'
+}
+
+impl<'a> Iterator for Lines<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        if self.rest.is_empty() || self.max_chars == 0 {
+            return None;
+        }
+
+        let (paragraph, after) = match self.rest.find('\n') {
+            Some(end) => (&self.rest[..end], &self.rest[end + 1..]),
+            None => (self.rest, ""),
+        };
+
+        // Byte index of the first character past the line and of the last
+        // space where the line could be broken
+        let mut overflow = None;
+        let mut last_space = None;
+        for (count, (i, c)) in paragraph.char_indices().enumerate() {
+            if is_space(c) {
+                last_space = Some(i);
+            }
+            if count == self.max_chars {
+                overflow = Some(i);
+                break;
+            }
+        }
+
+        let (line, rest) = match (overflow, last_space) {
+            (None, _) => (paragraph, after),
+            // Break at the space, it can be the first character past the line
+            (Some(_), Some(space)) if !paragraph[..space].trim().is_empty() => {
+                (&paragraph[..space], &self.rest[space..])
+            }
+            (Some(end), _) => {
+                self.broke_word = true;
+                (&paragraph[..end], &self.rest[end..])
+            }
+        };
+        self.rest = match overflow {
+            Some(_) => rest.trim_start_matches(is_space),
+            None => rest,
+        };
+        Some(line.trim_end_matches(is_space))
+    }
+}
+
+/// What [`TextBox`] picked to fit its text
+#[derive(Clone, Copy, Debug)]
+pub struct Fit<'a> {
+    pub font: &'a MonoFont<'a>,
+    /// Lines drawn
+    pub lines: usize,
+    /// The text didn't fit even with the smallest font, it was cut short and
+    /// the last line ends in "..."
+    pub truncated: bool,
+    /// A word was broken where a line ended, only done with the smallest font
+    pub broke_word: bool,
+}
+
+/// Text word wrapped into `area` with the largest font that fits
+///
+/// `fonts` are tried from first to last, so list them from largest to
+/// smallest. If the text doesn't fit even in the last font, words are broken
+/// where lines end, and if that isn't enough the last line that fits ends in
+/// "...". ISO-8859-15 fonts work for text with ä, ö and €.
+///
+/// ```ignore
+/// use embedded_graphics::mono_font::iso_8859_15::{FONT_10X20, FONT_6X13, FONT_9X15};
+///
+/// let fonts = [&FONT_10X20, &FONT_9X15, &FONT_6X13];
+/// TextBox::new("Heikki 'Zokol' Juva", area, &fonts).draw(&mut display)?;
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct TextBox<'a> {
+    pub text: &'a str,
+    pub area: Rectangle,
+    pub fonts: &'a [&'a MonoFont<'a>],
+    pub color: BinaryColor,
+    /// Where the lines go in `area`, also aligns the lines with each other
+    pub anchor: AnchorPoint,
+}
+
+impl<'a> TextBox<'a> {
+    /// Black text centered in `area`
+    pub fn new(text: &'a str, area: Rectangle, fonts: &'a [&'a MonoFont<'a>]) -> Self {
+        Self {
+            text,
+            area,
+            fonts,
+            color: BinaryColor::Off,
+            anchor: AnchorPoint::Center,
+        }
+    }
+
+    #[must_use]
+    pub fn with_color(self, color: BinaryColor) -> Self {
+        Self { color, ..self }
+    }
+
+    #[must_use]
+    pub fn with_anchor(self, anchor: AnchorPoint) -> Self {
+        Self { anchor, ..self }
+    }
+
+    /// Characters of `font` that fit on a line
+    fn max_chars(&self, font: &MonoFont) -> usize {
+        let advance = font.character_size.width + font.character_spacing;
+        ((self.area.size.width + font.character_spacing) / advance.max(1)) as usize
+    }
+
+    /// Lines of `font` that fit in the area
+    fn max_lines(&self, font: &MonoFont) -> usize {
+        (self.area.size.height / font.character_size.height.max(1)) as usize
+    }
+
+    /// The font the text is drawn with, `None` if there are no fonts
+    pub fn fit(&self) -> Option<Fit<'a>> {
+        for &font in self.fonts {
+            let mut lines = Lines::new(self.text, self.max_chars(font));
+            let count = lines.by_ref().count();
+            if self.max_chars(font) > 0 && !lines.broke_word() && count <= self.max_lines(font) {
+                return Some(Fit {
+                    font,
+                    lines: count,
+                    truncated: false,
+                    broke_word: false,
+                });
+            }
+        }
+
+        let font = *self.fonts.last()?;
+        let mut lines = Lines::new(self.text, self.max_chars(font));
+        let count = lines.by_ref().count();
+        let max_lines = self.max_lines(font);
+        Some(Fit {
+            font,
+            lines: count.min(max_lines),
+            truncated: count > max_lines || (self.max_chars(font) == 0 && !self.text.is_empty()),
+            broke_word: lines.broke_word(),
+        })
+    }
+
+    /// Width of `line` in `font`
+    fn line_width(font: &MonoFont, line: &str) -> u32 {
+        let chars = line.chars().count() as u32;
+        (chars * (font.character_size.width + font.character_spacing))
+            .saturating_sub(font.character_spacing)
+    }
+}
+
+impl Dimensions for TextBox<'_> {
+    fn bounding_box(&self) -> Rectangle {
+        self.area
+    }
+}
+
+impl Transform for TextBox<'_> {
+    fn translate(&self, by: Point) -> Self {
+        Self {
+            area: self.area.translate(by),
+            ..*self
+        }
+    }
+
+    fn translate_mut(&mut self, by: Point) -> &mut Self {
+        self.area.translate_mut(by);
+        self
+    }
+}
+
+impl<'a> Drawable for TextBox<'a> {
+    type Color = BinaryColor;
+    /// The fit the text was drawn with
+    type Output = Option<Fit<'a>>;
+
+    fn draw<D>(&self, target: &mut D) -> Result<Self::Output, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let fit = match self.fit() {
+            Some(fit) => fit,
+            None => return Ok(None),
+        };
+        let font = fit.font;
+        let max_chars = self.max_chars(font);
+        let line_height = font.character_size.height;
+        let style = MonoTextStyle::new(font, self.color);
+
+        let block = self.area.resized(
+            Size::new(self.area.size.width, fit.lines as u32 * line_height),
+            self.anchor,
+        );
+        for (i, line) in Lines::new(self.text, max_chars).take(fit.lines).enumerate() {
+            let mut shortened: String<128> = String::new();
+            let line = if fit.truncated && i + 1 == fit.lines {
+                let keep = max_chars.saturating_sub(ELLIPSIS.len());
+                let end = line.char_indices().nth(keep).map_or(line.len(), |(i, _)| i);
+                for c in line[..end].trim_end().chars() {
+                    if shortened.len() + c.len_utf8() + ELLIPSIS.len() > shortened.capacity() {
+                        break;
+                    }
+                    let _ = shortened.push(c);
+                }
+                let _ = shortened.push_str(ELLIPSIS);
+                // Lines too short for the whole ellipsis get part of it
+                let end = shortened
+                    .char_indices()
+                    .nth(max_chars)
+                    .map_or(shortened.len(), |(i, _)| i);
+                shortened.truncate(end);
+                shortened.as_str()
+            } else {
+                line
+            };
+
+            let row = Rectangle::new(
+                block.top_left + Point::new(0, (i as u32 * line_height) as i32),
+                Size::new(block.size.width, line_height),
+            );
+            let size = Size::new(Self::line_width(font, line), line_height);
+            let at = row.resized(size, self.anchor).top_left;
+            Text::with_baseline(line, at, style, Baseline::Top).draw(target)?;
+        }
+        Ok(Some(fit))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::iso_8859_15::{FONT_10X20, FONT_6X10},
+    };
+
+    fn lines(text: &str, max_chars: usize) -> (Vec<&str>, bool) {
+        let mut lines = Lines::new(text, max_chars);
+        let all = lines.by_ref().collect();
+        (all, lines.broke_word())
+    }
+
+    #[test]
+    fn wraps_between_words() {
+        assert_eq!(
+            lines("Heikki 'Zokol' Juva", 10),
+            (vec!["Heikki", "'Zokol'", "Juva"], false)
+        );
+        assert_eq!(lines("Heikki Juva", 11), (vec!["Heikki Juva"], false));
+        // The space can be the first character past the line
+        assert_eq!(lines("Heikki Juva", 6), (vec!["Heikki", "Juva"], false));
+        assert_eq!(lines("a  b   c", 2), (vec!["a", "b", "c"], false));
+        assert_eq!(lines("", 5), (vec![], false));
+        assert_eq!(lines("text", 0), (vec![], false));
+    }
+
+    #[test]
+    fn keeps_newlines() {
+        assert_eq!(
+            lines("Zokol\nHacker at large", 8),
+            (vec!["Zokol", "Hacker", "at large"], false)
+        );
+        assert_eq!(lines("a\n\nb", 8), (vec!["a", "", "b"], false));
+    }
+
+    #[test]
+    fn breaks_long_words() {
+        assert_eq!(
+            lines("Kouvostotelecom oy", 6),
+            (vec!["Kouvos", "totele", "com oy"], true)
+        );
+    }
+
+    #[test]
+    fn counts_characters_not_bytes() {
+        assert_eq!(
+            lines("Hyvää päivää, 5 €", 6),
+            (vec!["Hyvää", "päivää", ", 5 €"], true)
+        );
+        assert_eq!(lines("Hyvää päivää", 12), (vec!["Hyvää päivää"], false));
+        assert_eq!(lines("äääääää", 3), (vec!["äää", "äää", "ä"], true));
+    }
+
+    const FONTS: [&MonoFont; 2] = [&FONT_10X20, &FONT_6X10];
+
+    fn text_box(text: &str, width: u32, height: u32) -> TextBox<'_> {
+        let area = Rectangle::new(Point::zero(), Size::new(width, height));
+        TextBox::new(text, area, &FONTS).with_anchor(AnchorPoint::TopLeft)
+    }
+
+    #[test]
+    fn picks_largest_font_that_fits() {
+        let fit = text_box("Zokol", 60, 20).fit().unwrap();
+        assert_eq!(
+            (fit.font.character_size, fit.lines, fit.truncated),
+            (Size::new(10, 20), 1, false)
+        );
+
+        // Two lines of the large font don't fit, three of the small one do
+        let fit = text_box("Heikki Juva", 60, 30).fit().unwrap();
+        assert_eq!(
+            (fit.font.character_size, fit.lines, fit.truncated),
+            (Size::new(6, 10), 2, false)
+        );
+
+        // The large font would break the word
+        let fit = text_box("Kouvosto", 60, 40).fit().unwrap();
+        assert_eq!(
+            (fit.font.character_size, fit.lines, fit.truncated),
+            (Size::new(6, 10), 1, false)
+        );
+
+        assert!(TextBox::new("Zokol", Rectangle::zero(), &[])
+            .fit()
+            .is_none());
+    }
+
+    #[test]
+    fn truncates_with_ellipsis() {
+        let fit = text_box("Hyvää päivää kaikille", 36, 20).fit().unwrap();
+        assert_eq!((fit.lines, fit.truncated, fit.broke_word), (2, true, true));
+
+        // Fits in two lines, but only by breaking the word
+        let fit = text_box("Kouvostotelecom", 60, 20).fit().unwrap();
+        assert_eq!((fit.lines, fit.truncated, fit.broke_word), (2, false, true));
+
+        let fit = text_box("Zokol", 5, 20).fit().unwrap();
+        assert_eq!((fit.lines, fit.truncated, fit.broke_word), (0, true, false));
+    }
+
+    /// Draw `lines` in the small font, one per row from the top left
+    fn drawn(lines: &[&str]) -> MockDisplay<BinaryColor> {
+        let mut display = MockDisplay::new();
+        let style = MonoTextStyle::new(&FONT_6X10, BinaryColor::Off);
+        for (i, line) in lines.iter().enumerate() {
+            Text::with_baseline(line, Point::new(0, i as i32 * 10), style, Baseline::Top)
+                .draw(&mut display)
+                .unwrap();
+        }
+        display
+    }
+
+    #[test]
+    fn draws_ellipsis_only_when_cut_short() {
+        let mut display = MockDisplay::new();
+        text_box("Hyvää päivää kaikille", 36, 20)
+            .draw(&mut display)
+            .unwrap();
+        assert_eq!(display, drawn(&["Hyvää", "päi..."]));
+
+        let mut display = MockDisplay::new();
+        text_box("Kouvostotelecom", 60, 20)
+            .draw(&mut display)
+            .unwrap();
+        assert_eq!(display, drawn(&["Kouvostote", "lecom"]));
+    }
+
+    #[test]
+    fn draws_lines_centered() {
+        let area = Rectangle::new(Point::zero(), Size::new(36, 30));
+        let mut display = MockDisplay::new();
+        TextBox::new("Hyvää päivää", area, &FONTS[1..])
+            .draw(&mut display)
+            .unwrap();
+
+        let mut expected = MockDisplay::new();
+        let style = MonoTextStyle::new(&FONT_6X10, BinaryColor::Off);
+        for (line, at) in [("Hyvää", Point::new(3, 5)), ("päivää", Point::new(0, 15))] {
+            Text::with_baseline(line, at, style, Baseline::Top)
+                .draw(&mut expected)
+                .unwrap();
+        }
+        assert_eq!(display, expected);
+    }
+}
@@ -0,0 +1,5 @@
+//! Images converted at build time from `gfx/images.toml`
+
+use embedded_graphics::{image::ImageRaw, pixelcolor::BinaryColor};
+
+include!(concat!(env!("OUT_DIR"), "/images.rs"));
@@ -0,0 +1,418 @@
+//! Splitting areas into rows, columns and grids
+//!
+//! Layouts only compute rectangles, nothing is allocated or drawn. Move
+//! drawables into the resulting slots with [`Align`], or draw anything with its
+//! origin at a slot with [`draw_in`]:
+//!
+//! ```ignore
+//! let [avatar_slot, text_slot] = HStack::new([Length::fit(&avatar), Length::Fraction(1)])
+//!     .spacing(8)
+//!     .layout(&display.bounding_box());
+//! avatar.align(&avatar_slot, AnchorPoint::CenterLeft).draw(&mut display)?;
+//! text.align(&text_slot, AnchorPoint::Center).draw(&mut display)?;
+//! ```
+//!
+//! [`Align`]: crate::graphics_extensions::Align
+
+use embedded_graphics::{prelude::*, primitives::Rectangle};
+
+use crate::graphics_extensions::Insets;
+
+/// Length of a slot along a stack, or of a grid column or row
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Length {
+    /// Exactly this many pixels
+    Fixed(u32),
+    /// A share of the space the other lengths leave free, weighed against the
+    /// other fractions
+    Fraction(u32),
+    /// Enough for content of this size, its width in an [`HStack`] or grid
+    /// column and its height in a [`VStack`] or grid row
+    Content(Size),
+}
+
+impl Length {
+    /// Enough for the bounding box of `content`
+    pub fn fit(content: &impl Dimensions) -> Self {
+        Length::Content(content.bounding_box().size)
+    }
+
+    /// Pixels taken before fractions are shared out
+    fn base(&self, horizontal: bool) -> u32 {
+        match *self {
+            Length::Fixed(pixels) => pixels,
+            Length::Fraction(_) => 0,
+            Length::Content(size) if horizontal => size.width,
+            Length::Content(size) => size.height,
+        }
+    }
+}
+
+/// Start and length of each slot along one axis
+type Slots<const N: usize> = [(i32, u32); N];
+
+/// Split `total` pixels from `start` on into slots
+///
+/// Fractions are rounded so that together they fill the free space exactly.
+/// Lengths that don't fit run past the end.
+fn split<const N: usize>(
+    lengths: &[Length; N],
+    horizontal: bool,
+    start: i32,
+    total: u32,
+    spacing: u32,
+) -> Slots<N> {
+    let gaps = spacing.saturating_mul((N as u32).saturating_sub(1));
+    let taken = lengths.iter().fold(0u32, |sum, length| {
+        sum.saturating_add(length.base(horizontal))
+    });
+    let free = total.saturating_sub(taken.saturating_add(gaps));
+    let weights = lengths.iter().fold(0u32, |sum, length| match length {
+        Length::Fraction(weight) => sum.saturating_add(*weight),
+        _ => sum,
+    });
+
+    let mut slots = [(0, 0); N];
+    let mut position = start;
+    let mut weight_before = 0u32;
+    for (slot, length) in slots.iter_mut().zip(lengths) {
+        let size = match *length {
+            Length::Fraction(_) if weights == 0 => 0,
+            Length::Fraction(weight) => {
+                let share = |weight: u32| (free as u64 * weight as u64 / weights as u64) as u32;
+                let size = share(weight_before.saturating_add(weight)) - share(weight_before);
+                weight_before = weight_before.saturating_add(weight);
+                size
+            }
+            _ => length.base(horizontal),
+        };
+        *slot = (position, size);
+        let step = i32::try_from(size.saturating_add(spacing)).unwrap_or(i32::MAX);
+        position = position.saturating_add(step);
+    }
+    slots
+}
+
+/// Slots side by side, left to right, each as high as the area
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HStack<const N: usize> {
+    lengths: [Length; N],
+    spacing: u32,
+    padding: Insets,
+}
+
+impl<const N: usize> HStack<N> {
+    pub fn new(lengths: [Length; N]) -> Self {
+        Self {
+            lengths,
+            spacing: 0,
+            padding: Insets::ZERO,
+        }
+    }
+
+    /// Space between the slots
+    #[must_use]
+    pub fn spacing(self, spacing: u32) -> Self {
+        Self { spacing, ..self }
+    }
+
+    /// Space between the edges of the area and the slots
+    #[must_use]
+    pub fn padding(self, padding: impl Into<Insets>) -> Self {
+        Self {
+            padding: padding.into(),
+            ..self
+        }
+    }
+
+    /// The slots in `area`
+    pub fn layout(&self, area: &Rectangle) -> [Rectangle; N] {
+        let area = self.padding.shrink(area);
+        let columns = split(
+            &self.lengths,
+            true,
+            area.top_left.x,
+            area.size.width,
+            self.spacing,
+        );
+        columns.map(|(x, width)| {
+            Rectangle::new(
+                Point::new(x, area.top_left.y),
+                Size::new(width, area.size.height),
+            )
+        })
+    }
+}
+
+/// Slots on top of each other, top to bottom, each as wide as the area
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VStack<const N: usize> {
+    lengths: [Length; N],
+    spacing: u32,
+    padding: Insets,
+}
+
+impl<const N: usize> VStack<N> {
+    pub fn new(lengths: [Length; N]) -> Self {
+        Self {
+            lengths,
+            spacing: 0,
+            padding: Insets::ZERO,
+        }
+    }
+
+    /// Space between the slots
+    #[must_use]
+    pub fn spacing(self, spacing: u32) -> Self {
+        Self { spacing, ..self }
+    }
+
+    /// Space between the edges of the area and the slots
+    #[must_use]
+    pub fn padding(self, padding: impl Into<Insets>) -> Self {
+        Self {
+            padding: padding.into(),
+            ..self
+        }
+    }
+
+    /// The slots in `area`
+    pub fn layout(&self, area: &Rectangle) -> [Rectangle; N] {
+        let area = self.padding.shrink(area);
+        let rows = split(
+            &self.lengths,
+            false,
+            area.top_left.y,
+            area.size.height,
+            self.spacing,
+        );
+        rows.map(|(y, height)| {
+            Rectangle::new(
+                Point::new(area.top_left.x, y),
+                Size::new(area.size.width, height),
+            )
+        })
+    }
+}
+
+/// `C` columns and `R` rows of cells
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Grid<const C: usize, const R: usize> {
+    columns: [Length; C],
+    rows: [Length; R],
+    spacing: Size,
+    padding: Insets,
+}
+
+impl<const C: usize, const R: usize> Grid<C, R> {
+    pub fn new(columns: [Length; C], rows: [Length; R]) -> Self {
+        Self {
+            columns,
+            rows,
+            spacing: Size::zero(),
+            padding: Insets::ZERO,
+        }
+    }
+
+    /// Space between the columns and between the rows
+    #[must_use]
+    pub fn spacing(self, columns: u32, rows: u32) -> Self {
+        Self {
+            spacing: Size::new(columns, rows),
+            ..self
+        }
+    }
+
+    /// Space between the edges of the area and the cells
+    #[must_use]
+    pub fn padding(self, padding: impl Into<Insets>) -> Self {
+        Self {
+            padding: padding.into(),
+            ..self
+        }
+    }
+
+    fn split(&self, area: &Rectangle) -> (Slots<C>, Slots<R>) {
+        let area = self.padding.shrink(area);
+        let columns = split(
+            &self.columns,
+            true,
+            area.top_left.x,
+            area.size.width,
+            self.spacing.width,
+        );
+        let rows = split(
+            &self.rows,
+            false,
+            area.top_left.y,
+            area.size.height,
+            self.spacing.height,
+        );
+        (columns, rows)
+    }
+
+    /// The cells in `area`, indexed by row and then column
+    pub fn layout(&self, area: &Rectangle) -> [[Rectangle; C]; R] {
+        let (columns, rows) = self.split(area);
+        rows.map(|(y, height)| {
+            columns.map(|(x, width)| Rectangle::new(Point::new(x, y), Size::new(width, height)))
+        })
+    }
+
+    /// The cell at `column` and `row` in `area`
+    ///
+    /// # Panics
+    ///
+    /// If the grid has no such cell.
+    pub fn cell(&self, area: &Rectangle, column: usize, row: usize) -> Rectangle {
+        self.span(area, column, row, 1, 1)
+    }
+
+    /// Cells from `column` and `row` on, `columns` wide and `rows` high,
+    /// merged into one including the spacing between them
+    ///
+    /// # Panics
+    ///
+    /// If the span doesn't fit the grid or is empty.
+    pub fn span(
+        &self,
+        area: &Rectangle,
+        column: usize,
+        row: usize,
+        columns: usize,
+        rows: usize,
+    ) -> Rectangle {
+        let (xs, ys) = self.split(area);
+        let (left, _) = xs[column];
+        let (top, _) = ys[row];
+        let (last_x, last_width) = xs[column + columns - 1];
+        let (last_y, last_height) = ys[row + rows - 1];
+        Rectangle::new(
+            Point::new(left, top),
+            Size::new(
+                (last_x + last_width as i32 - left) as u32,
+                (last_y + last_height as i32 - top) as u32,
+            ),
+        )
+    }
+}
+
+/// Draw `drawable` with its origin at the top left corner of `slot`, cut off
+/// at the edges of the slot
+///
+/// For drawables that can't be moved, like badges drawn from the origin.
+pub fn draw_in<T, D>(drawable: &T, slot: &Rectangle, target: &mut D) -> Result<T::Output, D::Error>
+where
+    T: Drawable,
+    D: DrawTarget<Color = T::Color>,
+{
+    drawable.draw(&mut target.clipped(slot).translated(slot.top_left))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_graphics::{
+        mock_display::MockDisplay, pixelcolor::BinaryColor, primitives::PrimitiveStyle,
+    };
+
+    fn area(x: i32, y: i32, width: u32, height: u32) -> Rectangle {
+        Rectangle::new(Point::new(x, y), Size::new(width, height))
+    }
+
+    #[test]
+    fn fixed_lengths() {
+        let slots = HStack::new([Length::Fixed(10), Length::Fixed(20)]).layout(&area(5, 1, 100, 8));
+        assert_eq!(slots, [area(5, 1, 10, 8), area(15, 1, 20, 8)]);
+    }
+
+    #[test]
+    fn fractions_share_the_rest() {
+        let slots = HStack::new([Length::Fixed(10), Length::Fraction(1), Length::Fraction(2)])
+            .spacing(5)
+            .layout(&area(0, 0, 100, 8));
+        assert_eq!(
+            slots,
+            [area(0, 0, 10, 8), area(15, 0, 26, 8), area(46, 0, 54, 8)]
+        );
+    }
+
+    #[test]
+    fn fractions_round_to_fill_exactly() {
+        let slots = VStack::new([Length::Fraction(1); 3]).layout(&area(0, 0, 4, 10));
+        assert_eq!(
+            slots,
+            [area(0, 0, 4, 3), area(0, 3, 4, 3), area(0, 6, 4, 4)]
+        );
+    }
+
+    #[test]
+    fn content_lengths_with_padding() {
+        let content = area(40, 40, 7, 5);
+        let slots = VStack::new([Length::fit(&content), Length::Fraction(1)])
+            .padding(1)
+            .layout(&area(2, 3, 20, 30));
+        assert_eq!(slots, [area(3, 4, 18, 5), area(3, 9, 18, 23)]);
+
+        let slots = HStack::new([Length::fit(&content), Length::Fraction(1)])
+            .padding(Insets::symmetric(2, 1))
+            .layout(&area(0, 0, 20, 10));
+        assert_eq!(slots, [area(2, 1, 7, 8), area(9, 1, 9, 8)]);
+    }
+
+    #[test]
+    fn grid_cells_and_spans() {
+        let grid = Grid::new(
+            [Length::Fraction(2), Length::Fraction(1)],
+            [Length::Fixed(10), Length::Fraction(1)],
+        )
+        .spacing(2, 3);
+        let screen = area(0, 0, 32, 40);
+        assert_eq!(
+            grid.layout(&screen),
+            [
+                [area(0, 0, 20, 10), area(22, 0, 10, 10)],
+                [area(0, 13, 20, 27), area(22, 13, 10, 27)],
+            ]
+        );
+        assert_eq!(grid.cell(&screen, 1, 1), area(22, 13, 10, 27));
+        assert_eq!(grid.span(&screen, 0, 0, 2, 2), screen);
+    }
+
+    #[test]
+    fn lengths_that_dont_fit_run_past_the_end() {
+        let slots = HStack::new([Length::Fixed(30), Length::Fixed(30), Length::Fraction(1)])
+            .spacing(2)
+            .layout(&area(0, 0, 40, 8));
+        assert_eq!(
+            slots,
+            [area(0, 0, 30, 8), area(32, 0, 30, 8), area(64, 0, 0, 8)]
+        );
+    }
+
+    #[test]
+    fn huge_lengths_dont_overflow() {
+        let slots = HStack::new([
+            Length::Fixed(u32::MAX),
+            Length::Fraction(u32::MAX),
+            Length::Fraction(u32::MAX),
+        ])
+        .spacing(u32::MAX)
+        .layout(&area(0, 0, 40, 8));
+        assert_eq!(slots[0], area(0, 0, u32::MAX, 8));
+        assert_eq!(slots[1].size.width, 0);
+        assert_eq!(slots[2].size.width, 0);
+    }
+
+    #[test]
+    fn draw_in_moves_and_clips() {
+        let mut display = MockDisplay::new();
+        let square = area(0, 0, 3, 3).into_styled(PrimitiveStyle::with_fill(BinaryColor::Off));
+        draw_in(&square, &area(2, 1, 2, 2), &mut display).unwrap();
+        display.assert_pattern(&[
+            "    ", //
+            "  ..", //
+            "  ..", //
+        ]);
+    }
+}
@@ -1,4 +1,25 @@
-#![no_std]
-#![no_main]
+#![cfg_attr(not(test), no_std)]
+#![cfg_attr(not(test), no_main)]
+
+#[cfg(feature = "sim")]
+extern crate std;
+
+pub mod badge;
+pub mod barcode;
+pub mod board;
 pub mod bsp;
+pub mod buttons;
+pub mod contact;
+pub mod display;
+pub mod fonts;
+pub mod gestures;
 pub mod graphics_extensions;
+pub mod images;
+pub mod layout;
+pub mod pages;
+pub mod power;
+pub mod qr;
+#[cfg(feature = "sim")]
+pub mod sim;
+
+pub use board::{Badger2040, Config};
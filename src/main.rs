@@ -8,99 +8,45 @@
 use panic_halt as _;
 
 // Required traits
-use embedded_hal::digital::v2::OutputPin;
-use fugit::RateExtU32;
-use rp2040_hal::clocks::Clock;
+use embedded_hal::digital::v2::{InputPin, OutputPin};
 
 // Hardware
-use hal::gpio::{Floating, Function, Input, Output, Pin, PushPull, Spi};
+use badger2040::power::{self, GuardAction, GuardConfig, LowBatteryGuard};
+use badger2040::{Badger2040, Config};
 
-use badger2040::bsp;
-
-use bsp::entry;
-use bsp::hal;
-use bsp::hal::pac;
-use uc8151::Uc8151;
+use badger2040::bsp::{entry, hal::Timer};
 
 // Graphics library
 use embedded_graphics::{
     mono_font::{ascii::FONT_10X20, MonoTextStyle},
     pixelcolor::BinaryColor,
     prelude::*,
-    primitives::{PrimitiveStyleBuilder, StrokeAlignment::Outside},
     text::{Alignment, Text},
 };
 // endregion
 
 // region: embedded_graphics extensions
-use badger2040::graphics_extensions::Centering;
+use badger2040::graphics_extensions::{BoxStyle, Boxed, Centering, Insets};
 // endregion
 
 #[entry]
 fn main() -> ! {
-    // region: initialization
-    let mut pac = pac::Peripherals::take().unwrap();
-    let core = pac::CorePeripherals::take().unwrap();
-    let mut watchdog = hal::Watchdog::new(pac.WATCHDOG);
-    let clocks = hal::clocks::init_clocks_and_plls(
-        bsp::XOSC_CRYSTAL_FREQ,
-        pac.XOSC,
-        pac.CLOCKS,
-        pac.PLL_SYS,
-        pac.PLL_USB,
-        &mut pac.RESETS,
-        &mut watchdog,
-    )
-    .ok()
+    let mut board = Badger2040::take(Config {
+        lut: uc8151::LUT::Fast,
+        ..Config::default()
+    })
     .unwrap();
 
-    let mut delay = cortex_m::delay::Delay::new(core.SYST, clocks.system_clock.freq().to_Hz());
-
-    let sio = hal::Sio::new(pac.SIO);
-
-    let pins = bsp::Pins::new(
-        pac.IO_BANK0,
-        pac.PADS_BANK0,
-        sio.gpio_bank0,
-        &mut pac.RESETS,
-    );
-
-    let _sclk: Pin<_, Function<Spi>> = pins.sclk.into_mode();
-    let _miso: Pin<_, Function<Spi>> = pins.miso.into_mode();
-    let _mosi: Pin<_, Function<Spi>> = pins.mosi.into_mode();
-    let spi_cs: Pin<_, Output<PushPull>> = pins.inky_cs_gpio.into_mode();
-
-    let spi = hal::Spi::<_, _, 8>::new(pac.SPI0);
-
-    let spi = spi.init(
-        &mut pac.RESETS,
-        clocks.peripheral_clock.freq(),
-        10_000_000u32.Hz(),
-        &embedded_hal::spi::MODE_0,
-    );
-
-    let dc_pin: Pin<_, Output<PushPull>> = pins.inky_dc.into_mode();
-    let reset_pin: Pin<_, Output<PushPull>> = pins.inky_res.into_mode();
-    let busy_pin: Pin<_, Input<Floating>> = pins.inky_busy.into_mode();
-
-    // endregion
-
-    let mut display = Uc8151::new(spi, spi_cs, dc_pin, busy_pin, reset_pin);
-
-    display.enable();
-    display.setup(&mut delay, uc8151::LUT::Fast).unwrap();
-
     let style_black = MonoTextStyle::new(&FONT_10X20, BinaryColor::Off);
-    let box_style = PrimitiveStyleBuilder::new()
-        .stroke_color(BinaryColor::Off)
-        .stroke_width(10)
-        .stroke_alignment(Outside)
-        .fill_color(BinaryColor::On)
-        .build();
+    let box_style = BoxStyle {
+        border: 10,
+        padding: Insets::ZERO,
+        ..BoxStyle::default()
+    };
 
-    let mut led: Pin<_, Output<PushPull>> = pins.led.into_mode();
+    let now_ms = |timer: &Timer| (timer.get_counter().ticks() / 1000) as u32;
+    let mut guard = LowBatteryGuard::new(GuardConfig::default(), now_ms(&board.timer));
 
-    let screen_center = Point::new((uc8151::WIDTH / 2) as i32, (uc8151::HEIGHT / 2) as i32);
     loop {
         let text = Text::with_alignment(
             "Automatic\nsupersonic\nmultiline\ncentering",
@@ -109,18 +55,27 @@ fn main() -> ! {
             Alignment::Center,
         );
 
-        let text = text.center(screen_center);
+        let mut screen = board.screen();
+        let text = text.center(screen.bounding_box().center());
+
+        Boxed::new(text, box_style).draw(&mut screen).unwrap();
 
-        text.bounding_box()
-            .into_styled(box_style)
-            .draw(&mut display)
-            .unwrap();
-        text.draw(&mut display).unwrap();
+        board.led.set_high().unwrap();
+        board.display.update().unwrap();
+        board.led.set_low().unwrap();
 
-        led.set_high().unwrap();
-        display.update().unwrap();
-        led.set_low().unwrap();
+        let reading = board.battery.read();
+        let usb_powered = board.vbus_detect.is_high().unwrap();
+        let now = now_ms(&board.timer);
+        if guard.update(reading.millivolts, usb_powered, now) == GuardAction::Freeze {
+            power::freeze(
+                &mut board.display,
+                &mut board.delay,
+                &mut board.p3v3_en,
+                &board.buttons,
+            );
+        }
 
-        delay.delay_ms(10000);
+        board.delay.delay_ms(10000);
     }
 }
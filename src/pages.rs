@@ -0,0 +1,484 @@
+//! Several screens on one badge, switched with the front buttons
+//!
+//! A badge lists its [`Page`]s, e.g. identity, contact QR code, schedule and
+//! about, and feeds its [`ButtonEvent`]s to [`Pages`]:
+//!
+//! * `A` and `B` show the first and second page, `C` the third and on the
+//!   third and later pages the next one, wrapping back to the third
+//! * `Up` and `Down` scroll the current page, held down they repeat
+//!
+//! ```ignore
+//! let mut pages = Pages::new(manager, [&identity, &contact, &schedule, &about]);
+//! loop {
+//!     buttons.poll(now_ms)?;
+//!     while let Some(event) = buttons.next_event() {
+//!         pages.handle(event);
+//!     }
+//!     pages.update(now_ms)?;
+//! }
+//! ```
+//!
+//! Each page picks its waveform. [`LUT::Normal`] pages get a full, cleaning
+//! refresh whenever they change, faster ones go through the
+//! [`RefreshManager`] and scrolling refreshes only their
+//! [`Page::scroll_area`] with a partial refresh.
+
+use embedded_graphics::{pixelcolor::BinaryColor, prelude::*, primitives::Rectangle};
+use uc8151::{SpiDataError, LUT};
+
+use crate::badge::BadgeSpec;
+use crate::buttons::{Button, ButtonEvent, EventKind};
+use crate::display::{Panel, Refresh, RefreshManager};
+
+/// The page `C` shows first, later pages are reached by pressing it again
+const FIRST_C_PAGE: usize = 2;
+
+/// One screen of a badge
+///
+/// Pages are drawn from scratch on a white screen whenever they are shown or
+/// scrolled, state like the scroll position is kept by [`Pages`].
+pub trait Page<D: DrawTarget<Color = BinaryColor>> {
+    /// Draw the page scrolled down by `scroll` steps
+    fn draw(&self, target: &mut D, scroll: u32) -> Result<(), D::Error>;
+
+    /// Waveform the page is refreshed with
+    fn lut(&self) -> LUT {
+        LUT::Normal
+    }
+
+    /// Steps the page scrolls down, 0 for pages that don't scroll
+    fn scroll_steps(&self) -> u32 {
+        0
+    }
+
+    /// The part of `screen` that changes when scrolling
+    fn scroll_area(&self, screen: Rectangle) -> Rectangle {
+        screen
+    }
+}
+
+/// A badge as a page that doesn't scroll, e.g. the identity page
+impl<D: DrawTarget<Color = BinaryColor>> Page<D> for BadgeSpec<'_> {
+    fn draw(&self, target: &mut D, _scroll: u32) -> Result<(), D::Error> {
+        Drawable::draw(self, target)
+    }
+}
+
+/// What has to be redrawn, a new page covers scrolling
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Redraw {
+    Scroll,
+    Page,
+}
+
+/// Why a page couldn't be shown
+#[derive(Debug)]
+pub enum Error<E> {
+    Draw(E),
+    Refresh(SpiDataError),
+}
+
+/// Switches between the pages of a badge and refreshes the display for them
+pub struct Pages<'a, D: Panel, const N: usize> {
+    manager: RefreshManager<D>,
+    pages: [&'a dyn Page<D>; N],
+    current: usize,
+    /// Scroll position of each page, kept while other pages are shown
+    scroll: [u32; N],
+    pending: Option<Redraw>,
+}
+
+impl<'a, D: Panel, const N: usize> Pages<'a, D, N> {
+    /// Pages shown on `manager`, starting with the first one at the next
+    /// [`Pages::update`]
+    pub fn new(manager: RefreshManager<D>, pages: [&'a dyn Page<D>; N]) -> Self {
+        Self {
+            manager,
+            pages,
+            current: 0,
+            scroll: [0; N],
+            pending: Some(Redraw::Page),
+        }
+    }
+
+    /// Index of the page shown
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    /// Scroll position of the page shown
+    pub fn scroll(&self) -> u32 {
+        self.scroll.get(self.current).copied().unwrap_or(0)
+    }
+
+    /// Returns true if a change waits for [`Pages::update`]
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// The refresh manager, e.g. to clean the screen
+    pub fn manager(&mut self) -> &mut RefreshManager<D> {
+        &mut self.manager
+    }
+
+    /// Release the refresh manager
+    pub fn free(self) -> RefreshManager<D> {
+        self.manager
+    }
+
+    fn request(&mut self, redraw: Redraw) {
+        self.pending = self.pending.max(Some(redraw));
+    }
+
+    /// Switch to `page`, returns true if it wasn't shown already
+    pub fn show(&mut self, page: usize) -> bool {
+        if page >= N || page == self.current {
+            return false;
+        }
+        self.current = page;
+        self.request(Redraw::Page);
+        true
+    }
+
+    /// Scroll the page shown to `scroll`, limited to its
+    /// [`Page::scroll_steps`], returns true if it moved
+    pub fn scroll_to(&mut self, scroll: u32) -> bool {
+        let (page, current) = match self.pages.get(self.current) {
+            Some(page) => (page, self.scroll[self.current]),
+            None => return false,
+        };
+        let scroll = scroll.min(page.scroll_steps());
+        if scroll == current {
+            return false;
+        }
+        self.scroll[self.current] = scroll;
+        self.request(Redraw::Scroll);
+        true
+    }
+
+    /// Switch pages or scroll for `event`, returns true if anything changed
+    pub fn handle(&mut self, event: ButtonEvent) -> bool {
+        let pressed = event.kind == EventKind::Press;
+        let repeated = matches!(event.kind, EventKind::Press | EventKind::Repeat);
+        match event.button {
+            Button::A if pressed => self.show(0),
+            Button::B if pressed => self.show(1),
+            Button::C if pressed && self.current >= FIRST_C_PAGE => {
+                let next = self.current + 1;
+                self.show(if next < N { next } else { FIRST_C_PAGE })
+            }
+            Button::C if pressed => self.show(FIRST_C_PAGE),
+            Button::Up if repeated => self.scroll_to(self.scroll().saturating_sub(1)),
+            Button::Down if repeated => self.scroll_to(self.scroll().saturating_add(1)),
+            _ => false,
+        }
+    }
+
+    /// Draw and refresh what changed, or clean the screen when that is due
+    ///
+    /// Call this regularly, like [`RefreshManager::refresh`]. When drawing or
+    /// refreshing fails the change stays pending for the next call.
+    pub fn update(&mut self, now_ms: u32) -> Result<Refresh, Error<D::Error>> {
+        let (redraw, page) = match (self.pending.take(), self.pages.get(self.current)) {
+            (Some(redraw), Some(page)) => (redraw, *page),
+            _ => return self.manager.refresh(now_ms).map_err(Error::Refresh),
+        };
+        let result = self.redraw(redraw, page, now_ms);
+        if result.is_err() {
+            self.request(redraw);
+        }
+        result
+    }
+
+    fn redraw(
+        &mut self,
+        redraw: Redraw,
+        page: &dyn Page<D>,
+        now_ms: u32,
+    ) -> Result<Refresh, Error<D::Error>> {
+        let lut = page.lut();
+        let display = self.manager.display();
+        display.set_lut(lut).map_err(Error::Refresh)?;
+        display.clear(BinaryColor::On).map_err(Error::Draw)?;
+        page.draw(display, self.scroll[self.current])
+            .map_err(Error::Draw)?;
+
+        if matches!(lut, LUT::Normal | LUT::Internal) {
+            return self.manager.clean().map_err(Error::Refresh);
+        }
+        // Outside the scroll area the page is drawn as it was
+        let screen = display.bounding_box();
+        let area = match redraw {
+            Redraw::Page => screen,
+            Redraw::Scroll => page.scroll_area(screen),
+        };
+        self.manager.mark_dirty(area);
+        self.manager.refresh(now_ms).map_err(Error::Refresh)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::RefreshConfig;
+    use core::cell::Cell;
+    use uc8151::{HEIGHT, WIDTH};
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    enum Call {
+        Refresh { clean: bool },
+        Area(Rectangle),
+    }
+
+    #[derive(Debug)]
+    struct DrawError;
+
+    /// A panel that records refreshes and fails when told to
+    struct Recorder {
+        calls: std::vec::Vec<Call>,
+        lut: LUT,
+        fail_draw: bool,
+        fail_refresh: bool,
+    }
+
+    impl OriginDimensions for Recorder {
+        fn size(&self) -> Size {
+            Size::new(WIDTH, HEIGHT)
+        }
+    }
+
+    impl DrawTarget for Recorder {
+        type Color = BinaryColor;
+        type Error = DrawError;
+
+        fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Pixel<Self::Color>>,
+        {
+            pixels.into_iter().for_each(drop);
+            if self.fail_draw {
+                return Err(DrawError);
+            }
+            Ok(())
+        }
+    }
+
+    impl Recorder {
+        fn result(&self) -> Result<(), SpiDataError> {
+            match self.fail_refresh {
+                true => Err(SpiDataError::SpiError),
+                false => Ok(()),
+            }
+        }
+    }
+
+    impl Panel for Recorder {
+        fn refresh(&mut self, clean: bool) -> Result<(), SpiDataError> {
+            self.result()?;
+            self.calls.push(Call::Refresh { clean });
+            Ok(())
+        }
+
+        fn refresh_area(&mut self, area: Rectangle) -> Result<(), SpiDataError> {
+            self.result()?;
+            self.calls.push(Call::Area(area));
+            Ok(())
+        }
+
+        fn set_lut(&mut self, lut: LUT) -> Result<(), SpiDataError> {
+            self.lut = lut;
+            Ok(())
+        }
+    }
+
+    /// A page that remembers the scroll position it was last drawn at
+    struct TestPage {
+        lut: LUT,
+        steps: u32,
+        drawn: Cell<Option<u32>>,
+    }
+
+    impl TestPage {
+        fn new(lut: LUT, steps: u32) -> Self {
+            Self {
+                lut,
+                steps,
+                drawn: Cell::new(None),
+            }
+        }
+    }
+
+    impl<D: DrawTarget<Color = BinaryColor>> Page<D> for TestPage {
+        fn draw(&self, target: &mut D, scroll: u32) -> Result<(), D::Error> {
+            self.drawn.set(Some(scroll));
+            Pixel(Point::new(10, 50), BinaryColor::Off).draw(target)
+        }
+
+        fn lut(&self) -> LUT {
+            self.lut
+        }
+
+        fn scroll_steps(&self) -> u32 {
+            self.steps
+        }
+
+        fn scroll_area(&self, screen: Rectangle) -> Rectangle {
+            Rectangle::new(Point::new(0, 34), Size::new(screen.size.width, 60))
+        }
+    }
+
+    fn pages<'a, const N: usize>(pages: [&'a dyn Page<Recorder>; N]) -> Pages<'a, Recorder, N> {
+        let recorder = Recorder {
+            calls: std::vec::Vec::new(),
+            lut: LUT::Internal,
+            fail_draw: false,
+            fail_refresh: false,
+        };
+        Pages::new(
+            RefreshManager::new(recorder, RefreshConfig::default()),
+            pages,
+        )
+    }
+
+    fn press(button: Button) -> ButtonEvent {
+        ButtonEvent {
+            button,
+            kind: EventKind::Press,
+        }
+    }
+
+    /// Take the refreshes asked for
+    fn calls<const N: usize>(pages: &mut Pages<Recorder, N>) -> std::vec::Vec<Call> {
+        core::mem::take(&mut pages.manager().display().calls)
+    }
+
+    #[test]
+    fn buttons_switch_pages() {
+        let page = TestPage::new(LUT::Normal, 0);
+        let mut pages = pages([&page, &page, &page, &page]);
+        assert_eq!(pages.current(), 0);
+        assert!(!pages.handle(press(Button::A)));
+
+        let mut shown = std::vec::Vec::new();
+        for button in [
+            Button::B,
+            Button::C,
+            Button::C,
+            Button::C,
+            Button::A,
+            Button::C,
+        ] {
+            assert!(pages.handle(press(button)));
+            shown.push(pages.current());
+        }
+        // C moves on from the third page and wraps back to it
+        assert_eq!(shown, [1, 2, 3, 2, 0, 2]);
+
+        let release = ButtonEvent {
+            button: Button::B,
+            kind: EventKind::Release,
+        };
+        let long_press = ButtonEvent {
+            button: Button::B,
+            kind: EventKind::LongPress,
+        };
+        assert!(!pages.handle(release));
+        assert!(!pages.handle(long_press));
+        assert_eq!(pages.current(), 2);
+    }
+
+    #[test]
+    fn c_on_the_only_c_page() {
+        let page = TestPage::new(LUT::Normal, 0);
+        let mut three = pages([&page, &page, &page]);
+        assert!(three.handle(press(Button::C)));
+        assert!(!three.handle(press(Button::C)));
+        assert_eq!(three.current(), 2);
+
+        let mut two = pages([&page, &page]);
+        assert!(!two.handle(press(Button::C)));
+        assert_eq!(two.current(), 0);
+    }
+
+    #[test]
+    fn scrolling_is_clamped() {
+        let long = TestPage::new(LUT::Fast, 2);
+        let short = TestPage::new(LUT::Fast, 0);
+        let mut pages = pages([&long, &short]);
+        let repeat = ButtonEvent {
+            button: Button::Down,
+            kind: EventKind::Repeat,
+        };
+
+        assert!(!pages.handle(press(Button::Up)));
+        assert!(pages.handle(press(Button::Down)));
+        assert!(pages.handle(repeat));
+        assert!(!pages.handle(repeat));
+        assert_eq!(pages.scroll(), 2);
+        assert!(!pages.scroll_to(7));
+        assert_eq!(pages.scroll(), 2);
+
+        // Each page keeps its own position
+        pages.handle(press(Button::B));
+        assert!(!pages.handle(press(Button::Down)));
+        assert_eq!(pages.scroll(), 0);
+        pages.handle(press(Button::A));
+        assert_eq!(pages.scroll(), 2);
+
+        assert!(pages.handle(press(Button::Up)));
+        assert!(pages.scroll_to(0));
+        assert!(!pages.handle(press(Button::Up)));
+        assert_eq!(pages.scroll(), 0);
+    }
+
+    #[test]
+    fn update_refreshes_by_waveform() {
+        let normal = TestPage::new(LUT::Normal, 0);
+        let fast = TestPage::new(LUT::Fast, 3);
+        let mut pages = pages([&normal, &fast]);
+
+        // Normal pages get a cleaning refresh
+        assert!(pages.is_pending());
+        assert_eq!(pages.update(0).unwrap(), Refresh::Clean);
+        assert_eq!(calls(&mut pages), [Call::Refresh { clean: true }]);
+        assert_eq!(normal.drawn.get(), Some(0));
+        assert!(!pages.is_pending());
+        assert_eq!(pages.update(10).unwrap(), Refresh::None);
+
+        // Fast ones are refreshed in full when shown
+        pages.handle(press(Button::B));
+        assert_eq!(pages.update(20).unwrap(), Refresh::Full);
+        assert!(pages.manager().display().lut == LUT::Fast);
+        assert_eq!(calls(&mut pages), [Call::Refresh { clean: false }]);
+
+        // and only in their scroll area when scrolled
+        pages.handle(press(Button::Down));
+        assert_eq!(pages.update(30).unwrap(), Refresh::Partial(1));
+        assert_eq!(fast.drawn.get(), Some(1));
+        let area = Rectangle::new(Point::new(0, 32), Size::new(WIDTH, 64));
+        assert_eq!(calls(&mut pages), [Call::Area(area)]);
+    }
+
+    #[test]
+    fn failed_update_stays_pending() {
+        let fast = TestPage::new(LUT::Fast, 3);
+        let mut pages = pages([&fast]);
+        pages.update(0).unwrap();
+
+        pages.scroll_to(1);
+        pages.manager().display().fail_refresh = true;
+        assert!(matches!(pages.update(10), Err(Error::Refresh(_))));
+        assert!(pages.is_pending());
+
+        pages.manager().display().fail_refresh = false;
+        pages.manager().display().fail_draw = true;
+        assert!(matches!(pages.update(20), Err(Error::Draw(DrawError))));
+        assert!(pages.is_pending());
+
+        pages.manager().display().fail_draw = false;
+        calls(&mut pages);
+        assert_eq!(pages.update(30).unwrap(), Refresh::Partial(1));
+        assert!(!pages.is_pending());
+        assert_eq!(fast.drawn.get(), Some(1));
+    }
+}
@@ -0,0 +1,516 @@
+//! Battery monitoring and power control for the badger2040
+
+use embedded_graphics::{prelude::*, primitives::Rectangle};
+use embedded_hal::adc::OneShot;
+use embedded_hal::blocking::delay::DelayUs;
+use embedded_hal::digital::v2::{InputPin, OutputPin};
+
+use crate::board::{self, Display};
+use crate::bsp::{self, hal};
+use crate::buttons::{Button, Input};
+use crate::graphics_extensions::Banner;
+
+/// Voltage of the reference on `vref_1v24`
+pub const VREF_MILLIVOLTS: u32 = 1240;
+
+/// `vbat_sense` sees a third of the battery voltage
+const VBAT_DIVIDER: u32 = 3;
+
+/// ADC samples averaged per reading
+const SAMPLES: u32 = 8;
+
+/// A point on a battery discharge curve
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CurvePoint {
+    pub millivolts: u16,
+    pub percent: u8,
+}
+
+const fn point(millivolts: u16, percent: u8) -> CurvePoint {
+    CurvePoint {
+        millivolts,
+        percent,
+    }
+}
+
+/// Single cell LiPo, points ordered from full to empty
+pub const LIPO: &[CurvePoint] = &[
+    point(4200, 100),
+    point(4000, 85),
+    point(3850, 65),
+    point(3750, 45),
+    point(3700, 30),
+    point(3600, 15),
+    point(3500, 5),
+    point(3300, 0),
+];
+
+/// Three alkaline AAA cells in series, points ordered from full to empty
+pub const AAA_ALKALINE: &[CurvePoint] = &[
+    point(4650, 100),
+    point(4200, 80),
+    point(3900, 60),
+    point(3700, 40),
+    point(3500, 20),
+    point(3300, 10),
+    point(3000, 0),
+];
+
+/// A battery measurement
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Reading {
+    pub millivolts: u16,
+    pub percent: u8,
+}
+
+/// Battery voltage from raw `vbat_sense` and `vref_1v24` ADC values
+///
+/// The supply voltage of the ADC follows the battery, so the raw value is
+/// scaled against the 1.24 V reference instead of assuming 3.3 V.
+pub fn battery_millivolts(vbat_raw: u16, vref_raw: u16) -> u16 {
+    if vref_raw == 0 {
+        return 0;
+    }
+    let millivolts = VBAT_DIVIDER * VREF_MILLIVOLTS * vbat_raw as u32 / vref_raw as u32;
+    millivolts.min(u16::MAX as u32) as u16
+}
+
+/// Estimated charge left, interpolated linearly between the points of `curve`
+///
+/// The points of `curve` must be ordered from full to empty, with both the
+/// voltage and the percentage falling. Other curves don't panic but give
+/// meaningless estimates.
+pub fn percentage(millivolts: u16, curve: &[CurvePoint]) -> u8 {
+    let (first, last) = match (curve.first(), curve.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return 0,
+    };
+    if millivolts >= first.millivolts {
+        return first.percent;
+    }
+    if millivolts <= last.millivolts {
+        return last.percent;
+    }
+
+    for pair in curve.windows(2) {
+        let (high, low) = (pair[0], pair[1]);
+        if millivolts >= low.millivolts {
+            let span = high.millivolts.saturating_sub(low.millivolts) as u32;
+            let above = (millivolts - low.millivolts) as u32;
+            let range = high.percent.saturating_sub(low.percent) as u32;
+            let above = above.min(span);
+            return low
+                .percent
+                .saturating_add((above * range / span.max(1)) as u8);
+        }
+    }
+    last.percent
+}
+
+/// Measures the battery through `vbat_sense` and the 1.24 V reference
+pub struct Battery {
+    adc: hal::Adc,
+    vbat_sense: bsp::VbatSense,
+    vref_power: bsp::VrefPower,
+    vref_1v24: bsp::Vref1v24,
+    curve: &'static [CurvePoint],
+}
+
+impl Battery {
+    pub fn new(
+        adc: hal::Adc,
+        vbat_sense: bsp::VbatSense,
+        vref_power: bsp::VrefPower,
+        vref_1v24: bsp::Vref1v24,
+        curve: &'static [CurvePoint],
+    ) -> Self {
+        Self {
+            adc,
+            vbat_sense,
+            vref_power,
+            vref_1v24,
+            curve,
+        }
+    }
+
+    /// Change the discharge curve used for the percentage
+    pub fn set_curve(&mut self, curve: &'static [CurvePoint]) {
+        self.curve = curve;
+    }
+
+    /// Raw averaged ADC values of `vbat_sense` and `vref_1v24`
+    pub fn read_raw(&mut self) -> (u16, u16) {
+        // Ignoring return value for set, RP2040 GPIO is infallible
+        let _ = self.vref_power.set_high();
+
+        let mut vbat = 0u32;
+        let mut vref = 0u32;
+        for _ in 0..SAMPLES {
+            let sample: u16 = nb::block!(self.adc.read(&mut self.vbat_sense)).unwrap_or(0);
+            vbat += sample as u32;
+            let sample: u16 = nb::block!(self.adc.read(&mut self.vref_1v24)).unwrap_or(0);
+            vref += sample as u32;
+        }
+
+        let _ = self.vref_power.set_low();
+        ((vbat / SAMPLES) as u16, (vref / SAMPLES) as u16)
+    }
+
+    /// Measure the battery voltage and estimate the charge left
+    pub fn read(&mut self) -> Reading {
+        let (vbat_raw, vref_raw) = self.read_raw();
+        let millivolts = battery_millivolts(vbat_raw, vref_raw);
+        Reading {
+            millivolts,
+            percent: percentage(millivolts, self.curve),
+        }
+    }
+
+    /// Release the ADC and pins
+    pub fn free(self) -> (hal::Adc, bsp::VbatSense, bsp::VrefPower, bsp::Vref1v24) {
+        (self.adc, self.vbat_sense, self.vref_power, self.vref_1v24)
+    }
+}
+
+/// What started the board
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WakeReason {
+    /// A button was held down while booting
+    Button(Button),
+    /// Power was plugged in or the board was reset
+    Other,
+}
+
+/// Decode the wake reason from the button pins, first pressed input wins
+///
+/// Must be called right after boot while the waking button is still held.
+pub fn wake_reason<P: InputPin>(inputs: &[Input<P>]) -> Result<WakeReason, P::Error> {
+    for input in inputs {
+        if input.read()? {
+            return Ok(WakeReason::Button(input.button));
+        }
+    }
+    Ok(WakeReason::Other)
+}
+
+/// Power the board down until a front button is pressed
+///
+/// Waits for the display to finish refreshing and turns it off, calls
+/// `persist` to save any state and releases the 3V3 rail. On battery this cuts
+/// the power and the next boot reports the button in
+/// [`crate::Badger2040::wake_reason`]. On USB power the rail stays up, so the
+/// buttons are polled instead and the board is reset once one is pressed.
+pub fn sleep_until_button(
+    display: &mut Display,
+    p3v3_en: &mut bsp::P3v3En,
+    buttons: &board::Buttons,
+    persist: impl FnOnce(),
+) -> ! {
+    while display.is_busy() {}
+    let _ = display.off();
+
+    persist();
+
+    // Ignoring return value for set, RP2040 GPIO is infallible
+    let _ = p3v3_en.set_low();
+
+    // Still running, so we are powered through USB
+    while buttons.wake_reason() == WakeReason::Other {}
+    cortex_m::peripheral::SCB::sys_reset()
+}
+
+/// Thresholds for [`LowBatteryGuard`]
+#[derive(Clone, Copy, Debug)]
+pub struct GuardConfig {
+    /// The battery counts as low below this voltage
+    pub low_millivolts: u16,
+    /// A low battery has to recover this much above `low_millivolts`
+    pub hysteresis_millivolts: u16,
+    /// Never freeze earlier than this after boot, so the badge gets drawn
+    pub min_on_time_ms: u32,
+}
+
+impl Default for GuardConfig {
+    fn default() -> Self {
+        Self {
+            low_millivolts: 3400,
+            hysteresis_millivolts: 150,
+            min_on_time_ms: 10_000,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GuardAction {
+    /// Keep running
+    Run,
+    /// Draw the final image and power down with [`freeze`]
+    Freeze,
+}
+
+/// Decides when a draining battery should freeze the badge
+#[derive(Clone, Copy, Debug)]
+pub struct LowBatteryGuard {
+    config: GuardConfig,
+    started_at: u32,
+    low: bool,
+    frozen: bool,
+}
+
+impl LowBatteryGuard {
+    pub fn new(config: GuardConfig, now_ms: u32) -> Self {
+        Self {
+            config,
+            started_at: now_ms,
+            low: false,
+            frozen: false,
+        }
+    }
+
+    /// Returns true while the battery counts as low
+    pub fn is_low(&self) -> bool {
+        self.low
+    }
+
+    /// Returns true once [`GuardAction::Freeze`] has been returned
+    pub fn is_frozen(&self) -> bool {
+        self.frozen
+    }
+
+    /// Feed a battery reading, `usb_powered` never freezes
+    ///
+    /// [`GuardAction::Freeze`] is returned only once, so the final image is
+    /// drawn a single time even if the board stays powered.
+    pub fn update(&mut self, millivolts: u16, usb_powered: bool, now_ms: u32) -> GuardAction {
+        let recovered = self
+            .config
+            .low_millivolts
+            .saturating_add(self.config.hysteresis_millivolts);
+        if millivolts < self.config.low_millivolts {
+            self.low = true;
+        } else if millivolts >= recovered {
+            self.low = false;
+        }
+
+        let on_time = now_ms.wrapping_sub(self.started_at);
+        if self.low && !usb_powered && !self.frozen && on_time >= self.config.min_on_time_ms {
+            self.frozen = true;
+            GuardAction::Freeze
+        } else {
+            GuardAction::Run
+        }
+    }
+}
+
+/// Freeze the current image with a low battery notice and power down
+///
+/// Draws a banner over the bottom of the screen, does a clean
+/// [`uc8151::LUT::Normal`] refresh and sleeps with [`sleep_until_button`],
+/// the e-ink keeps the badge visible without power.
+pub fn freeze(
+    display: &mut Display,
+    delay: &mut impl DelayUs<u32>,
+    p3v3_en: &mut bsp::P3v3En,
+    buttons: &board::Buttons,
+) -> ! {
+    // Reconfiguring keeps the framebuffer, only the waveform changes
+    let _ = display.setup(delay, uc8151::LUT::Normal);
+
+    let height = 14;
+    let area = Rectangle::new(
+        Point::new(0, (uc8151::HEIGHT - height) as i32),
+        Size::new(uc8151::WIDTH, height),
+    );
+    let _ = Banner::new("Battery low - badge frozen", area).draw(display);
+    let _ = display.update();
+
+    sleep_until_button(display, p3v3_en, buttons, || {})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::Infallible;
+
+    /// A pin fixed at one level
+    struct MockPin(bool);
+
+    impl InputPin for MockPin {
+        type Error = Infallible;
+
+        fn is_high(&self) -> Result<bool, Infallible> {
+            Ok(self.0)
+        }
+
+        fn is_low(&self) -> Result<bool, Infallible> {
+            Ok(!self.0)
+        }
+    }
+
+    /// The front buttons and the active low user button, `held` pressed
+    fn buttons(held: &[Button]) -> [Input<MockPin>; 6] {
+        let high = |button| Input::active_high(button, MockPin(held.contains(&button)));
+        [
+            high(Button::A),
+            high(Button::B),
+            high(Button::C),
+            high(Button::Up),
+            high(Button::Down),
+            Input::active_low(Button::User, MockPin(!held.contains(&Button::User))),
+        ]
+    }
+
+    #[test]
+    fn millivolts_from_recorded_adc_values() {
+        // 1.24 V reads 1539 of 4095 at a 3.3 V supply
+        assert_eq!(battery_millivolts(1675, 1539), 4048);
+        // The supply sags with the battery, the reference reads higher
+        assert_eq!(battery_millivolts(1675, 1675), 3720);
+        assert_eq!(battery_millivolts(1000, 0), 0);
+        assert_eq!(battery_millivolts(u16::MAX, 1), u16::MAX);
+    }
+
+    #[test]
+    fn percentage_at_points_and_between() {
+        for curve in [LIPO, AAA_ALKALINE] {
+            for point in curve {
+                assert_eq!(percentage(point.millivolts, curve), point.percent);
+            }
+        }
+        assert_eq!(percentage(4100, LIPO), 92);
+        assert_eq!(percentage(3800, LIPO), 55);
+        assert_eq!(percentage(3400, LIPO), 2);
+        assert_eq!(percentage(4425, AAA_ALKALINE), 90);
+    }
+
+    #[test]
+    fn percentage_at_boundaries() {
+        assert_eq!(percentage(5000, LIPO), 100);
+        assert_eq!(percentage(4201, LIPO), 100);
+        assert_eq!(percentage(3299, LIPO), 0);
+        assert_eq!(percentage(0, LIPO), 0);
+        assert_eq!(percentage(3700, &[]), 0);
+        assert_eq!(percentage(3700, &[point(3600, 50)]), 50);
+    }
+
+    #[test]
+    fn percentage_of_unordered_curve_does_not_panic() {
+        let rising = [point(3300, 0), point(4200, 100)];
+        let flat = [point(4000, 100), point(4000, 50), point(3000, 0)];
+        let increasing_percent = [point(4200, 10), point(3800, 90), point(3300, 0)];
+        for millivolts in (3000..4500).step_by(50) {
+            percentage(millivolts, &rising);
+            percentage(millivolts, &flat);
+            assert!(percentage(millivolts, &increasing_percent) <= 90);
+        }
+    }
+
+    #[test]
+    fn wake_reason_from_held_button() {
+        for button in [
+            Button::A,
+            Button::B,
+            Button::C,
+            Button::Up,
+            Button::Down,
+            Button::User,
+        ] {
+            assert_eq!(
+                wake_reason(&buttons(&[button])),
+                Ok(WakeReason::Button(button))
+            );
+        }
+    }
+
+    #[test]
+    fn wake_reason_without_button() {
+        assert_eq!(wake_reason(&buttons(&[])), Ok(WakeReason::Other));
+        assert_eq!(wake_reason::<MockPin>(&[]), Ok(WakeReason::Other));
+    }
+
+    #[test]
+    fn wake_reason_first_input_wins() {
+        assert_eq!(
+            wake_reason(&buttons(&[Button::Down, Button::B])),
+            Ok(WakeReason::Button(Button::B))
+        );
+    }
+
+    /// Feed `readings` as `(millivolts, usb_powered, now_ms)`, returns the
+    /// times the guard froze
+    fn freezes(guard: &mut LowBatteryGuard, readings: &[(u16, bool, u32)]) -> Vec<u32> {
+        readings
+            .iter()
+            .filter(|&&(millivolts, usb, now)| {
+                guard.update(millivolts, usb, now) == GuardAction::Freeze
+            })
+            .map(|&(_, _, now)| now)
+            .collect()
+    }
+
+    #[test]
+    fn guard_hysteresis() {
+        let mut guard = LowBatteryGuard::new(GuardConfig::default(), 0);
+        assert_eq!(guard.update(3390, true, 1000), GuardAction::Run);
+        assert!(guard.is_low());
+        // Recovering less than the hysteresis keeps it low
+        guard.update(3500, true, 2000);
+        assert!(guard.is_low());
+        guard.update(3549, true, 3000);
+        assert!(guard.is_low());
+        guard.update(3550, true, 4000);
+        assert!(!guard.is_low());
+        // Dropping into the hysteresis band doesn't make it low again
+        guard.update(3400, true, 5000);
+        assert!(!guard.is_low());
+        guard.update(3399, true, 6000);
+        assert!(guard.is_low());
+    }
+
+    #[test]
+    fn guard_waits_for_min_on_time() {
+        let mut guard = LowBatteryGuard::new(GuardConfig::default(), 500);
+        let readings = [
+            (3300, false, 1000),
+            (3300, false, 10_499),
+            (3300, false, 10_500),
+        ];
+        assert_eq!(freezes(&mut guard, &readings), [10_500]);
+    }
+
+    #[test]
+    fn guard_freezes_once() {
+        let mut guard = LowBatteryGuard::new(GuardConfig::default(), 0);
+        let readings = [
+            (3300, false, 20_000),
+            (3300, false, 21_000),
+            (3600, false, 22_000),
+            (3300, false, 23_000),
+        ];
+        assert_eq!(freezes(&mut guard, &readings), [20_000]);
+        assert!(guard.is_frozen());
+    }
+
+    #[test]
+    fn guard_never_freezes_on_usb() {
+        let mut guard = LowBatteryGuard::new(GuardConfig::default(), 0);
+        let readings = [
+            (3300, true, 20_000),
+            (3300, true, 30_000),
+            (3300, false, 40_000),
+        ];
+        assert_eq!(freezes(&mut guard, &readings), [40_000]);
+    }
+
+    #[test]
+    fn guard_on_time_across_wrap() {
+        let start = u32::MAX - 1000;
+        let mut guard = LowBatteryGuard::new(GuardConfig::default(), start);
+        let readings = [
+            (3300, false, start + 500),
+            (3300, false, 8_000),
+            (3300, false, 9_000),
+        ];
+        assert_eq!(freezes(&mut guard, &readings), [9_000]);
+    }
+}
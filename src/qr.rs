@@ -0,0 +1,1130 @@
+//! QR codes generated on the badge
+//!
+//! A small encoder for versions 1 to 10 (21x21 to 57x57 modules) in byte and
+//! alphanumeric mode, enough for URLs and contact cards. It needs no
+//! allocator, a [`QrCode`] is about half a kilobyte.
+//!
+//! ```ignore
+//! let qr = QrCode::encode(b"https://kouvostotelecom.fi", EcLevel::M)?
+//!     .with_module_size(3)
+//!     .align(&area, AnchorPoint::Center);
+//! qr.draw(&mut display)?;
+//! ```
+
+use embedded_graphics::{
+    geometry::AnchorPoint, pixelcolor::BinaryColor, prelude::*, primitives::Rectangle,
+};
+
+use crate::graphics_extensions::Align;
+
+/// Largest supported version
+pub const MAX_VERSION: u8 = 10;
+
+/// Modules on a side of the largest supported version
+const MAX_SIZE: usize = 17 + 4 * MAX_VERSION as usize;
+
+/// Codewords in the largest supported version
+const MAX_CODEWORDS: usize = 346;
+
+/// Largest number of error correction codewords in a block
+const MAX_BLOCK_ECC: usize = 30;
+
+/// Largest number of blocks
+const MAX_BLOCKS: usize = 8;
+
+/// Characters of alphanumeric mode, the index is the value of a character
+const ALPHANUMERIC: &[u8; 45] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ $%*+-./:";
+
+/// Error correction codewords per block by level and version
+const ECC_PER_BLOCK: [[u8; MAX_VERSION as usize]; 4] = [
+    [7, 10, 15, 20, 26, 18, 20, 24, 30, 18],
+    [10, 16, 26, 18, 24, 16, 18, 22, 22, 26],
+    [13, 22, 18, 26, 18, 24, 18, 22, 20, 24],
+    [17, 28, 22, 16, 22, 28, 26, 26, 24, 28],
+];
+
+/// Error correction blocks by level and version
+const BLOCKS: [[u8; MAX_VERSION as usize]; 4] = [
+    [1, 1, 1, 1, 1, 2, 2, 2, 2, 4],
+    [1, 1, 1, 2, 2, 4, 4, 4, 5, 5],
+    [1, 1, 2, 2, 4, 4, 6, 6, 8, 8],
+    [1, 1, 2, 4, 4, 4, 5, 6, 8, 8],
+];
+
+/// How much of the code can be damaged and still be read
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EcLevel {
+    /// About 7 %
+    L,
+    /// About 15 %
+    #[default]
+    M,
+    /// About 25 %
+    Q,
+    /// About 30 %
+    H,
+}
+
+impl EcLevel {
+    /// Bits identifying the level in the format information
+    fn format_bits(self) -> u32 {
+        match self {
+            EcLevel::L => 0b01,
+            EcLevel::M => 0b00,
+            EcLevel::Q => 0b11,
+            EcLevel::H => 0b10,
+        }
+    }
+}
+
+/// How the data is stored in the code
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// Digits, upper case letters and ` $%*+-./:`, 5.5 bits per character
+    Alphanumeric,
+    /// Any bytes, usually UTF-8 text, 8 bits per byte
+    Byte,
+}
+
+impl Mode {
+    /// The most compact mode that can store `data`
+    pub fn for_data(data: &[u8]) -> Self {
+        if data.iter().all(|byte| ALPHANUMERIC.contains(byte)) {
+            Mode::Alphanumeric
+        } else {
+            Mode::Byte
+        }
+    }
+
+    fn indicator(self) -> u32 {
+        match self {
+            Mode::Alphanumeric => 0b0010,
+            Mode::Byte => 0b0100,
+        }
+    }
+
+    /// Bits of the character count in `version`
+    fn count_bits(self, version: u8) -> u8 {
+        match (self, version) {
+            (Mode::Alphanumeric, 1..=9) => 9,
+            (Mode::Alphanumeric, _) => 11,
+            (Mode::Byte, 1..=9) => 8,
+            (Mode::Byte, _) => 16,
+        }
+    }
+
+    /// Bits needed to store `len` characters in `version`
+    fn bits(self, len: usize, version: u8) -> usize {
+        let data = match self {
+            Mode::Alphanumeric => 11 * (len / 2) + 6 * (len % 2),
+            Mode::Byte => 8 * len,
+        };
+        4 + self.count_bits(version) as usize + data
+    }
+}
+
+/// Why a QR code couldn't be made
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The data doesn't fit in version 10 with the error correction level
+    TooLong,
+    /// Only versions 1 to 10 are supported
+    Version,
+}
+
+/// Codewords of all blocks, data and error correction, of `version`
+fn codewords(version: u8) -> usize {
+    let v = version as usize;
+    let mut modules = (16 * v + 128) * v + 64;
+    if v >= 2 {
+        let alignment = v / 7 + 2;
+        modules -= (25 * alignment - 10) * alignment - 55;
+        if v >= 7 {
+            modules -= 36;
+        }
+    }
+    modules / 8
+}
+
+/// Data codewords that fit in `version` with `ec` error correction
+///
+/// # Panics
+///
+/// If `version` isn't between 1 and 10.
+pub fn data_capacity(version: u8, ec: EcLevel) -> usize {
+    let (level, v) = (ec as usize, version as usize - 1);
+    codewords(version) - ECC_PER_BLOCK[level][v] as usize * BLOCKS[level][v] as usize
+}
+
+/// Bits appended one after another, most significant bit first
+struct BitBuffer {
+    data: [u8; MAX_CODEWORDS],
+    len: usize,
+}
+
+impl BitBuffer {
+    fn new() -> Self {
+        Self {
+            data: [0; MAX_CODEWORDS],
+            len: 0,
+        }
+    }
+
+    /// Append the `bits` lowest bits of `value`
+    fn push(&mut self, value: u32, bits: u8) {
+        for i in (0..bits).rev() {
+            if value >> i & 1 != 0 {
+                self.data[self.len / 8] |= 0x80 >> (self.len % 8);
+            }
+            self.len += 1;
+        }
+    }
+}
+
+/// Multiplication in GF(2^8) modulo x^8 + x^4 + x^3 + x^2 + 1
+fn gf_mul(x: u8, y: u8) -> u8 {
+    let mut z: u16 = 0;
+    for i in (0..8).rev() {
+        z = (z << 1) ^ ((z >> 7) * 0x11d);
+        z ^= ((y >> i) & 1) as u16 * x as u16;
+    }
+    z as u8
+}
+
+/// Reed-Solomon error correction of `data` into `ecc`
+fn reed_solomon(data: &[u8], ecc: &mut [u8]) {
+    let degree = ecc.len();
+
+    // Coefficients of the generator polynomial, highest power first, the
+    // leading 1 left out
+    let mut divisor = [0u8; MAX_BLOCK_ECC];
+    let divisor = &mut divisor[..degree];
+    divisor[degree - 1] = 1;
+    let mut root = 1;
+    for _ in 0..degree {
+        for j in 0..degree {
+            divisor[j] = gf_mul(divisor[j], root);
+            if j + 1 < degree {
+                divisor[j] ^= divisor[j + 1];
+            }
+        }
+        root = gf_mul(root, 0x02);
+    }
+
+    ecc.fill(0);
+    for &byte in data {
+        let factor = byte ^ ecc[0];
+        ecc.copy_within(1.., 0);
+        ecc[degree - 1] = 0;
+        for (remainder, &coefficient) in ecc.iter_mut().zip(divisor.iter()) {
+            *remainder ^= gf_mul(coefficient, factor);
+        }
+    }
+}
+
+/// Rows of modules, bit `x` of row `y` is the module at `x`, `y`
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Matrix {
+    rows: [u64; MAX_SIZE],
+    size: usize,
+}
+
+impl Matrix {
+    fn new(size: usize) -> Self {
+        Self {
+            rows: [0; MAX_SIZE],
+            size,
+        }
+    }
+
+    fn get(&self, x: usize, y: usize) -> bool {
+        self.rows[y] >> x & 1 != 0
+    }
+
+    fn set(&mut self, x: usize, y: usize, dark: bool) {
+        if dark {
+            self.rows[y] |= 1 << x;
+        } else {
+            self.rows[y] &= !(1 << x);
+        }
+    }
+}
+
+/// The modules while a code is built, and which of them are function
+/// patterns that data and masks leave alone
+struct Builder {
+    modules: Matrix,
+    function: Matrix,
+    version: u8,
+}
+
+impl Builder {
+    fn new(version: u8) -> Self {
+        let size = 17 + 4 * version as usize;
+        Self {
+            modules: Matrix::new(size),
+            function: Matrix::new(size),
+            version,
+        }
+    }
+
+    fn size(&self) -> usize {
+        self.modules.size
+    }
+
+    fn set_function(&mut self, x: usize, y: usize, dark: bool) {
+        self.modules.set(x, y, dark);
+        self.function.set(x, y, true);
+    }
+
+    /// Centers of the alignment patterns on each axis
+    fn alignment_positions(&self) -> ([usize; 3], usize) {
+        let mut positions = [6; 3];
+        if self.version == 1 {
+            return (positions, 0);
+        }
+        let count = self.version as usize / 7 + 2;
+        let step = (self.version as usize * 4 + count * 2 + 1) / (count * 2 - 2) * 2;
+        for i in 1..count {
+            positions[count - i] = self.size() - 7 - (i - 1) * step;
+        }
+        (positions, count)
+    }
+
+    fn draw_function_patterns(&mut self) {
+        let size = self.size();
+        for i in 0..size {
+            self.set_function(6, i, i % 2 == 0);
+            self.set_function(i, 6, i % 2 == 0);
+        }
+
+        for (x, y) in [(3, 3), (size - 4, 3), (3, size - 4)] {
+            for dy in -4i32..=4 {
+                for dx in -4i32..=4 {
+                    let (xx, yy) = (x as i32 + dx, y as i32 + dy);
+                    if (0..size as i32).contains(&xx) && (0..size as i32).contains(&yy) {
+                        let distance = dx.abs().max(dy.abs());
+                        self.set_function(xx as usize, yy as usize, distance != 2 && distance != 4);
+                    }
+                }
+            }
+        }
+
+        let (positions, count) = self.alignment_positions();
+        for i in 0..count {
+            for j in 0..count {
+                // Not over the finder patterns
+                let last = count - 1;
+                if (i, j) == (0, 0) || (i, j) == (0, last) || (i, j) == (last, 0) {
+                    continue;
+                }
+                for dy in -2i32..=2 {
+                    for dx in -2i32..=2 {
+                        let x = (positions[i] as i32 + dx) as usize;
+                        let y = (positions[j] as i32 + dy) as usize;
+                        self.set_function(x, y, dx.abs().max(dy.abs()) != 1);
+                    }
+                }
+            }
+        }
+
+        // Reserve the format areas, they are drawn once the mask is known
+        self.draw_format(EcLevel::L, 0);
+        self.draw_version();
+    }
+
+    fn draw_format(&mut self, ec: EcLevel, mask: u8) {
+        let data = ec.format_bits() << 3 | mask as u32;
+        let mut remainder = data;
+        for _ in 0..10 {
+            remainder = (remainder << 1) ^ ((remainder >> 9) * 0x537);
+        }
+        let bits = (data << 10 | remainder) ^ 0x5412;
+        let bit = |i: usize| bits >> i & 1 != 0;
+        let size = self.size();
+
+        // Around the top left finder pattern
+        for i in 0..6 {
+            self.set_function(8, i, bit(i));
+        }
+        self.set_function(8, 7, bit(6));
+        self.set_function(8, 8, bit(7));
+        self.set_function(7, 8, bit(8));
+        for i in 9..15 {
+            self.set_function(14 - i, 8, bit(i));
+        }
+
+        // Split between the other two
+        for i in 0..8 {
+            self.set_function(size - 1 - i, 8, bit(i));
+        }
+        for i in 8..15 {
+            self.set_function(8, size - 15 + i, bit(i));
+        }
+        self.set_function(8, size - 8, true);
+    }
+
+    fn draw_version(&mut self) {
+        if self.version < 7 {
+            return;
+        }
+        let version = self.version as u32;
+        let mut remainder = version;
+        for _ in 0..12 {
+            remainder = (remainder << 1) ^ ((remainder >> 11) * 0x1f25);
+        }
+        let bits = version << 12 | remainder;
+        let size = self.size();
+        for i in 0..18 {
+            let dark = bits >> i & 1 != 0;
+            let (a, b) = (size - 11 + i % 3, i / 3);
+            self.set_function(a, b, dark);
+            self.set_function(b, a, dark);
+        }
+    }
+
+    /// Place the codewords in the zigzag from the bottom right corner
+    fn draw_codewords(&mut self, codewords: &[u8]) {
+        let size = self.size();
+        let mut i = 0;
+        let mut right = size - 1;
+        loop {
+            // Skip the vertical timing pattern
+            if right == 6 {
+                right = 5;
+            }
+            let upward = (right + 1) & 2 == 0;
+            for vertical in 0..size {
+                let y = if upward {
+                    size - 1 - vertical
+                } else {
+                    vertical
+                };
+                for x in [right, right - 1] {
+                    if !self.function.get(x, y) && i < codewords.len() * 8 {
+                        let dark = codewords[i / 8] >> (7 - i % 8) & 1 != 0;
+                        self.modules.set(x, y, dark);
+                        i += 1;
+                    }
+                }
+            }
+            if right < 3 {
+                break;
+            }
+            right -= 2;
+        }
+    }
+
+    /// Flip the data modules where the mask pattern is set, applying it
+    /// again undoes it
+    fn apply_mask(&mut self, mask: u8) {
+        let size = self.size();
+        for y in 0..size {
+            for x in 0..size {
+                let flip = match mask {
+                    0 => (x + y) % 2 == 0,
+                    1 => y % 2 == 0,
+                    2 => x % 3 == 0,
+                    3 => (x + y) % 3 == 0,
+                    4 => (x / 3 + y / 2) % 2 == 0,
+                    5 => x * y % 2 + x * y % 3 == 0,
+                    6 => (x * y % 2 + x * y % 3) % 2 == 0,
+                    _ => ((x + y) % 2 + x * y % 3) % 2 == 0,
+                };
+                if flip && !self.function.get(x, y) {
+                    let dark = self.modules.get(x, y);
+                    self.modules.set(x, y, !dark);
+                }
+            }
+        }
+    }
+
+    /// How hard the code is to read, masks are chosen to minimise this
+    fn penalty(&self) -> u32 {
+        let size = self.size();
+        let mut penalty = 0;
+
+        for transposed in [false, true] {
+            for a in 0..size {
+                let mut history = FinderHistory::new(size);
+                let mut run_dark = false;
+                let mut run = 0;
+                for b in 0..size {
+                    let dark = if transposed {
+                        self.modules.get(a, b)
+                    } else {
+                        self.modules.get(b, a)
+                    };
+                    if dark == run_dark {
+                        run += 1;
+                        if run == 5 {
+                            penalty += 3;
+                        } else if run > 5 {
+                            penalty += 1;
+                        }
+                    } else {
+                        history.add(run);
+                        if !run_dark {
+                            penalty += history.count_patterns() * 40;
+                        }
+                        run_dark = dark;
+                        run = 1;
+                    }
+                }
+                penalty += history.terminate(run_dark, run) * 40;
+            }
+        }
+
+        for y in 0..size - 1 {
+            for x in 0..size - 1 {
+                let dark = self.modules.get(x, y);
+                if dark == self.modules.get(x + 1, y)
+                    && dark == self.modules.get(x, y + 1)
+                    && dark == self.modules.get(x + 1, y + 1)
+                {
+                    penalty += 3;
+                }
+            }
+        }
+
+        let dark: u32 = self.modules.rows[..size]
+            .iter()
+            .map(|row| row.count_ones())
+            .sum();
+        let total = (size * size) as u32;
+        // Every 5 % away from half dark
+        let k = (dark * 20)
+            .abs_diff(total * 10)
+            .div_ceil(total)
+            .saturating_sub(1);
+        penalty + k * 10
+    }
+}
+
+/// Lengths of the latest runs of light and dark modules in a row or column,
+/// for finding patterns that look like finder patterns
+struct FinderHistory {
+    runs: [usize; 7],
+    size: usize,
+}
+
+impl FinderHistory {
+    fn new(size: usize) -> Self {
+        Self { runs: [0; 7], size }
+    }
+
+    fn add(&mut self, mut run: usize) {
+        // The light border counts as part of the first run
+        if self.runs[0] == 0 {
+            run += self.size;
+        }
+        self.runs.copy_within(0..6, 1);
+        self.runs[0] = run;
+    }
+
+    /// 1:1:3:1:1 dark and light runs with four light modules on either side
+    fn count_patterns(&self) -> u32 {
+        let r = &self.runs;
+        let n = r[1];
+        let core = n > 0 && r[2] == n && r[3] == n * 3 && r[4] == n && r[5] == n;
+        (core && r[0] >= n * 4 && r[6] >= n) as u32 + (core && r[6] >= n * 4 && r[0] >= n) as u32
+    }
+
+    fn terminate(&mut self, run_dark: bool, mut run: usize) -> u32 {
+        if run_dark {
+            self.add(run);
+            run = 0;
+        }
+        // The light border at the end
+        run += self.size;
+        self.add(run);
+        self.count_patterns()
+    }
+}
+
+/// An encoded QR code, drawn as a square of dark and light modules
+///
+/// By default every module is one pixel, dark modules are black and the code
+/// is surrounded by the four modules wide light quiet zone scanners need. The
+/// bounding box includes the quiet zone.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct QrCode {
+    modules: Matrix,
+    version: u8,
+    ec: EcLevel,
+    mask: u8,
+    top_left: Point,
+    module_size: u32,
+    quiet_zone: u32,
+    inverted: bool,
+}
+
+impl QrCode {
+    /// Encode `data` in the smallest version it fits
+    pub fn encode(data: &[u8], ec: EcLevel) -> Result<Self, Error> {
+        let mode = Mode::for_data(data);
+        let version = (1..=MAX_VERSION)
+            .find(|&version| mode.bits(data.len(), version) <= data_capacity(version, ec) * 8)
+            .ok_or(Error::TooLong)?;
+        Self::encode_version(data, ec, version)
+    }
+
+    /// Encode `data` in a fixed `version`, for codes that shouldn't change
+    /// size with their contents
+    pub fn encode_version(data: &[u8], ec: EcLevel, version: u8) -> Result<Self, Error> {
+        if !(1..=MAX_VERSION).contains(&version) {
+            return Err(Error::Version);
+        }
+        let mode = Mode::for_data(data);
+        let capacity = data_capacity(version, ec);
+        if mode.bits(data.len(), version) > capacity * 8 {
+            return Err(Error::TooLong);
+        }
+
+        let bits = data_codewords(data, mode, version, capacity);
+        let mut codewords = [0; MAX_CODEWORDS];
+        let codewords = interleave(&bits[..capacity], version, ec, &mut codewords);
+
+        let mut builder = Builder::new(version);
+        builder.draw_function_patterns();
+        builder.draw_codewords(codewords);
+
+        let mut best = (u32::MAX, 0);
+        for mask in 0..8 {
+            builder.apply_mask(mask);
+            builder.draw_format(ec, mask);
+            let penalty = builder.penalty();
+            if penalty < best.0 {
+                best = (penalty, mask);
+            }
+            builder.apply_mask(mask);
+        }
+        let mask = best.1;
+        builder.apply_mask(mask);
+        builder.draw_format(ec, mask);
+
+        Ok(Self {
+            modules: builder.modules,
+            version,
+            ec,
+            mask,
+            top_left: Point::zero(),
+            module_size: 1,
+            quiet_zone: 4,
+            inverted: false,
+        })
+    }
+
+    /// Version of the code, 1 to 10
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    pub fn ec_level(&self) -> EcLevel {
+        self.ec
+    }
+
+    /// The mask pattern chosen to make the code easy to read
+    pub fn mask(&self) -> u8 {
+        self.mask
+    }
+
+    /// Modules on a side, without the quiet zone
+    pub fn modules(&self) -> u32 {
+        self.modules.size as u32
+    }
+
+    /// Returns true if the module at `x`, `y` is dark, modules outside the
+    /// code are light
+    pub fn module(&self, x: i32, y: i32) -> bool {
+        let size = self.modules.size as i32;
+        (0..size).contains(&x) && (0..size).contains(&y) && self.modules.get(x as usize, y as usize)
+    }
+
+    /// Pixels per module
+    #[must_use]
+    pub fn with_module_size(self, module_size: u32) -> Self {
+        Self {
+            module_size: module_size.max(1),
+            ..self
+        }
+    }
+
+    /// Light modules around the code, scanners expect 4 but often read
+    /// codes with less
+    #[must_use]
+    pub fn with_quiet_zone(self, quiet_zone: u32) -> Self {
+        Self { quiet_zone, ..self }
+    }
+
+    /// Draw dark modules white and light ones black, for codes on black
+    #[must_use]
+    pub fn with_inverted(self, inverted: bool) -> Self {
+        Self { inverted, ..self }
+    }
+
+    /// Pixels on a side, including the quiet zone
+    pub fn pixels(&self) -> u32 {
+        (self.modules() + 2 * self.quiet_zone) * self.module_size
+    }
+
+    /// The largest module size that fits in `area`, centered in it
+    ///
+    /// Codes too large for `area` even with one pixel modules stay at one
+    /// pixel.
+    #[must_use]
+    pub fn fit_into(self, area: &Rectangle) -> Self {
+        let side = area.size.width.min(area.size.height);
+        let module_size = side / (self.modules() + 2 * self.quiet_zone);
+        self.with_module_size(module_size)
+            .align(area, AnchorPoint::Center)
+    }
+}
+
+/// `data` in `mode` padded to the `capacity` data codewords of `version`
+fn data_codewords(data: &[u8], mode: Mode, version: u8, capacity: usize) -> [u8; MAX_CODEWORDS] {
+    let mut bits = BitBuffer::new();
+    bits.push(mode.indicator(), 4);
+    bits.push(data.len() as u32, mode.count_bits(version));
+    match mode {
+        Mode::Alphanumeric => {
+            let value = |byte: &u8| ALPHANUMERIC.iter().position(|c| c == byte).unwrap_or(0) as u32;
+            for pair in data.chunks(2) {
+                match pair {
+                    [a, b] => bits.push(value(a) * 45 + value(b), 11),
+                    [a] => bits.push(value(a), 6),
+                    _ => {}
+                }
+            }
+        }
+        Mode::Byte => {
+            for &byte in data {
+                bits.push(byte as u32, 8);
+            }
+        }
+    }
+
+    // Terminator, then whole bytes of alternating padding
+    let terminator = (capacity * 8 - bits.len).min(4);
+    bits.push(0, terminator as u8);
+    bits.push(0, ((8 - bits.len % 8) % 8) as u8);
+    for pad in [0xec, 0x11].iter().cycle().take(capacity - bits.len / 8) {
+        bits.push(*pad, 8);
+    }
+
+    bits.data
+}
+
+/// Split the data codewords into blocks, add error correction to each and
+/// interleave them
+fn interleave<'a>(data: &[u8], version: u8, ec: EcLevel, out: &'a mut [u8]) -> &'a [u8] {
+    let (level, v) = (ec as usize, version as usize - 1);
+    let blocks = BLOCKS[level][v] as usize;
+    let block_ecc = ECC_PER_BLOCK[level][v] as usize;
+    let total = codewords(version);
+    let short_blocks = blocks - total % blocks;
+    let short_data = total / blocks - block_ecc;
+
+    // Start and length of the data of each block, long blocks come last
+    let block = |i: usize| {
+        let start = i * short_data + i.saturating_sub(short_blocks);
+        (start, short_data + (i >= short_blocks) as usize)
+    };
+
+    let mut ecc = [[0; MAX_BLOCK_ECC]; MAX_BLOCKS];
+    for (i, ecc) in ecc.iter_mut().enumerate().take(blocks) {
+        let (start, len) = block(i);
+        reed_solomon(&data[start..start + len], &mut ecc[..block_ecc]);
+    }
+
+    let mut n = 0;
+    for i in 0..=short_data {
+        for j in 0..blocks {
+            let (start, len) = block(j);
+            if i < len {
+                out[n] = data[start + i];
+                n += 1;
+            }
+        }
+    }
+    for i in 0..block_ecc {
+        for ecc in &ecc[..blocks] {
+            out[n] = ecc[i];
+            n += 1;
+        }
+    }
+    &out[..n]
+}
+
+/// Draws the code as text, `#` is a dark module
+impl core::fmt::Debug for QrCode {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        writeln!(
+            f,
+            "QrCode {}-{:?} mask {}",
+            self.version, self.ec, self.mask
+        )?;
+        for y in 0..self.modules() as i32 {
+            for x in 0..self.modules() as i32 {
+                f.write_str(if self.module(x, y) { "##" } else { "  " })?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Dimensions for QrCode {
+    fn bounding_box(&self) -> Rectangle {
+        Rectangle::new(self.top_left, Size::new_equal(self.pixels()))
+    }
+}
+
+impl Transform for QrCode {
+    fn translate(&self, by: Point) -> Self {
+        Self {
+            top_left: self.top_left + by,
+            ..*self
+        }
+    }
+
+    fn translate_mut(&mut self, by: Point) -> &mut Self {
+        self.top_left += by;
+        self
+    }
+}
+
+impl Drawable for QrCode {
+    type Color = BinaryColor;
+    type Output = ();
+
+    fn draw<D>(&self, target: &mut D) -> Result<Self::Output, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let area = self.bounding_box();
+        let module_size = self.module_size as i32;
+        let quiet_zone = self.quiet_zone as i32;
+        let colors = area.points().map(|point| {
+            let offset = point - self.top_left;
+            let x = offset.x / module_size - quiet_zone;
+            let y = offset.y / module_size - quiet_zone;
+            // Dark modules are black
+            BinaryColor::from(self.module(x, y) == self.inverted)
+        });
+        target.fill_contiguous(&area, colors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_graphics::mock_display::MockDisplay;
+
+    const MECARD: &[u8] = b"MECARD:N:Juva,Heikki;NICKNAME:Zokol;URL:https://zokol.fi;;";
+    const VCARD: &[u8] = b"BEGIN:VCARD\nVERSION:3.0\nFN:Heikki Juva\nNICKNAME:Zokol\n\
+        ORG:Kouvosto Telecom\nURL:https://kouvostotelecom.fi\nEND:VCARD";
+
+    // Reference encodings made with the qrcodegen crate, `#` is a dark module
+
+    const HELLO_WORLD_1M: &[&str] = &[
+        "#######...#.#.#######",
+        "#.....#.###...#.....#",
+        "#.###.#...#.#.#.###.#",
+        "#.###.#...#.#.#.###.#",
+        "#.###.#.#.###.#.###.#",
+        "#.....#..###..#.....#",
+        "#######.#.#.#.#######",
+        ".....................",
+        "#.#.#.#..#..#...#..#.",
+        ".####...#..#....#...#",
+        "...#######.#..#.##...",
+        "####.#.##..###.#.###.",
+        ".#..####.#.#..###.#.#",
+        "........#.#...#...#.#",
+        "#######.....#..#.##..",
+        "#.....#..##...##.#...",
+        "#.###.#.##..#.#######",
+        "#.###.#...##.#.#...#.",
+        "#.###.#.####.###.#..#",
+        "#.....#....###...#.##",
+        "#######.##.#.###....#",
+    ];
+
+    const URL_2L: &[&str] = &[
+        "#######.#....####.#######",
+        "#.....#.#..###....#.....#",
+        "#.###.#.##..#.##..#.###.#",
+        "#.###.#.###.####..#.###.#",
+        "#.###.#..##.#.###.#.###.#",
+        "#.....#.###..#..#.#.....#",
+        "#######.#.#.#.#.#.#######",
+        "..........##.............",
+        "##..###...#.#..#...#.####",
+        "##..##..#....####...##.#.",
+        ".#....#..##...###.#####..",
+        ".##.##.#.#..#.##..#.#.##.",
+        ".##...#.#..##...####.####",
+        "######....#.######..#..#.",
+        "..#.#.#....###.#...####..",
+        "....#....#.#..#.#..##.##.",
+        "##.####.##.##...#######..",
+        "........##.#..###...#....",
+        "#######..###.##.#.#.#....",
+        "#.....#.#.#.#.#.#...#####",
+        "#.###.#.#.#.#...#######..",
+        "#.###.#..###..######..###",
+        "#.###.#..###..#...#..#.#.",
+        "#.....#.#...#.##...#####.",
+        "#######.#.###.##......###",
+    ];
+
+    const NAME_3Q: &[&str] = &[
+        "#######...##..#....##.#######",
+        "#.....#.##..####.###..#.....#",
+        "#.###.#..##.#.#....#..#.###.#",
+        "#.###.#.##......##..#.#.###.#",
+        "#.###.#.#..#.#....##..#.###.#",
+        "#.....#..##.#.###...#.#.....#",
+        "#######.#.#.#.#.#.#.#.#######",
+        "........##.###.####..........",
+        ".#.####.###...#.#....##.##.#.",
+        ".##.......#.##.#.##...##.##..",
+        "....###.##..##.#...#####.....",
+        ".###.#.#..#.###..#.####..#..#",
+        "#.##.##.#....#.##...####.#.#.",
+        ".##.....##.#......#...###.###",
+        "###...###.#..#..#.##.###....#",
+        "...#...#.########..####..####",
+        "#.#...###.##.....###.#..#....",
+        "#.......##....#......####...#",
+        "##.#.####..#.##.##.#.##.#.#.#",
+        "##.###..##...####.###...#####",
+        "####..#####..##.##########.##",
+        "........#...#.#..####...#..#.",
+        "#######..##...##....#.#.###..",
+        "#.....#.#..#.#..#.###...##...",
+        "#.###.#.##.....#.#.#######.#.",
+        "#.###.#.#######...#........#.",
+        "#.###.#..####.##.#...#.######",
+        "#.....#.###.#...#...##.#.#..#",
+        "#######..#.#..##..##.###..#..",
+    ];
+
+    const MECARD_7H: &[&str] = &[
+        "#######.#####.#..#.##.##...#####....#.#######",
+        "#.....#.##.......#...#.###..##.#...#..#.....#",
+        "#.###.#.####.#.#.##....#####..##.#.#..#.###.#",
+        "#.###.#..##...##....##..##.#.#...#.##.#.###.#",
+        "#.###.#..#..#...##..######..####.####.#.###.#",
+        "#.....#.#..#...######...#..##.#..#....#.....#",
+        "#######.#.#.#.#.#.#.#.#.#.#.#.#.#.#.#.#######",
+        "........#.###.##.##.#...##.####.###..........",
+        "..###.#.#.#.#...###.########.###.###.###..###",
+        "#...##...####.###..####...##.##.#...##.###.##",
+        ".##...#.#..##...#.##....#######.##.#..#..#...",
+        ".....#.##.##.#######...#.##.##.#.##.#...###..",
+        "..##..#.......#.##..##.#..##.#.#.....#.....##",
+        ".....#.##.##..###..####...########.###.#.#...",
+        "..######.#..#...#......####.#...#.##.#...###.",
+        "..#.##.###.##.##.#.##.#..#..##...###..#.###.#",
+        "...#..##...####....###..##..#..#..#..#.#..###",
+        "##.#.#.#..###.#..###.###.#####.#...###...#..#",
+        "......##.#...##.....##.##.....#.####..#...#..",
+        "###.##.#...##..#....##.##..##.#####...######.",
+        "#.#########.##.#..#.#####..#.###.########.#..",
+        "#.#.#...#.###.###.###...##..#.#.##.##...#.#..",
+        ".#.##.#.##...##..#.##.#.#..#.##.###.#.#.####.",
+        ".####...#..###.#...##...#.#...#...#.#...###.#",
+        "#############.##..#.######..#..####.#####.#.#",
+        ".##.#..##..#####...#.####.#.###.#.#...#.....#",
+        "##....#.##.#.#.#.#...###..#..#####...#.....#.",
+        "#......#.#.#..##.##.#.#..##.#..##.####...####",
+        ".#.#..##..####.#.#####.#.###..##.##..#.#....#",
+        ".#...#.#..##....##.#.#..#..#..####.##....#...",
+        "#.##..######..#.....##.#...######.##.#..#.##.",
+        "..#....#......##..#.###..#.#.#######.###..#.#",
+        "#.....##..##.#..##.#.###..###.#...#.#.###....",
+        "#.##.#.###..##.#.####.#.###...#.....##...##..",
+        "....#.#...#.#.#..#.##...#.#..#####..#..#####.",
+        ".####..###.#..#.#...#.........#...##.##.#####",
+        "#..##.#.##....#.....#####....#.#..#.#####.#.#",
+        "........##.##.###.###...###.###.#...#...#...#",
+        "#######..##.##.##..##.#.##...########.#.#.##.",
+        "#.....#..####..##..##...######....#.#...###.#",
+        "#.###.#.##...#..#.#.######.....#..#######..#.",
+        "#.###.#.###.#.#.#..###.#.#.###..##......##.##",
+        "#.###.#.#.#.##...#.####.#.....#.#####.#..##..",
+        "#.....#....##....#.####.##.#.###.#.#.#.#.##..",
+        "#######....##...#.#...#..##.###........##.##.",
+    ];
+
+    const VCARD_10M: &[&str] = &[
+        "#######..#.#..#.#..#.#.#...#.####.........###.##..#######",
+        "#.....#..#...##..##..#...##.#...##.###.#.#.#.#.#..#.....#",
+        "#.###.#.#.#.###.##.##..#....##....#...##..##..##..#.###.#",
+        "#.###.#.##..#..##...##.#.#...########.#.##.#...#..#.###.#",
+        "#.###.#.####.#...##.#..##.#####.#.#.#..#####.#.#..#.###.#",
+        "#.....#.###.####.#.#..#.#.#...####.####.#..#.##...#.....#",
+        "#######.#.#.#.#.#.#.#.#.#.#.#.#.#.#.#.#.#.#.#.#.#.#######",
+        "........##.#..##..#####.#.#...##.#..####..##.#.#.........",
+        "#.#####...#.#.##.#.##.#.#######....#####.##....##.#####..",
+        ".####..#.##..###.#...#.######.##.#...#....##....#....#...",
+        "##.##.#....##.##.###.#....###.#....########....###.#.#.#.",
+        "###....#.#..####..##...#.###..#...#.#.####....#.###.#.###",
+        "##....###...#.###.####.##...########..#....##.#...#.....#",
+        ".....#.#####...#.###....#..##.#.##...#.###..#..####...#..",
+        "#####.#....#..#.##...#.###..#####.##......#####.#....###.",
+        "####....####..#.###.....#....#..#..##...#.#.#.#####.#.###",
+        "#...#.###.....#.##.##.#.##.#...#....##.#...#..#...##.##..",
+        "#.#..#..#.##.#..#.######.#...#.##..##..#####...##....#.#.",
+        ".###.##..#...####..####..#.#..#####.##..####..##.##......",
+        ".##.....#####.#..#..#.##.#.#..#.##.#####......#...##.#.#.",
+        ".##.#.#.##........#....####.####..###.#.#..#.##.#.#...#..",
+        ".#.#.#.####.#....####.#.#.###.##.....######.#.####....#..",
+        "##.##.##.###.####.##.#.##.#.###.##......#.#..#.#.#.##..##",
+        "#.#.##..#.##.##.###.#...##.#...#.#.##....#...#..##...##.#",
+        ".######.#.....####..####..#..#.....#####.#.#...##.##..###",
+        "#...#..#......#.######.#########.#..#....##.##..##...#..#",
+        ".##.#######..#.####.#...#######.....#.##...#...######....",
+        "#.#.#...##...###...#.####.#...#...#.#..###...#..#...###.#",
+        "..#.#.#.##..##..##..##..###.#.#########....####.#.#.#.#.#",
+        "#.###...#.###.##.##.#######...#.#.#..#.###..#...#...##...",
+        ".#.#######..##..####.#....########...##...####..######.#.",
+        "..##.#.##.#####.##.###.....#.....#####..#.##..#...##.####",
+        "..#.####......#....#....#..#.....##.##...##..#.#####.....",
+        ".#..#..#.####.#...#..###..#........###....##.#.#..#.##.##",
+        "#..####..###....#.#..#.....#.###.#..#....##..#.#.####..#.",
+        ".#..##.#.##.######.##..#.###.##.#.#.####..#.#..#....##...",
+        "##..#####..#...###.#..#####..#.#.#.#..#..#.#.#......#.#..",
+        "#.#.#....#.##..#...####.##.#...##....##.###....###....#..",
+        "..#..##.####......####.##.#..#...###....#.#####.#...#####",
+        ".##.##.####.###...##.##.#..#..#.##.#.......#.#.#.#..#.#.#",
+        "#...####..###.#.....##..####..##....##.#.#.#......##.####",
+        ".##.#...#......######..#....####....#....#####.#..#..###.",
+        "##.#..#...#...#.#.##.##..####.##...###.#...##..###...##..",
+        "...##...#.##.#####.###.#.#..###...#..###.#.#..#..#..####.",
+        "#.#.#.#.#.....#####.#.##.....#.####.#.#.####.##.#.##..#..",
+        ".....#...#.##...##.#.####..#....#.##...##.#.#.##..#...###",
+        "#.#..##..#..##...#..#...###..#.###.#.##.#...##..##..###..",
+        "#####..##.###.#.#####.###.##..##.#..##.##.##......#..####",
+        "......#...#.##.#.##...#.#.#####.....#....##..#.######.#..",
+        "........#..#.###.##..##.#.#...##.#..##.##.##.#..#...#....",
+        "#######...#....#.......####.#.#.....#.#.###..#.##.#.##.#.",
+        "#.....#.#.##.#...#..#...#.#...#..##.###.##...####...####.",
+        "#.###.#.###...##.#.##.###.######.#.#..#....##########...#",
+        "#.###.#.##..#.#..###.#..##......#....#..##..#..#####..#..",
+        "#.###.#.#..##.#..#.#.#.####..##..###....#.####..###.###..",
+        "#.....#..##.#######.#...#.#...#.#..##..#....#..###.##.#..",
+        "#######.####.....##.....###....#....##...###....###.####.",
+    ];
+
+    /// Data, level, version, the mask qrcodegen chose and its modules
+    type Reference = (&'static [u8], EcLevel, u8, u8, &'static [&'static str]);
+
+    const REFERENCES: [Reference; 5] = [
+        (b"HELLO WORLD", EcLevel::M, 1, 0, HELLO_WORLD_1M),
+        (b"https://kouvostotelecom.fi", EcLevel::L, 2, 4, URL_2L),
+        (b"Heikki 'Zokol' Juva", EcLevel::Q, 3, 6, NAME_3Q),
+        (MECARD, EcLevel::H, 7, 2, MECARD_7H),
+        (VCARD, EcLevel::M, 10, 2, VCARD_10M),
+    ];
+
+    fn rows(qr: &QrCode) -> Vec<String> {
+        let size = qr.modules() as i32;
+        (0..size)
+            .map(|y| {
+                (0..size)
+                    .map(|x| if qr.module(x, y) { '#' } else { '.' })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn matches_reference_encodings() {
+        for (data, ec, version, mask, expected) in REFERENCES {
+            let qr = QrCode::encode_version(data, ec, version).unwrap();
+            assert_eq!(
+                (qr.version(), qr.ec_level(), qr.mask()),
+                (version, ec, mask)
+            );
+            assert_eq!(rows(&qr), expected, "version {version}-{ec:?}");
+        }
+    }
+
+    #[test]
+    fn picks_smallest_version() {
+        let smallest = [1, 2, 2, 6, 7];
+        for ((data, ec, ..), version) in REFERENCES.into_iter().zip(smallest) {
+            assert_eq!(QrCode::encode(data, ec).unwrap().version(), version);
+        }
+    }
+
+    #[test]
+    fn modes() {
+        assert_eq!(
+            Mode::for_data(b"HELLO WORLD $%*+-./:09"),
+            Mode::Alphanumeric
+        );
+        assert_eq!(Mode::for_data(b"Hello"), Mode::Byte);
+        assert_eq!(Mode::Alphanumeric.bits(11, 1), 4 + 9 + 5 * 11 + 6);
+        assert_eq!(Mode::Byte.bits(3, 10), 4 + 16 + 24);
+    }
+
+    #[test]
+    fn capacity_limits() {
+        assert_eq!(data_capacity(1, EcLevel::L), 19);
+        assert_eq!(data_capacity(10, EcLevel::H), 122);
+
+        // Version 1-H holds 7 bytes
+        assert!(QrCode::encode_version(b"hello, ", EcLevel::H, 1).is_ok());
+        assert_eq!(
+            QrCode::encode_version(b"hello, w", EcLevel::H, 1),
+            Err(Error::TooLong)
+        );
+        assert_eq!(
+            QrCode::encode_version(b"hello", EcLevel::L, 11),
+            Err(Error::Version)
+        );
+        assert_eq!(
+            QrCode::encode(&[b'a'; 272], EcLevel::L),
+            Err(Error::TooLong)
+        );
+        assert_eq!(
+            QrCode::encode(&[b'a'; 271], EcLevel::L).unwrap().version(),
+            10
+        );
+    }
+
+    #[test]
+    fn draws_modules_with_quiet_zone() {
+        let qr = QrCode::encode(b"HELLO WORLD", EcLevel::M)
+            .unwrap()
+            .with_quiet_zone(1)
+            .with_module_size(2)
+            .translate(Point::new(1, 1));
+        assert_eq!(
+            qr.bounding_box(),
+            Rectangle::new(Point::new(1, 1), Size::new_equal(46))
+        );
+
+        let mut display = MockDisplay::new();
+        qr.draw(&mut display).unwrap();
+        for (y, row) in HELLO_WORLD_1M.iter().enumerate() {
+            for (x, module) in row.chars().enumerate() {
+                let point = Point::new(3 + 2 * x as i32, 3 + 2 * y as i32);
+                let color = BinaryColor::from(module == '.');
+                assert_eq!(display.get_pixel(point), Some(color), "{point:?}");
+                assert_eq!(display.get_pixel(point + Point::new(1, 1)), Some(color));
+            }
+        }
+        assert_eq!(display.get_pixel(Point::new(1, 1)), Some(BinaryColor::On));
+        assert_eq!(display.get_pixel(Point::zero()), None);
+
+        let mut inverted = MockDisplay::new();
+        qr.with_inverted(true).draw(&mut inverted).unwrap();
+        assert_eq!(inverted.get_pixel(Point::new(3, 3)), Some(BinaryColor::On));
+        assert_eq!(inverted.get_pixel(Point::new(2, 2)), Some(BinaryColor::Off));
+    }
+}
@@ -0,0 +1,503 @@
+//! Host-side display for rendering screens without a badger2040
+//!
+//! Enabled with the `sim` feature. [`SimDisplay`] has the geometry of the
+//! e-ink display, anything drawn on it can be saved as PNG or PBM and compared
+//! against golden files in tests:
+//!
+//! ```ignore
+//! let mut display = SimDisplay::new();
+//! badges::ZOKOL.draw(&mut display)?;
+//! display.assert_golden("tests/golden/zokol.png");
+//! ```
+//!
+//! [`SimPanel`] adds the refreshes of a [`Panel`], to check what a
+//! [`RefreshManager`](crate::display::RefreshManager) or
+//! [`Pages`](crate::pages::Pages) refreshes with which waveform.
+//!
+//! Run the tests on the host with
+//! `cargo test --lib --features sim --target x86_64-unknown-linux-gnu`, set
+//! `UPDATE_GOLDEN=1` to write the current output as the new golden files.
+
+use core::convert::Infallible;
+use core::fmt;
+use std::fs;
+use std::path::Path;
+use std::string::String;
+use std::vec::Vec;
+use std::{env, format, io};
+
+use embedded_graphics::{pixelcolor::BinaryColor, prelude::*, primitives::Rectangle};
+use uc8151::{SpiDataError, HEIGHT, LUT, WIDTH};
+
+use crate::display::Panel;
+
+/// Errors loading, saving and comparing screens
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Png(&'static str),
+    Pbm(&'static str),
+    /// The file isn't 296x128 pixels
+    Size(Size),
+    /// The file extension isn't `png` or `pbm`
+    Format,
+    /// The golden file doesn't exist, run with `UPDATE_GOLDEN=1` to write it
+    MissingGolden,
+    /// This many pixels differ from the golden file, the first one at `first`
+    Mismatch {
+        pixels: usize,
+        first: Point,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "{}", err),
+            Error::Png(err) => write!(f, "invalid PNG: {}", err),
+            Error::Pbm(err) => write!(f, "invalid PBM: {}", err),
+            Error::Size(size) => write!(f, "expected {}x{}, got {}", WIDTH, HEIGHT, size),
+            Error::Format => write!(f, "only .png and .pbm files are supported"),
+            Error::MissingGolden => write!(f, "no golden file, set UPDATE_GOLDEN=1 to write it"),
+            Error::Mismatch { pixels, first } => {
+                write!(f, "{} pixels differ, the first at {}", pixels, first)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+/// A framebuffer with the size of the e-ink display
+///
+/// Starts out white like a freshly set up display.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SimDisplay {
+    pixels: Vec<BinaryColor>,
+}
+
+impl SimDisplay {
+    pub fn new() -> Self {
+        Self {
+            pixels: std::vec![BinaryColor::On; (WIDTH * HEIGHT) as usize],
+        }
+    }
+
+    /// Color of the pixel at `point`, white outside the screen
+    pub fn pixel(&self, point: Point) -> BinaryColor {
+        match self.index(point) {
+            Some(index) => self.pixels[index],
+            None => BinaryColor::On,
+        }
+    }
+
+    fn index(&self, point: Point) -> Option<usize> {
+        let inside = self.bounding_box().contains(point);
+        inside.then(|| (point.y as u32 * WIDTH + point.x as u32) as usize)
+    }
+
+    /// Rows packed 8 pixels per byte, most significant bit first, white is 1
+    fn packed(&self, white: bool) -> Vec<u8> {
+        let mut data = Vec::new();
+        for row in self.pixels.chunks(WIDTH as usize) {
+            for chunk in row.chunks(8) {
+                let byte = chunk.iter().enumerate().fold(0u8, |byte, (i, &color)| {
+                    byte | (((color == BinaryColor::On) == white) as u8) << (7 - i)
+                });
+                data.push(byte);
+            }
+        }
+        data
+    }
+
+    /// Encode as a 1 bit greyscale PNG
+    pub fn to_png(&self) -> Vec<u8> {
+        let mut png = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png, WIDTH, HEIGHT);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::One);
+        // Writing to a Vec doesn't fail
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(&self.packed(true)).unwrap();
+        writer.finish().unwrap();
+        png
+    }
+
+    /// Decode a PNG, anything at least half bright is white
+    pub fn from_png(data: &[u8]) -> Result<Self, Error> {
+        let mut decoder = png::Decoder::new(data);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder
+            .read_info()
+            .map_err(|_| Error::Png("decoding failed"))?;
+        let mut buffer = std::vec![0; reader.output_buffer_size()];
+        let info = reader
+            .next_frame(&mut buffer)
+            .map_err(|_| Error::Png("decoding failed"))?;
+        if (info.width, info.height) != (WIDTH, HEIGHT) {
+            return Err(Error::Size(Size::new(info.width, info.height)));
+        }
+
+        let samples = info.color_type.samples();
+        let pixels = buffer[..info.buffer_size()]
+            .chunks(samples)
+            .map(|pixel| {
+                // Ignore alpha, average the color channels
+                let channels = if samples == 2 || samples == 4 {
+                    &pixel[..samples - 1]
+                } else {
+                    pixel
+                };
+                let sum: u32 = channels.iter().map(|&c| c as u32).sum();
+                BinaryColor::from(sum / channels.len() as u32 >= 128)
+            })
+            .collect();
+        Ok(Self { pixels })
+    }
+
+    /// Encode as a binary PBM, black is 1
+    pub fn to_pbm(&self) -> Vec<u8> {
+        let mut pbm = format!("P4\n{} {}\n", WIDTH, HEIGHT).into_bytes();
+        pbm.extend(self.packed(false));
+        pbm
+    }
+
+    /// Decode a binary (`P4`) PBM
+    pub fn from_pbm(data: &[u8]) -> Result<Self, Error> {
+        // Magic, width and height separated by whitespace, then a single
+        // whitespace before the pixel data
+        let mut fields = [""; 3];
+        let mut rest = data;
+        for field in fields.iter_mut() {
+            let start = rest
+                .iter()
+                .position(|b| !b.is_ascii_whitespace())
+                .ok_or(Error::Pbm("truncated header"))?;
+            rest = &rest[start..];
+            let end = rest
+                .iter()
+                .position(|b| b.is_ascii_whitespace())
+                .ok_or(Error::Pbm("truncated header"))?;
+            *field = core::str::from_utf8(&rest[..end]).map_err(|_| Error::Pbm("bad header"))?;
+            rest = &rest[end + 1..];
+        }
+
+        if fields[0] != "P4" {
+            return Err(Error::Pbm("only binary P4 files are supported"));
+        }
+        let size: Result<Vec<u32>, _> = fields[1..].iter().map(|f| f.parse()).collect();
+        let size = size.map_err(|_| Error::Pbm("bad size"))?;
+        if size != [WIDTH, HEIGHT] {
+            return Err(Error::Size(Size::new(size[0], size[1])));
+        }
+
+        let row_bytes = WIDTH.div_ceil(8) as usize;
+        if rest.len() < row_bytes * HEIGHT as usize {
+            return Err(Error::Pbm("truncated pixel data"));
+        }
+        let mut display = Self::new();
+        for y in 0..HEIGHT as usize {
+            for x in 0..WIDTH as usize {
+                let black = rest[y * row_bytes + x / 8] & (0x80 >> (x % 8)) != 0;
+                display.pixels[y * WIDTH as usize + x] = BinaryColor::from(!black);
+            }
+        }
+        Ok(display)
+    }
+
+    /// Save as PNG or PBM, depending on the extension of `path`
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let path = path.as_ref();
+        let data = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("png") => self.to_png(),
+            Some("pbm") => self.to_pbm(),
+            _ => return Err(Error::Format),
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        Ok(fs::write(path, data)?)
+    }
+
+    /// Load a PNG or PBM, depending on the extension of `path`
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let data = fs::read(path)?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("png") => Self::from_png(&data),
+            Some("pbm") => Self::from_pbm(&data),
+            _ => Err(Error::Format),
+        }
+    }
+
+    /// Number of pixels that differ and the first of them
+    pub fn diff(&self, other: &Self) -> Option<(usize, Point)> {
+        let mut differing = self
+            .pixels
+            .iter()
+            .zip(&other.pixels)
+            .enumerate()
+            .filter(|(_, (a, b))| a != b)
+            .map(|(i, _)| i);
+        let first = differing.next()?;
+        let first = Point::new((first as u32 % WIDTH) as i32, (first as u32 / WIDTH) as i32);
+        Some((differing.count() + 1, first))
+    }
+
+    /// Compare against a golden PNG or PBM file
+    ///
+    /// When the `UPDATE_GOLDEN` environment variable is set the golden file
+    /// is written instead, a missing golden file is an error otherwise. On a
+    /// mismatch the actual screen is saved next to it, `zokol.png` becomes
+    /// `zokol.actual.png`.
+    pub fn compare_golden(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        self.check_golden(path.as_ref(), env::var_os("UPDATE_GOLDEN").is_some())
+    }
+
+    fn check_golden(&self, path: &Path, update: bool) -> Result<(), Error> {
+        if update {
+            return self.save(path);
+        }
+        if !path.exists() {
+            return Err(Error::MissingGolden);
+        }
+
+        let golden = Self::load(path)?;
+        match self.diff(&golden) {
+            None => Ok(()),
+            Some((pixels, first)) => {
+                let ext = path.extension().unwrap_or_default().to_string_lossy();
+                self.save(path.with_extension(format!("actual.{}", ext)))?;
+                Err(Error::Mismatch { pixels, first })
+            }
+        }
+    }
+
+    /// [`SimDisplay::compare_golden`] that panics on any difference
+    #[track_caller]
+    pub fn assert_golden(&self, path: impl AsRef<Path>) {
+        let path = path.as_ref();
+        if let Err(err) = self.compare_golden(path) {
+            panic!("{}: {}", path.display(), err);
+        }
+    }
+}
+
+impl Default for SimDisplay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Draws the screen as text, `#` is black
+impl fmt::Debug for SimDisplay {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut row = String::with_capacity(WIDTH as usize);
+        for pixels in self.pixels.chunks(WIDTH as usize) {
+            row.clear();
+            row.extend(pixels.iter().map(|&color| match color {
+                BinaryColor::Off => '#',
+                BinaryColor::On => '.',
+            }));
+            writeln!(f, "{}", row.trim_end_matches('.'))?;
+        }
+        Ok(())
+    }
+}
+
+impl OriginDimensions for SimDisplay {
+    fn size(&self) -> Size {
+        Size::new(WIDTH, HEIGHT)
+    }
+}
+
+impl DrawTarget for SimDisplay {
+    type Color = BinaryColor;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            if let Some(index) = self.index(point) {
+                self.pixels[index] = color;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A refresh asked of a [`SimPanel`]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct SimRefresh {
+    pub lut: LUT,
+    /// The whole screen for full refreshes
+    pub area: Rectangle,
+}
+
+impl fmt::Debug for SimRefresh {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let lut = match self.lut {
+            LUT::Internal => "Internal",
+            LUT::Normal => "Normal",
+            LUT::Medium => "Medium",
+            LUT::Fast => "Fast",
+            LUT::Ultrafast => "Ultrafast",
+        };
+        f.debug_struct("SimRefresh")
+            .field("lut", &lut)
+            .field("area", &self.area)
+            .finish()
+    }
+}
+
+/// A [`SimDisplay`] that records its refreshes
+///
+/// What is on `display` is what has been drawn, refreshed or not.
+pub struct SimPanel {
+    pub display: SimDisplay,
+    /// Waveform of the next refresh that isn't cleaning
+    pub lut: LUT,
+    pub refreshes: Vec<SimRefresh>,
+}
+
+impl SimPanel {
+    pub fn new(lut: LUT) -> Self {
+        Self {
+            display: SimDisplay::new(),
+            lut,
+            refreshes: Vec::new(),
+        }
+    }
+}
+
+impl OriginDimensions for SimPanel {
+    fn size(&self) -> Size {
+        self.display.size()
+    }
+}
+
+impl DrawTarget for SimPanel {
+    type Color = BinaryColor;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        self.display.draw_iter(pixels)
+    }
+}
+
+impl Panel for SimPanel {
+    fn refresh(&mut self, clean: bool) -> Result<(), SpiDataError> {
+        let lut = if clean { LUT::Normal } else { self.lut };
+        let area = self.bounding_box();
+        self.refreshes.push(SimRefresh { lut, area });
+        Ok(())
+    }
+
+    fn refresh_area(&mut self, area: Rectangle) -> Result<(), SpiDataError> {
+        let lut = self.lut;
+        self.refreshes.push(SimRefresh { lut, area });
+        Ok(())
+    }
+
+    fn set_lut(&mut self, lut: LUT) -> Result<(), SpiDataError> {
+        self.lut = lut;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_graphics::primitives::{PrimitiveStyle, Triangle};
+    use std::path::PathBuf;
+
+    fn screen() -> SimDisplay {
+        let mut display = SimDisplay::new();
+        Triangle::new(Point::new(3, 5), Point::new(290, 20), Point::new(40, 127))
+            .into_styled(PrimitiveStyle::with_fill(BinaryColor::Off))
+            .draw(&mut display)
+            .unwrap();
+        display
+    }
+
+    /// An empty directory for the files of `test`
+    fn scratch(test: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("badger2040-sim-{}", test));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn png_and_pbm_round_trip() {
+        let display = screen();
+        assert_eq!(SimDisplay::from_png(&display.to_png()).unwrap(), display);
+        assert_eq!(SimDisplay::from_pbm(&display.to_pbm()).unwrap(), display);
+        assert!(matches!(
+            SimDisplay::from_pbm(b"P4\n8 8\n\0\0\0\0\0\0\0\0"),
+            Err(Error::Size(_))
+        ));
+    }
+
+    #[test]
+    fn diff_counts_pixels() {
+        let mut other = screen();
+        assert_eq!(screen().diff(&other), None);
+        Pixel(Point::new(200, 100), BinaryColor::Off)
+            .draw(&mut other)
+            .unwrap();
+        Pixel(Point::new(0, 0), BinaryColor::Off)
+            .draw(&mut other)
+            .unwrap();
+        assert_eq!(screen().diff(&other), Some((2, Point::zero())));
+    }
+
+    #[test]
+    fn missing_golden_fails() {
+        let path = scratch("missing").join("screen.png");
+        assert!(matches!(
+            screen().check_golden(&path, false),
+            Err(Error::MissingGolden)
+        ));
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn update_writes_golden() {
+        let path = scratch("update").join("screen.pbm");
+        screen().check_golden(&path, true).unwrap();
+        assert_eq!(SimDisplay::load(&path).unwrap(), screen());
+        screen().check_golden(&path, false).unwrap();
+    }
+
+    #[test]
+    fn mismatch_saves_actual() {
+        let dir = scratch("mismatch");
+        let path = dir.join("screen.png");
+        SimDisplay::new().save(&path).unwrap();
+        let (pixels, first) = screen().diff(&SimDisplay::new()).unwrap();
+        match screen().check_golden(&path, false) {
+            Err(Error::Mismatch {
+                pixels: p,
+                first: f,
+            }) => assert_eq!((p, f), (pixels, first)),
+            other => panic!("expected a mismatch, got {:?}", other),
+        }
+        assert_eq!(
+            SimDisplay::load(dir.join("screen.actual.png")).unwrap(),
+            screen()
+        );
+        assert_eq!(SimDisplay::load(&path).unwrap(), SimDisplay::new());
+    }
+}